@@ -35,6 +35,218 @@ fn silent_cmd(program: &str, args: &[&str]) -> std::io::Result<std::process::Out
     Command::new(program).args(args).output()
 }
 
+/// Program and arguments for running a tool. On Windows tools go through `cmd /C`
+/// so `.cmd`/`.bat` shims (npm, bd installed via npm, ...) resolve like in a shell.
+fn tool_command(windows: bool, program: &str, args: &[&str]) -> (String, Vec<String>) {
+    if windows {
+        let mut cmd_args = vec!["/C".to_string(), program.to_string()];
+        cmd_args.extend(args.iter().map(|a| a.to_string()));
+        ("cmd".to_string(), cmd_args)
+    } else {
+        (program.to_string(), args.iter().map(|a| a.to_string()).collect())
+    }
+}
+
+/// Program and arguments for looking a tool up on the PATH
+fn tool_lookup_command(windows: bool, name: &str) -> (String, Vec<String>) {
+    if windows {
+        tool_command(true, "where", &[name])
+    } else {
+        ("which".to_string(), vec![name.to_string()])
+    }
+}
+
+fn run_silent(command: (String, Vec<String>)) -> std::io::Result<std::process::Output> {
+    let (program, args) = command;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    silent_cmd(&program, &args)
+}
+
+/// Run a tool silently; fails with its stderr when it exits unsuccessfully
+fn run_tool(program: &str, args: &[&str]) -> Result<std::process::Output, String> {
+    let output = run_silent(tool_command(cfg!(windows), program, args))
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(if stderr.is_empty() {
+            format!("{} exited with {}", program, output.status)
+        } else {
+            stderr
+        });
+    }
+    Ok(output)
+}
+
+/// First PATH match for a tool, if any
+fn tool_path(name: &str) -> Option<String> {
+    let output = run_silent(tool_lookup_command(cfg!(windows), name)).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().next().map(|l| l.trim().to_string()).filter(|l| !l.is_empty())
+}
+
+fn tool_installed(name: &str) -> bool {
+    tool_path(name).is_some()
+}
+
+/// Spawnable, silent Command for a tool (see tool_command)
+fn tool_process(program: &str, args: &[&str]) -> Command {
+    let (program, args) = tool_command(cfg!(windows), program, args);
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+/// Streamed as `install-progress` while an install runs. The final event for an
+/// install has `done: true` with `success` and a summary `message`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallProgress {
+    install_id: String,
+    tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<String>,
+    done: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    success: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+/// Run one install step, emitting each stdout/stderr line. Errors carry the stderr tail.
+fn run_install_step(app: &tauri::AppHandle, install_id: &str, tool: &str, program: &str, args: &[&str]) -> Result<(), String> {
+    let (app, install_id, tool) = (app.clone(), install_id.to_string(), tool.to_string());
+    let emit_line = move |line: String| {
+        let _ = app.emit("install-progress", InstallProgress {
+            install_id: install_id.clone(),
+            tool: tool.clone(),
+            line: Some(line),
+            done: false,
+            success: None,
+            message: None,
+        });
+    };
+    emit_line(format!("$ {} {}", program, args.join(" ")));
+    run_streamed(tool_process(program, args), program, None, emit_line)
+}
+
+/// Lines of combined output included in a run_streamed timeout error
+const STREAMED_TIMEOUT_LOG_LINES: usize = 20;
+
+/// Run a process, passing each stdout/stderr line to `on_line`. Errors carry the stderr
+/// tail. With a `timeout` the process is killed once it runs that long, and the error
+/// carries the last lines it printed.
+fn run_streamed<F>(mut command: Command, program: &str, timeout: Option<std::time::Duration>, on_line: F) -> Result<(), String>
+where
+    F: Fn(String) + Clone + Send + 'static,
+{
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    let log = std::sync::Arc::new(Mutex::new(std::collections::VecDeque::new()));
+    let reader = |pipe: Option<Box<dyn std::io::Read + Send>>| {
+        let on_line = on_line.clone();
+        let log = log.clone();
+        std::thread::spawn(move || {
+            let mut tail = std::collections::VecDeque::new();
+            for line in pipe.map(BufReader::new).into_iter().flat_map(|r| r.lines()).map_while(Result::ok) {
+                on_line(line.clone());
+                let mut log = log.lock().unwrap();
+                log.push_back(line.clone());
+                if log.len() > STREAMED_TIMEOUT_LOG_LINES {
+                    log.pop_front();
+                }
+                tail.push_back(line);
+                if tail.len() > 10 {
+                    tail.pop_front();
+                }
+            }
+            Vec::from(tail).join("\n")
+        })
+    };
+    let stdout_thread = reader(child.stdout.take().map(|p| Box::new(p) as Box<dyn std::io::Read + Send>));
+    let stderr_thread = reader(child.stderr.take().map(|p| Box::new(p) as Box<dyn std::io::Read + Send>));
+
+    let deadline = timeout.map(|t| std::time::Instant::now() + t);
+    let status = loop {
+        let Some(deadline) = deadline else {
+            break child.wait().map_err(|e| format!("Failed to wait for {}: {}", program, e))?;
+        };
+        if let Some(status) = child.try_wait().map_err(|e| format!("Failed to wait for {}: {}", program, e))? {
+            break status;
+        }
+        if std::time::Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            // Readers are not joined: grandchildren may still hold the pipes open
+            let partial = Vec::from(log.lock().unwrap().clone()).join("\n");
+            return Err(format!(
+                "{} timed out after {}s and was stopped. Last output:\n{}",
+                program,
+                timeout.unwrap_or_default().as_secs(),
+                partial
+            ));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    };
+    let _ = stdout_thread.join();
+    let stderr_tail = stderr_thread.join().unwrap_or_default();
+    if status.success() {
+        Ok(())
+    } else if stderr_tail.is_empty() {
+        Err(format!("{} exited with {}", program, status))
+    } else {
+        Err(stderr_tail)
+    }
+}
+
+/// Start an install in the background and return its id right away. Steps are
+/// tried in order until one succeeds; `on_success` runs before the final event.
+fn spawn_install(
+    app: tauri::AppHandle,
+    tool: &str,
+    steps: Vec<(&'static str, Vec<&'static str>)>,
+    on_success: fn(),
+) -> String {
+    let install_id = uuid::Uuid::new_v4().to_string();
+    let id = install_id.clone();
+    let tool = tool.to_string();
+
+    std::thread::spawn(move || {
+        let mut result = Err("No install method available".to_string());
+        for (program, args) in &steps {
+            result = run_install_step(&app, &id, &tool, program, args);
+            if result.is_ok() {
+                break;
+            }
+        }
+        if result.is_ok() {
+            on_success();
+        }
+        println!("[Install] {} finished: {:?}", tool, result);
+        let _ = app.emit("install-progress", InstallProgress {
+            install_id: id,
+            tool: tool.clone(),
+            line: None,
+            done: true,
+            success: Some(result.is_ok()),
+            message: Some(match result {
+                Ok(()) => format!("{} installed successfully", tool),
+                Err(e) => format!("Failed to install {}: {}", tool, e),
+            }),
+        });
+    });
+
+    install_id
+}
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -49,6 +261,18 @@ pub struct FileEntry {
     pub children: Option<Vec<FileEntry>>,
 }
 
+/// Metadata for a single file, for the editor's details view
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInfo {
+    pub size: u64,
+    /// Last modification time in seconds since the Unix epoch
+    pub modified: u64,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub readonly: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSettings {
@@ -59,6 +283,30 @@ pub struct AppSettings {
     pub godot_mcp_installed: Option<bool>,
     pub auto_connect: Option<bool>,
     pub last_project_path: Option<String>,
+    /// Explicit .env file checked before the default locations
+    pub env_path: Option<String>,
+    /// Unix timestamp (seconds) when the OpenRouter key expires, if it was issued with a lifetime
+    pub openrouter_key_expires_at: Option<i64>,
+    /// Goose provider for the agent (openrouter, ollama, openai, ...), defaults to openrouter
+    pub agent_provider: Option<String>,
+    /// Base URL for a local or OpenAI-compatible provider endpoint
+    pub agent_base_url: Option<String>,
+    /// Port the web preview is served on, kept so preview URLs stay stable across runs
+    pub preview_port: Option<u16>,
+    /// Longest edge, in pixels, that frames are downscaled to before Gemini analysis (default 768)
+    pub frame_max_size: Option<u32>,
+    /// Mirror to download assets from instead of the public R2 bucket
+    pub asset_base_url: Option<String>,
+    /// Use assets bundled with the app instead of downloading them
+    pub offline_mode: Option<bool>,
+    /// Commit the project (when it is a git repo) before each agent turn
+    pub auto_commit: Option<bool>,
+    /// Seconds a Godot export may run before it is stopped (default 300)
+    pub export_timeout_secs: Option<u64>,
+    /// Web builds larger than this many MB trigger an `export-size-warning` (default 50)
+    pub export_size_warning_mb: Option<u64>,
+    /// Journal each agent turn's file changes under .tav/history so it can be undone
+    pub agent_history: Option<bool>,
 }
 
 // ============================================================================
@@ -92,6 +340,93 @@ pub struct GameSession {
     pub project_path: String,
     pub scene_path: String,
     pub frame_count: u32,
+    pub log_tail: LogTail,
+    pub started_at: std::time::Instant,
+}
+
+impl GameSession {
+    /// Whether the Godot process is still alive; reaps it if it exited
+    pub fn is_running(&mut self) -> bool {
+        self.process.as_mut().is_some_and(|p| matches!(p.try_wait(), Ok(None)))
+    }
+}
+
+/// Maximum number of game.log lines kept in memory per session
+const LOG_TAIL_CAPACITY: usize = 2000;
+
+/// Incremental reader for a growing log file. Only bytes appended since the
+/// last read are loaded; the most recent lines are kept with their line numbers.
+#[derive(Default)]
+pub struct LogTail {
+    offset: u64,
+    first_line: usize,
+    lines: std::collections::VecDeque<String>,
+}
+
+impl LogTail {
+    /// Read any complete lines appended since the last call
+    fn read_new(&mut self, path: &Path) -> std::io::Result<()> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // Log was truncated or replaced by a new run
+            *self = LogTail::default();
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+
+        // Leave a trailing partial line for the next read
+        let Some(end) = buf.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        self.offset += end as u64 + 1;
+        for line in String::from_utf8_lossy(&buf[..end]).lines() {
+            if self.lines.len() == LOG_TAIL_CAPACITY {
+                self.lines.pop_front();
+                self.first_line += 1;
+            }
+            self.lines.push_back(line.trim_end_matches('\r').to_string());
+        }
+        Ok(())
+    }
+
+    /// Total number of lines read so far, i.e. the next line number
+    fn next_line(&self) -> usize {
+        self.first_line + self.lines.len()
+    }
+
+    /// Numbered lines from `since_line` onward, optionally filtered to a `[LEVEL]` tag, limited to the last `max_lines`
+    fn query(&self, since_line: usize, level_filter: Option<&str>, max_lines: usize) -> Vec<GameLogLine> {
+        let tag = level_filter.map(|level| format!("[{}]", level.trim().to_uppercase()));
+        let matches: Vec<GameLogLine> = self.lines.iter()
+            .enumerate()
+            .map(|(i, text)| (self.first_line + i, text))
+            .filter(|(line, _)| *line >= since_line)
+            .filter(|(_, text)| tag.as_ref().is_none_or(|tag| text.to_uppercase().contains(tag.as_str())))
+            .map(|(line, text)| GameLogLine { line, text: text.clone() })
+            .collect();
+        let skip = matches.len().saturating_sub(max_lines);
+        matches.into_iter().skip(skip).collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameLogLine {
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameLogs {
+    pub lines: Vec<GameLogLine>,
+    /// Pass back as `since_line` to only receive newer lines
+    pub next_line: usize,
 }
 
 #[derive(Clone, Serialize)]
@@ -101,11 +436,30 @@ pub struct AgentEvent {
     pub content: String,
     pub tool_name: Option<String>,
     pub tool_args: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub turn_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancelled: Option<bool>,
+}
+
+/// An agent turn from the start of send_agent_message until Goose exits
+pub enum AgentTurn {
+    /// Preparing (auto-commit, Beads, history snapshot); Goose not started yet
+    Pending,
+    /// Stopped before Goose started; run_goose won't spawn it
+    Cancelled,
+    Running(std::process::Child),
 }
 
 pub struct AppState {
     settings: Mutex<AppSettings>,
     game_sessions: Mutex<std::collections::HashMap<String, GameSession>>,
+    /// Agent turns in progress, keyed by turn id
+    agent_turns: Mutex<std::collections::HashMap<String, AgentTurn>>,
+    /// Last detect_all_tools result and when it was taken
+    tool_status: Mutex<Option<(std::time::Instant, ToolStatus)>>,
+    /// Projects with a web export currently running
+    exporting: Mutex<std::collections::HashSet<String>>,
 }
 
 impl Default for AppState {
@@ -113,6 +467,9 @@ impl Default for AppState {
         Self {
             settings: Mutex::new(AppSettings::default()),
             game_sessions: Mutex::new(std::collections::HashMap::new()),
+            agent_turns: Mutex::new(std::collections::HashMap::new()),
+            tool_status: Mutex::new(None),
+            exporting: Mutex::new(std::collections::HashSet::new()),
         }
     }
 }
@@ -125,6 +482,51 @@ impl Default for AppState {
 
 const R2_BASE_URL: &str = "https://pub-b3ceaf5076804d56bc32fe9d83e9a3a9.r2.dev";
 
+/// Where assets are downloaded from: `TAV_ASSET_BASE_URL` (for CI), then the
+/// `asset_base_url` setting, then the public R2 bucket
+fn asset_base_url(settings: &AppSettings) -> String {
+    resolve_asset_base_url(std::env::var("TAV_ASSET_BASE_URL").ok(), settings)
+}
+
+/// asset_base_url with the environment override passed in
+fn resolve_asset_base_url(env_override: Option<String>, settings: &AppSettings) -> String {
+    env_override
+        .filter(|url| !url.trim().is_empty())
+        .or_else(|| settings.asset_base_url.clone())
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| R2_BASE_URL.to_string())
+}
+
+/// First `<root>/<asset_name>` file among `roots`; names that would escape the root are rejected
+fn find_bundled_asset(roots: &[PathBuf], asset_name: &str) -> Option<PathBuf> {
+    let name = Path::new(asset_name);
+    if !name.components().all(|c| matches!(c, std::path::Component::Normal(_))) {
+        return None;
+    }
+    roots.iter().map(|root| root.join(name)).find(|path| path.is_file())
+}
+
+/// Asset shipped in the app's `assets/` resources (or src-tauri/assets in dev builds)
+fn bundled_asset_path(app: &tauri::AppHandle, asset_name: &str) -> Option<PathBuf> {
+    let mut roots = Vec::new();
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        roots.push(resource_dir.join("assets"));
+    }
+    roots.push(Path::new(env!("CARGO_MANIFEST_DIR")).join("assets"));
+    find_bundled_asset(&roots, asset_name)
+}
+
+/// In offline mode, the bundled copy of an asset or an error; None when online
+fn offline_asset(app: &tauri::AppHandle, settings: &AppSettings, asset_name: &str) -> Option<Result<PathBuf, String>> {
+    if !settings.offline_mode.unwrap_or(false) {
+        return None;
+    }
+    Some(bundled_asset_path(app, asset_name).ok_or_else(|| {
+        format!("Offline mode is on and '{}' is not bundled with the app", asset_name)
+    }))
+}
+
 #[derive(Debug, Serialize, Clone)]
 struct DownloadProgress {
     asset: String,
@@ -138,11 +540,13 @@ async fn download_asset(
     asset_name: String,
     destination: String,
     app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     use futures_util::StreamExt;
     use tokio::io::AsyncWriteExt;
     
-    let url = format!("{}/{}", R2_BASE_URL, asset_name);
+    let settings = state.settings.lock().unwrap().clone();
+    let url = format!("{}/{}", asset_base_url(&settings), asset_name);
     let dest_path = Path::new(&destination);
     
     if let Some(parent) = dest_path.parent() {
@@ -150,6 +554,14 @@ async fn download_asset(
             .map_err(|e| format!("Failed to create directory: {}", e))?;
     }
     
+    if let Some(bundled) = offline_asset(&app, &settings, &asset_name) {
+        let bundled = bundled?;
+        println!("[download_asset] Offline, copying bundled: {}", bundled.display());
+        fs::copy(&bundled, dest_path)
+            .map_err(|e| format!("Failed to copy bundled asset: {}", e))?;
+        return Ok(destination);
+    }
+    
     println!("[download_asset] Downloading: {}", url);
     
     let client = reqwest::Client::builder()
@@ -203,16 +615,26 @@ async fn download_and_extract_asset(
     asset_name: String,
     destination_dir: String,
     app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     use futures_util::StreamExt;
     use tokio::io::AsyncWriteExt;
     
-    let url = format!("{}/{}", R2_BASE_URL, asset_name);
+    let settings = state.settings.lock().unwrap().clone();
+    let url = format!("{}/{}", asset_base_url(&settings), asset_name);
     let dest_dir = Path::new(&destination_dir);
     
     fs::create_dir_all(&dest_dir)
         .map_err(|e| format!("Failed to create directory: {}", e))?;
     
+    if let Some(bundled) = offline_asset(&app, &settings, &asset_name) {
+        let bundled = bundled?;
+        println!("[download_and_extract] Offline, extracting bundled: {}", bundled.display());
+        extract_zip_safe(&bundled, dest_dir, |_, _| {})
+            .map_err(|e| format!("Failed to extract bundled asset: {}", e))?;
+        return Ok(destination_dir);
+    }
+    
     println!("[download_and_extract] Downloading: {}", url);
     
     let client = reqwest::Client::builder()
@@ -269,31 +691,42 @@ async fn download_and_extract_asset(
         .map_err(|e| format!("Temp file not accessible: {}", e))?;
     println!("[download_and_extract] Temp file size: {} bytes", temp_meta.len());
     
-    let file = fs::File::open(&temp_path)
+    let extracted = extract_zip_safe(&temp_path, dest_dir, |_, _| {});
+    
+    // Clean up temp file
+    let _ = fs::remove_file(&temp_path);
+    let extracted_count = extracted?;
+    
+    println!("[download_and_extract] Complete: {} files extracted to {}", extracted_count, destination_dir);
+    Ok(destination_dir)
+}
+
+/// Extract a zip under `dest_dir`, skipping entries whose names would escape it
+/// (enclosed_name prevents path traversal). `on_entry(done, total)` runs after
+/// each entry. Returns the number of files written.
+fn extract_zip_safe(zip_path: &Path, dest_dir: &Path, mut on_entry: impl FnMut(usize, usize)) -> Result<usize, String> {
+    let file = fs::File::open(zip_path)
         .map_err(|e| format!("Failed to open zip: {}", e))?;
     let mut archive = zip::ZipArchive::new(file)
         .map_err(|e| format!("Failed to read zip (may be corrupt or wrong format): {}", e))?;
     
-    println!("[download_and_extract] Zip contains {} entries", archive.len());
+    println!("[Zip] {} contains {} entries", zip_path.display(), archive.len());
     
+    let total = archive.len();
     let mut extracted_count = 0;
-    for i in 0..archive.len() {
+    for i in 0..total {
         let mut file = archive.by_index(i)
             .map_err(|e| format!("Failed to read zip entry {}: {}", i, e))?;
         
-        // Use enclosed_name for safe path extraction (prevents path traversal)
         let outpath = match file.enclosed_name() {
             Some(path) => dest_dir.join(path),
             None => {
-                println!("[download_and_extract] Skipping unsafe entry: {}", file.name());
+                println!("[Zip] Skipping unsafe entry: {}", file.name());
                 continue;
             }
         };
         
-        println!("[download_and_extract] Entry {}: {} -> {}", i, file.name(), outpath.display());
-        
         if file.is_dir() {
-            println!("[download_and_extract] Creating dir: {}", outpath.display());
             fs::create_dir_all(&outpath)
                 .map_err(|e| format!("Failed to create dir {}: {}", outpath.display(), e))?;
         } else {
@@ -301,21 +734,15 @@ async fn download_and_extract_asset(
                 fs::create_dir_all(parent)
                     .map_err(|e| format!("Failed to create parent dir {}: {}", parent.display(), e))?;
             }
-            println!("[download_and_extract] Writing file: {} ({} bytes compressed)", outpath.display(), file.compressed_size());
             let mut outfile = fs::File::create(&outpath)
                 .map_err(|e| format!("Failed to create file {}: {}", outpath.display(), e))?;
-            let bytes_written = std::io::copy(&mut file, &mut outfile)
+            std::io::copy(&mut file, &mut outfile)
                 .map_err(|e| format!("Failed to write file {}: {}", outpath.display(), e))?;
-            println!("[download_and_extract] Wrote {} bytes to {}", bytes_written, outpath.display());
             extracted_count += 1;
         }
+        on_entry(i + 1, total);
     }
-    
-    // Clean up temp file
-    let _ = fs::remove_file(&temp_path);
-    
-    println!("[download_and_extract] Complete: {} files extracted to {}", extracted_count, destination_dir);
-    Ok(destination_dir)
+    Ok(extracted_count)
 }
 
 #[tauri::command]
@@ -328,23 +755,71 @@ fn check_asset_exists(path: String) -> bool {
 async fn setup_3d_character(
     project_path: String,
     app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
-    use futures_util::StreamExt;
-    use tokio::io::AsyncWriteExt;
-    
     let characters_dir = Path::new(&project_path).join("assets").join("characters");
     let character_path = characters_dir.join("character.glb");
     
-    // Skip if character already exists
-    if character_path.exists() {
-        println!("[setup_3d_character] Character already exists: {}", character_path.display());
-        return Ok(character_path.to_string_lossy().to_string());
+    // Skip if a character model already exists
+    if let Some(existing) = find_character_model(&characters_dir) {
+        println!("[setup_3d_character] Character already exists: {}", existing.display());
+        return Ok(existing.to_string_lossy().to_string());
     }
     
     fs::create_dir_all(&characters_dir)
         .map_err(|e| format!("Failed to create characters directory: {}", e))?;
     
-    let url = format!("{}/quaternius-character.zip", R2_BASE_URL);
+    let settings = state.settings.lock().unwrap().clone();
+    let (archive_path, downloaded, total_size) = match offline_asset(&app, &settings, "quaternius-character.zip") {
+        Some(bundled) => {
+            let bundled = bundled?;
+            println!("[setup_3d_character] Offline, using bundled: {}", bundled.display());
+            let size = fs::metadata(&bundled).map(|m| m.len()).unwrap_or(0);
+            (bundled, size, size)
+        }
+        None => {
+            let url = format!("{}/quaternius-character.zip", asset_base_url(&settings));
+            download_character_zip(&url, &characters_dir, &app).await?
+        }
+    };
+    
+    // Extract zip
+    extract_zip_safe(&archive_path, &characters_dir, |_, _| {})
+        .map_err(|e| format!("Failed to extract character: {}", e))?;
+    
+    // Clean up the download (never the bundled copy)
+    if archive_path.starts_with(&characters_dir) {
+        let _ = fs::remove_file(&archive_path);
+    }
+    
+    // The archive may name or nest the model differently; templates expect character.glb
+    let model = find_character_model(&characters_dir)
+        .ok_or("Character archive did not contain a .glb or .gltf model")?;
+    let model = if model.extension().and_then(|e| e.to_str()) == Some("glb") && model != character_path {
+        fs::copy(&model, &character_path)
+            .map_err(|e| format!("Failed to copy {} to character.glb: {}", model.display(), e))?;
+        character_path
+    } else {
+        model
+    };
+    
+    let _ = app.emit("download-progress", DownloadProgress {
+        asset: "quaternius-character".to_string(),
+        downloaded,
+        total: total_size.max(downloaded),
+        percent: 100,
+    });
+    
+    println!("[setup_3d_character] Complete: {}", model.display());
+    Ok(model.to_string_lossy().to_string())
+}
+
+/// Stream the character archive into `characters_dir`, returning the temp path,
+/// bytes downloaded and the reported total size
+async fn download_character_zip(url: &str, characters_dir: &Path, app: &tauri::AppHandle) -> Result<(PathBuf, u64, u64), String> {
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    
     println!("[setup_3d_character] Downloading: {}", url);
     
     let client = reqwest::Client::builder()
@@ -352,7 +827,7 @@ async fn setup_3d_character(
         .build()
         .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
     
-    let response = client.get(&url).send().await
+    let response = client.get(url).send().await
         .map_err(|e| format!("Character download failed: {}", e))?;
     
     if !response.status().is_success() {
@@ -390,52 +865,79 @@ async fn setup_3d_character(
     }
     
     file.flush().await.map_err(|e| format!("Flush error: {}", e))?;
-    drop(file);
-    
-    // Extract zip
-    let file = fs::File::open(&temp_path)
-        .map_err(|e| format!("Failed to open zip: {}", e))?;
-    let mut archive = zip::ZipArchive::new(file)
-        .map_err(|e| format!("Failed to read character zip: {}", e))?;
-    
-    archive.extract(&characters_dir)
-        .map_err(|e| format!("Failed to extract character: {}", e))?;
-    
-    // Clean up
-    let _ = fs::remove_file(&temp_path);
-    
-    println!("[setup_3d_character] Complete: {}", character_path.display());
-    Ok(character_path.to_string_lossy().to_string())
+    Ok((temp_path, downloaded, total_size))
+}
+
+/// `character.glb` if present, otherwise the first .glb (then .gltf) found under the directory
+fn find_character_model(characters_dir: &Path) -> Option<PathBuf> {
+    let preferred = characters_dir.join("character.glb");
+    if preferred.exists() {
+        return Some(preferred);
+    }
+
+    let mut models = Vec::new();
+    let mut pending = vec![characters_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if matches!(path.extension().and_then(|e| e.to_str()), Some("glb" | "gltf")) {
+                models.push(path);
+            }
+        }
+    }
+    // .glb first, then by path for a stable pick
+    models.sort_by_key(|path| (path.extension().and_then(|e| e.to_str()) != Some("glb"), path.clone()));
+    models.into_iter().next()
 }
 
 // ============================================================================
 // Project Config
 // ============================================================================
 
-fn ensure_project_config(project_path: &str) -> Result<(), String> {
+/// Agent rule files written into every project, with their embedded content
+const PROJECT_RULE_FILES: [(&str, &str); 3] = [
+    ("RULES.md", GODOT_RULES),
+    ("CLAUDE.md", CLAUDE_MD),
+    (".cursorrules", GODOT_RULES),
+];
+
+/// What `ensure_project_config` did: paths are relative to the project root
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfigReport {
+    pub created: Vec<String>,
+    pub existing: Vec<String>,
+    /// True when the `.tav/config_done` marker short-circuited the check
+    pub skipped: bool,
+}
+
+fn ensure_project_config(project_path: &str) -> Result<ProjectConfigReport, String> {
     let project_dir = Path::new(project_path);
+    let mut report = ProjectConfigReport::default();
     
     let project_godot = project_dir.join("project.godot");
     if !project_godot.exists() {
-        return Ok(());
-    }
-    
-    let rules_path = project_dir.join("RULES.md");
-    if !rules_path.exists() {
-        fs::write(&rules_path, GODOT_RULES)
-            .map_err(|e| format!("Failed to create RULES.md: {}", e))?;
+        return Ok(report);
     }
     
-    let claude_md_path = project_dir.join("CLAUDE.md");
-    if !claude_md_path.exists() {
-        fs::write(&claude_md_path, CLAUDE_MD)
-            .map_err(|e| format!("Failed to create CLAUDE.md: {}", e))?;
+    // Already set up once - don't re-stat every path on each list_files call
+    let marker_path = project_dir.join(".tav/config_done");
+    if marker_path.exists() {
+        report.skipped = true;
+        return Ok(report);
     }
     
-    let cursorrules_path = project_dir.join(".cursorrules");
-    if !cursorrules_path.exists() {
-        fs::write(&cursorrules_path, GODOT_RULES)
-            .map_err(|e| format!("Failed to create .cursorrules: {}", e))?;
+    for (file, content) in PROJECT_RULE_FILES {
+        let file_path = project_dir.join(file);
+        if file_path.exists() {
+            report.existing.push(file.to_string());
+        } else {
+            fs::write(&file_path, content)
+                .map_err(|e| format!("Failed to create {}: {}", file, e))?;
+            report.created.push(file.to_string());
+        }
     }
     
     for dir in &[
@@ -448,18 +950,60 @@ fn ensure_project_config(project_path: &str) -> Result<(), String> {
         "src/core", "src/systems", "src/components", "src/states", "src/utilities"
     ] {
         let dir_path = project_dir.join(dir);
-        if !dir_path.exists() {
-            let _ = fs::create_dir_all(&dir_path);
+        if dir_path.exists() {
+            report.existing.push(dir.to_string());
+        } else if fs::create_dir_all(&dir_path).is_ok() {
+            report.created.push(dir.to_string());
         }
     }
     
-    Ok(())
+    if let Some(parent) = marker_path.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    fs::write(&marker_path, "").ok();
+    
+    Ok(report)
+}
+
+/// Rewrite the agent rule files from the current embedded versions.
+/// Existing files are backed up to `<file>.bak` unless `overwrite` is set.
+#[tauri::command]
+fn refresh_project_rules(project_path: String, overwrite: Option<bool>) -> Result<Vec<String>, String> {
+    let project_dir = Path::new(&project_path);
+    if !project_dir.join("project.godot").exists() {
+        return Err("project.godot not found".to_string());
+    }
+    
+    let mut refreshed = Vec::new();
+    for (file, content) in PROJECT_RULE_FILES {
+        let file_path = project_dir.join(file);
+        if let Ok(existing) = fs::read_to_string(&file_path) {
+            if existing == content {
+                continue;
+            }
+            if !overwrite.unwrap_or(false) {
+                fs::write(project_dir.join(format!("{}.bak", file)), existing)
+                    .map_err(|e| format!("Failed to back up {}: {}", file, e))?;
+            }
+        }
+        fs::write(&file_path, content)
+            .map_err(|e| format!("Failed to write {}: {}", file, e))?;
+        refreshed.push(file.to_string());
+    }
+    
+    println!("[Config] Refreshed rule files: {:?}", refreshed);
+    Ok(refreshed)
 }
 
 // ============================================================================
 // File Operations
 // ============================================================================
 
+/// Entries hidden from the file tree: dotfiles/dirs (.godot, .tav, .git) and build output
+fn is_ignored_entry(name: &str) -> bool {
+    name.starts_with('.') || name == "node_modules" || name == "target"
+}
+
 fn build_file_tree(path: &Path, depth: usize) -> Vec<FileEntry> {
     if depth > 3 {
         return vec![];
@@ -483,11 +1027,7 @@ fn build_file_tree(path: &Path, depth: usize) -> Vec<FileEntry> {
             let entry_path = entry.path();
             let name = entry.file_name().to_string_lossy().to_string();
 
-            if name.starts_with('.')
-                || name == "node_modules"
-                || name == ".godot"
-                || name == "target"
-            {
+            if is_ignored_entry(&name) {
                 continue;
             }
 
@@ -532,6 +1072,144 @@ fn write_file(path: String, content: String) -> Result<(), String> {
     fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))
 }
 
+/// Binary-safe read for assets (.glb, .png, .pck) that aren't valid UTF-8
+#[tauri::command]
+fn read_file_bytes(path: String) -> Result<Vec<u8>, String> {
+    fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))
+}
+
+#[tauri::command]
+fn write_file_bytes(path: String, data: Vec<u8>) -> Result<(), String> {
+    fs::write(&path, data).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+#[tauri::command]
+fn get_file_info(path: String) -> Result<FileInfo, String> {
+    let p = Path::new(&path);
+    // symlink_metadata succeeds for dangling links, which metadata() would report as missing
+    let link_meta = fs::symlink_metadata(p).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("Path does not exist: {}", path),
+        _ => format!("Failed to read metadata for '{}': {}", path, e),
+    })?;
+    let is_symlink = link_meta.file_type().is_symlink();
+    let meta = if is_symlink { fs::metadata(p).unwrap_or(link_meta) } else { link_meta };
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Ok(FileInfo {
+        size: meta.len(),
+        modified,
+        is_dir: meta.is_dir(),
+        is_symlink,
+        readonly: meta.permissions().readonly(),
+    })
+}
+
+/// Image formats Godot can load outside a project
+const THUMBNAIL_IMAGE_EXTS: &[&str] = &["png", "jpg", "jpeg", "webp", "bmp", "tga", "svg"];
+
+/// Nearest ancestor containing project.godot, falling back to the file's folder
+fn thumbnail_project_root(source: &Path) -> PathBuf {
+    let parent = source.parent().unwrap_or(Path::new("."));
+    parent
+        .ancestors()
+        .find(|dir| dir.join("project.godot").exists())
+        .unwrap_or(parent)
+        .to_path_buf()
+}
+
+/// Cache file prefix for a source path; entries are `<prefix><mtime>-<size>.png`
+fn thumbnail_cache_prefix(source: &Path) -> String {
+    format!("{}-", &content_hash(source.to_string_lossy().as_bytes())[..16])
+}
+
+/// Width and height from a PNG's IHDR chunk
+fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.len() < 24 || &data[..8] != b"\x89PNG\r\n\x1a\n" || &data[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(data[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(data[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+/// Base64 PNG preview of an image or glTF model, at most `max_size` pixels on
+/// its longest side. Generated by Godot and cached under `.tav/thumbnails/`,
+/// keyed by the source's mtime.
+#[tauri::command]
+fn generate_thumbnail(path: String, max_size: u32, state: tauri::State<AppState>) -> Result<String, String> {
+    let source = Path::new(&path);
+    let info = get_file_info(path.clone())?;
+    if info.is_dir {
+        return Err(format!("Cannot generate a thumbnail for a directory: {}", path));
+    }
+    let ext = source.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    let is_model = ext == "glb" || ext == "gltf";
+    if !is_model && !THUMBNAIL_IMAGE_EXTS.contains(&ext.as_str()) {
+        return Err(format!("Thumbnails are not supported for '{}'", path));
+    }
+    let max_size = max_size.clamp(16, 1024);
+
+    let cache_dir = thumbnail_project_root(source).join(".tav/thumbnails");
+    let prefix = thumbnail_cache_prefix(source);
+    let cache_path = cache_dir.join(format!("{}{}-{}.png", prefix, info.modified, max_size));
+    if let Ok(data) = fs::read(&cache_path) {
+        return Ok(base64::engine::general_purpose::STANDARD.encode(&data));
+    }
+
+    // Small PNGs are already thumbnails
+    if ext == "png" {
+        let data = fs::read(source).map_err(|e| format!("Failed to read file: {}", e))?;
+        if png_dimensions(&data).is_some_and(|(w, h)| w.max(h) <= max_size) {
+            return Ok(base64::engine::general_purpose::STANDARD.encode(&data));
+        }
+    }
+
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = saved_godot_cmd(saved).ok_or("Godot not found")?;
+
+    fs::create_dir_all(&cache_dir).map_err(|e| format!("Failed to create thumbnail cache: {}", e))?;
+    // Drop thumbnails of older versions of this file
+    if let Ok(entries) = fs::read_dir(&cache_dir) {
+        let current = format!("{}{}-", prefix, info.modified);
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with(&prefix) && !name.starts_with(&current) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+    let script_path = cache_dir.join("thumbnail.gd");
+    fs::write(&script_path, THUMBNAIL_GD).map_err(|e| format!("Failed to write thumbnail script: {}", e))?;
+
+    let script = script_path.to_string_lossy().to_string();
+    let output_path = cache_path.to_string_lossy().to_string();
+    let size_arg = max_size.to_string();
+    let mut args = vec![];
+    // Models render through a SubViewport, which the headless dummy renderer leaves blank
+    if is_model {
+        args.extend(["--resolution", "64x64"]);
+    } else {
+        args.push("--headless");
+    }
+    args.extend(["--script", &script, "--", &path, &output_path, &size_arg]);
+
+    println!("[Thumbnail] Generating {} ({}px)", path, max_size);
+    let output = silent_cmd(&godot_cmd, &args).map_err(|e| format!("Failed to run Godot: {}", e))?;
+    if !output.status.success() || !cache_path.exists() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("no output");
+        return Err(format!("Thumbnail generation failed for '{}': {}", path, detail.trim()));
+    }
+
+    let data = fs::read(&cache_path).map_err(|e| format!("Failed to read thumbnail: {}", e))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(&data))
+}
+
 #[tauri::command]
 fn delete_file(path: String) -> Result<(), String> {
     let p = Path::new(&path);
@@ -549,40 +1227,83 @@ fn delete_file(path: String) -> Result<(), String> {
 // Godot Detection - Check common install locations
 // ============================================================================
 
-/// Read a key from .env.local file - checks multiple locations
-fn read_env_file_key(project_path: &str, key: &str) -> Option<String> {
-    let locations = [
-        // Game project .env.local
-        Path::new(project_path).join(".env.local"),
-        // App root .env.local (dev mode)
-        PathBuf::from("c:/My Apps/colorwave/.env.local"),
-        // Current directory
-        PathBuf::from(".env.local"),
+/// Read a key from .env/.env.local files - checks an explicit path first, then
+/// the project, its parent, the working directory and the app config dir
+fn read_env_file_key(project_path: &str, key: &str, env_path: Option<&str>) -> Option<String> {
+    let mut locations: Vec<PathBuf> = Vec::new();
+    // Explicitly configured env file
+    if let Some(path) = env_path.filter(|p| !p.is_empty()) {
+        locations.push(PathBuf::from(path));
+    }
+    let mut dirs_to_search = vec![
+        // Game project
+        Path::new(project_path).to_path_buf(),
         // Parent of project (common layout)
-        Path::new(project_path).parent().map(|p| p.join(".env.local")).unwrap_or_default(),
+        Path::new(project_path).parent().map(|p| p.to_path_buf()).unwrap_or_default(),
+        // Current directory (dev mode)
+        PathBuf::from("."),
     ];
+    // App config dir (next to settings.json)
+    if let Some(config_dir) = dirs::config_dir() {
+        dirs_to_search.push(config_dir.join("kobold"));
+    }
+    for dir in dirs_to_search {
+        locations.push(dir.join(".env.local"));
+        locations.push(dir.join(".env"));
+    }
     
     for env_path in &locations {
         if let Ok(content) = fs::read_to_string(env_path) {
-            for line in content.lines() {
-                let line = line.trim();
-                if line.starts_with('#') || line.is_empty() {
-                    continue;
-                }
-                if let Some(value) = line.strip_prefix(&format!("{}=", key)) {
-                    println!("[ENV] Found {} in {:?}", key, env_path);
-                    return Some(value.trim().to_string());
-                }
+            if let Some(value) = content.lines().find_map(|line| parse_env_line(line, key)) {
+                println!("[ENV] Found {} in {:?}", key, env_path);
+                return Some(value);
             }
         }
     }
-    println!("[ENV] Key {} not found in any .env.local", key);
+    println!("[ENV] Key {} not found in any .env file", key);
     None
 }
 
-fn find_godot_path() -> Option<String> {
-    // First try PATH
-    let path_names = if cfg!(windows) {
+/// Parse `KEY=value`, `export KEY=value`, and quoted values from a single .env line
+fn parse_env_line(line: &str, key: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with('#') || line.is_empty() {
+        return None;
+    }
+    let line = line.strip_prefix("export ").map(|l| l.trim_start()).unwrap_or(line);
+    let (name, value) = line.split_once('=')?;
+    if name.trim() != key {
+        return None;
+    }
+    
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote) {
+            // Quoted values run to the closing quote; anything after it is ignored
+            return inner.find(quote).map(|end| inner[..end].to_string());
+        }
+    }
+    // Unquoted values may carry a trailing " # comment"
+    let value = value.split(" #").next().unwrap_or(value).trim();
+    Some(value.to_string())
+}
+
+fn find_godot_path() -> Option<String> {
+    find_godot_paths().into_iter().next()
+}
+
+/// Every Godot binary on the PATH or in the usual install locations, PATH first
+fn find_godot_paths() -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    let mut add = |path: String| {
+        let canonical = fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(&path));
+        if !found.iter().any(|p| fs::canonicalize(p).unwrap_or_else(|_| PathBuf::from(p)) == canonical) {
+            found.push(path);
+        }
+    };
+
+    // First try PATH
+    let path_names = if cfg!(windows) {
         vec!["godot", "godot.exe"]
     } else if cfg!(target_os = "macos") {
         vec!["godot", "godot4"]
@@ -591,21 +1312,9 @@ fn find_godot_path() -> Option<String> {
     };
 
     for name in &path_names {
-        let result = if cfg!(windows) {
-            silent_cmd("cmd", &["/C", "where", name])
-        } else {
-            Command::new("which").arg(name).output()
-        };
-        
-        if let Ok(output) = result {
-            if output.status.success() {
-                let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !path.is_empty() {
-                    let first_line = path.lines().next().unwrap_or(&path);
-                    if Path::new(first_line).exists() {
-                        return Some(first_line.to_string());
-                    }
-                }
+        if let Some(path) = tool_path(name) {
+            if Path::new(&path).exists() {
+                add(path);
             }
         }
     }
@@ -626,9 +1335,12 @@ fn find_godot_path() -> Option<String> {
         
         for path in common_paths {
             if Path::new(path).exists() {
-                return Some(path.to_string());
+                add(path.to_string());
             }
         }
+        for path in godot_binaries_in(Path::new(r"C:\Program Files\Godot")) {
+            add(path);
+        }
         
         // Check user-specific locations
         if let Ok(localappdata) = std::env::var("LOCALAPPDATA") {
@@ -638,7 +1350,7 @@ fn find_godot_path() -> Option<String> {
             ];
             for path in user_paths {
                 if Path::new(&path).exists() {
-                    return Some(path);
+                    add(path);
                 }
             }
         }
@@ -651,7 +1363,7 @@ fn find_godot_path() -> Option<String> {
             ];
             for path in user_paths {
                 if Path::new(&path).exists() {
-                    return Some(path);
+                    add(path);
                 }
             }
         }
@@ -665,9 +1377,13 @@ fn find_godot_path() -> Option<String> {
         ];
         for path in mac_paths {
             if Path::new(path).exists() {
-                return Some(path.to_string());
+                add(path.to_string());
             }
         }
+        // Versioned bundles like "Godot_v4.2.app"
+        for path in godot_binaries_in(Path::new("/Applications")) {
+            add(path);
+        }
     }
 
     #[cfg(target_os = "linux")]
@@ -680,19 +1396,40 @@ fn find_godot_path() -> Option<String> {
         ];
         for path in linux_paths {
             if Path::new(path).exists() {
-                return Some(path.to_string());
+                add(path.to_string());
             }
         }
         
         if let Ok(home) = std::env::var("HOME") {
             let home_path = format!("{}/.local/bin/godot", home);
             if Path::new(&home_path).exists() {
-                return Some(home_path);
+                add(home_path);
+            }
+            // Versioned downloads like "Godot_v4.2-stable_linux.x86_64"
+            for path in godot_binaries_in(&Path::new(&home).join(".local/bin")) {
+                add(path);
             }
         }
     }
 
-    None
+    found
+}
+
+/// Godot binaries in a directory, by name ("godot*", "Godot*.app"), for
+/// side-by-side installs of several versions
+fn godot_binaries_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+    let mut binaries: Vec<String> = entries.flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_lowercase();
+            name.starts_with("godot") && !name.contains("console")
+        })
+        .map(|e| resolve_godot_binary(&e.path()))
+        .filter(|path| if cfg!(windows) { path.extension().is_some_and(|e| e.eq_ignore_ascii_case("exe")) } else { is_executable(path) })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    binaries.sort();
+    binaries
 }
 
 #[tauri::command]
@@ -725,58 +1462,195 @@ fn detect_godot(state: tauri::State<AppState>) -> Result<String, String> {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GodotInstallation {
+    pub path: String,
+    /// None when `--version` could not be read
+    pub version: Option<String>,
+}
+
+/// Every Godot binary found (the saved path first) with the version it reports,
+/// so users with several versions can pick one per project
 #[tauri::command]
-fn install_godot() -> Result<String, String> {
-    // Try winget first on Windows
-    #[cfg(windows)]
-    {
-        let result = Command::new("cmd")
-            .args(["/C", "winget", "install", "--id", "GodotEngine.GodotEngine", "-e", "--accept-package-agreements", "--accept-source-agreements"])
-            .creation_flags(CREATE_NO_WINDOW)
-            .output();
-        
-        if let Ok(output) = result {
-            if output.status.success() {
-                return Ok("Installing Godot via winget... Please wait and then click refresh.".to_string());
+async fn list_godot_installations(state: tauri::State<'_, AppState>) -> Result<Vec<GodotInstallation>, String> {
+    let saved = state.settings.lock().unwrap()
+        .godot_path
+        .clone()
+        .filter(|p| !p.is_empty() && Path::new(p).exists());
+    tokio::task::spawn_blocking(move || {
+        let mut paths: Vec<String> = saved.into_iter().collect();
+        for path in find_godot_paths() {
+            if !paths.contains(&path) {
+                paths.push(path);
             }
         }
-        
-        // Fallback to opening download page
-        open::that("https://godotengine.org/download/windows/")
-            .map_err(|e| format!("Failed to open download page: {}", e))?;
-        return Ok("Opening Godot download page...".to_string());
+        paths.into_iter()
+            .map(|path| GodotInstallation { version: get_godot_version(&path).ok(), path })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Godot lookup failed: {}", e))
+}
+
+/// Godot binary a project is pinned to, saved in `.tav/godot.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GodotPin {
+    pub path: String,
+    /// As reported by get_godot_version, e.g. "4.2.stable"
+    pub version: String,
+}
+
+fn godot_pin_path(project: &Path) -> PathBuf {
+    project.join(".tav/godot.json")
+}
+
+fn read_godot_pin(project: &Path) -> Option<GodotPin> {
+    let content = fs::read_to_string(godot_pin_path(project)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Godot binary for a project: its pin, else the saved path, else the first one
+/// found. A pinned binary that moved is looked up again by version; when that
+/// version is no longer installed this fails instead of using another version.
+fn project_godot_cmd(project: &Path, saved: Option<String>) -> Result<Option<String>, String> {
+    if let Some(pin) = read_godot_pin(project) {
+        if Path::new(&pin.path).exists() {
+            return Ok(Some(pin.path));
+        }
+        return find_godot_paths().into_iter()
+            .find(|path| get_godot_version(path).is_ok_and(|v| v == pin.version))
+            .map(Some)
+            .ok_or_else(|| format!(
+                "This project is pinned to Godot {} ({}), which is not installed. Install it or change the project's Godot version.",
+                pin.version, pin.path
+            ));
+    }
+    Ok(saved_godot_cmd(saved))
+}
+
+/// The Godot binary from settings when it still exists, else one found on this machine
+fn saved_godot_cmd(saved: Option<String>) -> Option<String> {
+    saved.filter(|p| !p.is_empty() && Path::new(p).exists()).or_else(find_godot_path)
+}
+
+/// project_godot_cmd for commands that may run before a project is open
+fn optional_project_godot_cmd(project_path: Option<&str>, saved: Option<String>) -> Result<Option<String>, String> {
+    match project_path.filter(|p| !p.is_empty()) {
+        Some(project) => project_godot_cmd(Path::new(project), saved),
+        None => Ok(saved_godot_cmd(saved)),
     }
-    
-    #[cfg(target_os = "macos")]
-    {
-        let result = Command::new("brew")
-            .args(["install", "--cask", "godot"])
-            .output();
-        
-        if let Ok(output) = result {
-            if output.status.success() {
-                return Ok("Installing Godot via Homebrew...".to_string());
-            }
+}
+
+/// Version whose export templates a project needs: its pinned version, else
+/// the version of `godot_cmd`
+fn export_templates_version(project_path: Option<&str>, godot_cmd: &str) -> Result<String, String> {
+    match project_path.filter(|p| !p.is_empty()).and_then(|p| read_godot_pin(Path::new(p))) {
+        Some(pin) => Ok(pin.version),
+        None => get_godot_version(godot_cmd),
+    }
+}
+
+/// Pin a project to a Godot binary (validated like set_godot_path), or remove
+/// the pin with `None` so it follows the app setting again
+#[tauri::command]
+fn pin_godot_version(project_path: String, godot_path: Option<String>) -> Result<Option<GodotPin>, String> {
+    let project = Path::new(&project_path);
+    let pin_file = godot_pin_path(project);
+    let Some(godot_path) = godot_path.filter(|p| !p.trim().is_empty()) else {
+        if pin_file.exists() {
+            fs::remove_file(&pin_file).map_err(|e| format!("Failed to remove Godot pin: {}", e))?;
         }
-        
-        open::that("https://godotengine.org/download/macos/")
-            .map_err(|e| format!("Failed to open download page: {}", e))?;
-        return Ok("Opening Godot download page...".to_string());
+        println!("[Godot] Unpinned {}", project_path);
+        return Ok(None);
+    };
+    if !project.join("project.godot").exists() {
+        return Err("Not a Godot project (project.godot not found)".to_string());
+    }
+    let (path, version) = validate_godot_binary(&godot_path)?;
+    let pin = GodotPin { path, version };
+    fs::create_dir_all(project.join(".tav")).map_err(|e| format!("Failed to create .tav: {}", e))?;
+    let json = serde_json::to_string_pretty(&pin).map_err(|e| format!("Failed to serialize Godot pin: {}", e))?;
+    write_file_atomic(&pin_file, &json).map_err(|e| format!("Failed to write Godot pin: {}", e))?;
+    println!("[Godot] Pinned {} to Godot {}", project_path, pin.version);
+    Ok(Some(pin))
+}
+
+#[tauri::command]
+fn get_godot_pin(project_path: String) -> Option<GodotPin> {
+    read_godot_pin(Path::new(&project_path))
+}
+
+/// Package manager command that installs Godot on this platform, if there is one
+fn godot_package_install() -> Option<(&'static str, &'static [&'static str])> {
+    if cfg!(windows) {
+        Some(("winget", &["install", "--id", "GodotEngine.GodotEngine", "-e", "--accept-package-agreements", "--accept-source-agreements"]))
+    } else if cfg!(target_os = "macos") {
+        Some(("brew", &["install", "--cask", "godot"]))
+    } else {
+        None
     }
-    
-    #[cfg(target_os = "linux")]
-    {
-        open::that("https://godotengine.org/download/linux/")
-            .map_err(|e| format!("Failed to open download page: {}", e))?;
-        return Ok("Opening Godot download page...".to_string());
+}
+
+fn godot_download_page() -> &'static str {
+    if cfg!(windows) {
+        "https://godotengine.org/download/windows/"
+    } else if cfg!(target_os = "macos") {
+        "https://godotengine.org/download/macos/"
+    } else if cfg!(target_os = "linux") {
+        "https://godotengine.org/download/linux/"
+    } else {
+        "https://godotengine.org/download/"
     }
-    
-    #[cfg(not(any(windows, target_os = "macos", target_os = "linux")))]
-    {
-        open::that("https://godotengine.org/download/")
-            .map_err(|e| format!("Failed to open download page: {}", e))?;
-        Ok("Opening Godot download page...".to_string())
+}
+
+/// Install Godot with winget/Homebrew and wait for it to finish. On success the
+/// installed executable is located and saved; failures (including a cancelled
+/// UAC prompt) return the package manager's error. Without a package manager
+/// the download page is opened instead.
+#[tauri::command]
+async fn install_godot(app: tauri::AppHandle) -> Result<String, String> {
+    // Package manager installs take minutes; keep them off the main thread
+    tokio::task::spawn_blocking(move || install_godot_blocking(&app))
+        .await
+        .map_err(|e| format!("Godot install task failed: {}", e))?
+}
+
+fn install_godot_blocking(app: &tauri::AppHandle) -> Result<String, String> {
+    if let Some((program, args)) = godot_package_install().filter(|(program, _)| tool_installed(program)) {
+        println!("[Setup] Installing Godot via {}", program);
+        let output = tool_process(program, args)
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+        if !output.status.success() {
+            // winget reports most failures on stdout
+            let text = [output.stderr, output.stdout].iter()
+                .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+                .find(|text| !text.is_empty())
+                .unwrap_or_default();
+            let lines: Vec<&str> = text.lines().collect();
+            let details = lines[lines.len().saturating_sub(5)..].join("\n");
+            return Err(format!("{} failed ({}): {}", program, output.status, details));
+        }
+
+        let path = find_godot_path().ok_or_else(|| format!(
+            "{} finished but Godot wasn't found. Restart Kobold or set the Godot path manually.",
+            program
+        ))?;
+        app.state::<AppState>().settings.lock().unwrap().godot_path = Some(path.clone());
+        save_settings_to_disk(&AppSettings {
+            godot_path: Some(path.clone()),
+            ..Default::default()
+        })?;
+        println!("[Setup] Godot installed at {}", path);
+        return Ok(path);
     }
+
+    open::that(godot_download_page())
+        .map_err(|e| format!("Failed to open download page: {}", e))?;
+    Ok("Opening Godot download page...".to_string())
 }
 
 // ============================================================================
@@ -784,59 +1658,117 @@ fn install_godot() -> Result<String, String> {
 // ============================================================================
 
 fn detect_goose() -> bool {
-    let result = if cfg!(windows) {
-        silent_cmd("cmd", &["/C", "where", "goose"])
-    } else {
-        Command::new("which").arg("goose").output()
-    };
-    result.map(|o| o.status.success()).unwrap_or(false)
+    tool_installed("goose")
+}
+
+// ============================================================================
+// Git Integration
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitFileStatus {
+    pub path: String,
+    /// Two-letter porcelain code, e.g. " M", "A ", "??"
+    pub status: String,
+}
+
+fn detect_git() -> bool {
+    tool_installed("git")
+}
+
+/// Run git in `project`, returning trimmed stdout or stderr on failure. git is a
+/// real executable, so it is spawned directly rather than through `cmd /C`,
+/// keeping commit messages free of shell quoting.
+fn run_git(project: &str, args: &[&str]) -> Result<String, String> {
+    let mut full_args = vec!["-C", project];
+    full_args.extend_from_slice(args);
+    let output = silent_cmd("git", &full_args).map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args.first().unwrap_or(&""), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    // Untrimmed: porcelain status lines can start with a space
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse `git status --porcelain -z`; renames/copies carry their old path as an extra entry
+fn parse_git_status(porcelain: &str) -> Vec<GitFileStatus> {
+    let mut files = Vec::new();
+    let mut entries = porcelain.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        let (Some(status), Some(path)) = (entry.get(..2), entry.get(3..)) else { continue };
+        if status.starts_with('R') || status.starts_with('C') {
+            entries.next();
+        }
+        files.push(GitFileStatus { path: path.to_string(), status: status.to_string() });
+    }
+    files
+}
+
+#[tauri::command]
+fn git_status(project_path: String) -> Result<Vec<GitFileStatus>, String> {
+    if !detect_git() {
+        return Err("Git is not installed".to_string());
+    }
+    Ok(parse_git_status(&run_git(&project_path, &["status", "--porcelain", "-z"])?))
+}
+
+/// Stage everything and commit it, returning the commit hash. With nothing to
+/// commit the current HEAD is returned instead.
+#[tauri::command]
+fn git_snapshot(project_path: String, message: String) -> Result<String, String> {
+    if !detect_git() {
+        return Err("Git is not installed".to_string());
+    }
+    run_git(&project_path, &["add", "-A"])?;
+    if run_git(&project_path, &["status", "--porcelain"])?.is_empty() {
+        return run_git(&project_path, &["rev-parse", "HEAD"]).map(|hash| hash.trim().to_string());
+    }
+    run_git(&project_path, &["commit", "-q", "-m", &message])?;
+    let hash = run_git(&project_path, &["rev-parse", "HEAD"])?.trim().to_string();
+    println!("[Git] Snapshot {} in {}", hash, project_path);
+    Ok(hash)
 }
 
 // ============================================================================
 // Beads Task Tracking Integration
 // ============================================================================
 
+/// Cached result of looking up `bd` on the PATH, set once per app run (or by install_beads)
+static BEADS_INSTALLED: Mutex<Option<bool>> = Mutex::new(None);
+
+/// Beads context per project, with the `.beads` modification time it was generated at
+static BEADS_CONTEXT_CACHE: Mutex<Option<std::collections::HashMap<PathBuf, (std::time::SystemTime, String)>>> =
+    Mutex::new(None);
+
 #[tauri::command]
 fn detect_beads() -> bool {
-    let result = if cfg!(windows) {
-        silent_cmd("cmd", &["/C", "where", "bd"])
-    } else {
-        Command::new("which").arg("bd").output()
-    };
-    result.map(|o| o.status.success()).unwrap_or(false)
+    let mut cached = BEADS_INSTALLED.lock().unwrap();
+    *cached.get_or_insert_with(|| tool_installed("bd"))
 }
 
-#[tauri::command]
-async fn install_beads() -> Result<String, String> {
-    // Use go install method (requires Go)
-    let result = if cfg!(windows) {
-        silent_cmd("cmd", &["/C", "go", "install", "github.com/steveyegge/beads/cmd/bd@latest"])
-    } else {
-        Command::new("go")
-            .args(["install", "github.com/steveyegge/beads/cmd/bd@latest"])
-            .output()
-    };
+/// Latest modification time of the `.beads` directory or any file directly inside it
+fn beads_modified(beads_dir: &Path) -> Option<std::time::SystemTime> {
+    let dir_time = fs::metadata(beads_dir).and_then(|m| m.modified()).ok()?;
+    let newest_file = fs::read_dir(beads_dir).ok()?
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .max();
+    Some(newest_file.map_or(dir_time, |t| t.max(dir_time)))
+}
 
-    match result {
-        Ok(output) if output.status.success() => {
-            Ok("Beads installed successfully".to_string())
-        }
-        Ok(output) => {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            // Try npm as fallback
-            let npm_result = if cfg!(windows) {
-                silent_cmd("cmd", &["/C", "npm", "install", "-g", "@beads/bd"])
-            } else {
-                Command::new("npm").args(["install", "-g", "@beads/bd"]).output()
-            };
-            
-            match npm_result {
-                Ok(o) if o.status.success() => Ok("Beads installed via npm".to_string()),
-                _ => Err(format!("Failed to install Beads: {}", stderr))
-            }
-        }
-        Err(e) => Err(format!("Failed to run installer: {}", e))
-    }
+/// Install Beads in the background; returns an install id for `install-progress` events
+#[tauri::command]
+fn install_beads(app: tauri::AppHandle) -> Result<String, String> {
+    // Use go install method (requires Go), falling back to npm
+    Ok(spawn_install(
+        app,
+        "Beads",
+        vec![
+            ("go", vec!["install", "github.com/steveyegge/beads/cmd/bd@latest"]),
+            ("npm", vec!["install", "-g", "@beads/bd"]),
+        ],
+        || *BEADS_INSTALLED.lock().unwrap() = Some(true),
+    ))
 }
 
 #[tauri::command]
@@ -857,17 +1789,7 @@ fn init_beads(project_path: String) -> Result<String, String> {
     }
 
     // Initialize Beads in the project
-    let result = if cfg!(windows) {
-        Command::new("cmd")
-            .args(["/C", "bd", "init", "--quiet"])
-            .current_dir(project_dir)
-            .output()
-    } else {
-        Command::new("bd")
-            .args(["init", "--quiet"])
-            .current_dir(project_dir)
-            .output()
-    };
+    let result = run_bd(project_dir, &["init", "--quiet"]);
 
     match result {
         Ok(output) if output.status.success() => {
@@ -888,172 +1810,386 @@ fn get_beads_context(project_path: String) -> Result<String, String> {
     }
 
     let project_dir = Path::new(&project_path);
-    if !project_dir.join(".beads").exists() {
+    let beads_dir = project_dir.join(".beads");
+    if !beads_dir.exists() {
         return Err("Beads not initialized in this project".to_string());
     }
 
+    // Reuse the previous context unless the task database changed since it was generated
+    if let Some(modified) = beads_modified(&beads_dir) {
+        if let Some((cached_at, context)) = BEADS_CONTEXT_CACHE.lock().unwrap()
+            .as_ref()
+            .and_then(|cache| cache.get(&beads_dir))
+        {
+            if *cached_at == modified {
+                return Ok(context.clone());
+            }
+        }
+    }
+
     // Get ready tasks for agent context
-    let result = if cfg!(windows) {
-        Command::new("cmd")
-            .args(["/C", "bd", "prime"])
-            .current_dir(project_dir)
-            .output()
-    } else {
-        Command::new("bd")
-            .args(["prime"])
-            .current_dir(project_dir)
-            .output()
-    };
+    let result = run_bd(project_dir, &["prime"]);
 
     match result {
         Ok(output) if output.status.success() => {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            let context = String::from_utf8_lossy(&output.stdout).to_string();
+            // Stamp after running bd, so any files it touches itself don't invalidate the cache
+            if let Some(modified) = beads_modified(&beads_dir) {
+                BEADS_CONTEXT_CACHE.lock().unwrap()
+                    .get_or_insert_with(std::collections::HashMap::new)
+                    .insert(beads_dir, (modified, context.clone()));
+            }
+            Ok(context)
         }
         Ok(_) => Ok("".to_string()), // No tasks yet
         Err(e) => Err(format!("Failed to get Beads context: {}", e))
     }
 }
 
-// ============================================================================
-// Godot MCP Setup
-// ============================================================================
-
-#[tauri::command]
-fn install_godot_mcp() -> Result<String, String> {
-    let result = if cfg!(windows) {
-        silent_cmd("cmd", &["/C", "npm", "install", "-g", "godot-mcp"])
-    } else {
-        Command::new("npm")
-            .args(["install", "-g", "godot-mcp"])
-            .output()
-    };
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeadsTask {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    pub priority: Option<i64>,
+    pub ready: bool,
+}
 
-    match result {
-        Ok(output) => {
-            if output.status.success() {
-                Ok("Godot MCP installed successfully".to_string())
-            } else {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Err(format!("Failed to install Godot MCP: {}", stderr))
-            }
-        }
-        Err(e) => Err(format!("Failed to run npm: {}", e)),
-    }
+/// Run `bd` with the given arguments inside a project directory
+fn run_bd(project_dir: &Path, args: &[&str]) -> std::io::Result<std::process::Output> {
+    tool_process("bd", args).current_dir(project_dir).output()
 }
 
-#[tauri::command]
-fn detect_godot_mcp() -> bool {
-    let result = if cfg!(windows) {
-        silent_cmd("cmd", &["/C", "npm", "list", "-g", "godot-mcp"])
-    } else {
-        Command::new("npm")
-            .args(["list", "-g", "godot-mcp"])
-            .output()
-    };
-    
-    result.map(|o| o.status.success()).unwrap_or(false)
+/// Build tasks from `bd list --json`, marking those that also appear in `bd ready --json`
+fn parse_beads_tasks(list_json: &str, ready_json: &str) -> Result<Vec<BeadsTask>, String> {
+    let issues: Vec<serde_json::Value> = serde_json::from_str(list_json)
+        .map_err(|e| format!("Failed to parse Beads tasks: {}", e))?;
+    let ready: std::collections::HashSet<String> = serde_json::from_str::<Vec<serde_json::Value>>(ready_json)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|issue| issue["id"].as_str().map(str::to_string))
+        .collect();
+
+    Ok(issues.iter().filter_map(|issue| {
+        let id = issue["id"].as_str()?.to_string();
+        let priority = issue["priority"].as_i64().or_else(|| {
+            issue["priority"].as_str()?.trim_start_matches(['P', 'p']).parse().ok()
+        });
+        Some(BeadsTask {
+            ready: ready.contains(&id),
+            title: issue["title"].as_str().unwrap_or_default().to_string(),
+            status: issue["status"].as_str().unwrap_or("open").to_string(),
+            priority,
+            id,
+        })
+    }).collect())
 }
 
 #[tauri::command]
-fn setup_godot_mcp_config() -> Result<(), String> {
-    // Configure Goose's MCP settings for Godot and Beads
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
-    let goose_config_dir = home.join(".config").join("goose");
-    let goose_profiles_path = goose_config_dir.join("profiles.yaml");
-    
-    // Create .config/goose directory if it doesn't exist
-    fs::create_dir_all(&goose_config_dir).ok();
-    
-    // Read existing profiles or create new
-    let mut profiles: serde_yaml::Value = if goose_profiles_path.exists() {
-        let content = fs::read_to_string(&goose_profiles_path).unwrap_or_default();
-        serde_yaml::from_str(&content).unwrap_or(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()))
-    } else {
-        serde_yaml::Value::Mapping(serde_yaml::Mapping::new())
-    };
-    
-    // Add extensions to default profile
-    if let serde_yaml::Value::Mapping(ref mut map) = profiles {
-        let default_profile = map
-            .entry(serde_yaml::Value::String("default".to_string()))
-            .or_insert(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
-        
-        if let serde_yaml::Value::Mapping(ref mut profile_map) = default_profile {
-            let extensions = profile_map
-                .entry(serde_yaml::Value::String("extensions".to_string()))
-                .or_insert(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
-            
-            if let serde_yaml::Value::Mapping(ref mut ext_map) = extensions {
-                // Add Godot MCP
-                let mut godot_config = serde_yaml::Mapping::new();
-                godot_config.insert(
-                    serde_yaml::Value::String("type".to_string()),
-                    serde_yaml::Value::String("stdio".to_string())
-                );
-                godot_config.insert(
-                    serde_yaml::Value::String("cmd".to_string()),
-                    serde_yaml::Value::String("npx".to_string())
-                );
-                let mut godot_args = serde_yaml::Sequence::new();
-                godot_args.push(serde_yaml::Value::String("-y".to_string()));
-                godot_args.push(serde_yaml::Value::String("godot-mcp".to_string()));
-                godot_config.insert(
-                    serde_yaml::Value::String("args".to_string()),
-                    serde_yaml::Value::Sequence(godot_args)
-                );
-                ext_map.insert(
-                    serde_yaml::Value::String("godot".to_string()),
-                    serde_yaml::Value::Mapping(godot_config)
-                );
-                
-                // Add Beads MCP for task tracking
-                let mut beads_config = serde_yaml::Mapping::new();
-                beads_config.insert(
-                    serde_yaml::Value::String("type".to_string()),
-                    serde_yaml::Value::String("stdio".to_string())
-                );
-                beads_config.insert(
-                    serde_yaml::Value::String("cmd".to_string()),
-                    serde_yaml::Value::String("beads-mcp".to_string())
-                );
-                ext_map.insert(
-                    serde_yaml::Value::String("beads".to_string()),
-                    serde_yaml::Value::Mapping(beads_config)
-                );
-            }
-        }
+fn get_beads_tasks(project_path: String) -> Result<Vec<BeadsTask>, String> {
+    if !detect_beads() {
+        return Err("Beads not installed".to_string());
     }
-    
-    // Write back
-    let yaml_str = serde_yaml::to_string(&profiles)
-        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
-    fs::write(&goose_profiles_path, yaml_str)
-        .map_err(|e| format!("Failed to write Goose profiles: {}", e))?;
-    
-    Ok(())
+
+    let project_dir = Path::new(&project_path);
+    if !project_dir.join(".beads").exists() {
+        return Err("Beads not initialized in this project".to_string());
+    }
+
+    let list = run_bd(project_dir, &["list", "--json"])
+        .map_err(|e| format!("Failed to list Beads tasks: {}", e))?;
+    if !list.status.success() {
+        return Err(format!("Failed to list Beads tasks: {}", String::from_utf8_lossy(&list.stderr)));
+    }
+
+    // Readiness is best-effort; tasks are still listed if `bd ready` fails
+    let ready = run_bd(project_dir, &["ready", "--json"])
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default();
+
+    parse_beads_tasks(&String::from_utf8_lossy(&list.stdout), &ready)
 }
 
 #[tauri::command]
-fn open_url(url: String) -> Result<(), String> {
-    open::that(&url).map_err(|e| format!("Failed to open URL: {}", e))
+fn close_beads_task(project_path: String, id: String) -> Result<(), String> {
+    if !detect_beads() {
+        return Err("Beads not installed".to_string());
+    }
+
+    let output = run_bd(Path::new(&project_path), &["close", &id])
+        .map_err(|e| format!("Failed to run bd close: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to close task {}: {}", id, String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(())
 }
 
 // ============================================================================
-// Project Templates
+// Godot MCP Setup
 // ============================================================================
 
+/// Install Godot MCP in the background; returns an install id for `install-progress` events
 #[tauri::command]
-fn initialize_godot_project(
-    project_path: String,
-    dimension: String,
-    template: String,
-) -> Result<(), String> {
-    let path = Path::new(&project_path);
-    let name = path.file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("game");
-    
-    // Create professional folder structure
+fn install_godot_mcp(app: tauri::AppHandle) -> Result<String, String> {
+    Ok(spawn_install(app, "Godot MCP", vec![("npm", vec!["install", "-g", "godot-mcp"])], || {}))
+}
+
+#[tauri::command]
+fn detect_godot_mcp() -> bool {
+    run_tool("npm", &["list", "-g", "godot-mcp"]).is_ok()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpVerification {
+    pub godot_mcp_runnable: bool,
+    pub beads_mcp_runnable: bool,
+    pub errors: Vec<String>,
+}
+
+/// How long an MCP server gets to answer `initialize` (npx may need to download first)
+const MCP_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+fn mcp_initialize_request() -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {"name": "kobold", "version": env!("CARGO_PKG_VERSION")}
+        }
+    }).to_string()
+}
+
+/// Whether a stdout line is the server's reply to our `initialize` request
+fn is_mcp_initialize_response(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line)
+        .map(|msg| msg["id"] == 1 && msg.get("result").is_some())
+        .unwrap_or(false)
+}
+
+/// Launch an MCP server over stdio and wait for it to answer the initialize handshake
+fn probe_mcp_server(program: &str, args: &[&str]) -> Result<(), String> {
+    use std::io::Write;
+
+    let mut child = tool_process(program, args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: failed to start: {}", program, e))?;
+
+    // Keep stdin open until we're done so the server doesn't exit before replying
+    let mut stdin = child.stdin.take();
+    if let Some(stdin) = stdin.as_mut() {
+        let _ = writeln!(stdin, "{}", mcp_initialize_request());
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take();
+    std::thread::spawn(move || {
+        for line in stdout.map(BufReader::new).into_iter().flat_map(|r| r.lines()).map_while(Result::ok) {
+            if is_mcp_initialize_response(&line) {
+                let _ = tx.send(());
+                break;
+            }
+        }
+    });
+    // Track the last stderr line without joining: npx children can keep the pipe open
+    let last_stderr = std::sync::Arc::new(Mutex::new(String::new()));
+    let stderr = child.stderr.take();
+    let stderr_line = last_stderr.clone();
+    std::thread::spawn(move || {
+        for line in stderr.map(BufReader::new).into_iter().flat_map(|r| r.lines()).map_while(Result::ok) {
+            if !line.trim().is_empty() {
+                *stderr_line.lock().unwrap() = line.trim().to_string();
+            }
+        }
+    });
+
+    let answered = rx.recv_timeout(MCP_PROBE_TIMEOUT).is_ok();
+    let exited = child.try_wait().ok().flatten();
+    drop(stdin);
+    let _ = child.kill();
+    let _ = child.wait();
+    if answered {
+        return Ok(());
+    }
+
+    let last_line = last_stderr.lock().unwrap().clone();
+    Err(match (exited, last_line.is_empty()) {
+        (_, false) => format!("{}: no MCP handshake ({})", program, last_line),
+        (Some(status), true) => format!("{}: exited with {} before the MCP handshake", program, status),
+        (None, true) => format!("{}: no MCP handshake within {}s", program, MCP_PROBE_TIMEOUT.as_secs()),
+    })
+}
+
+/// Check that Goose will be able to launch the Godot and Beads MCP servers
+#[tauri::command]
+async fn verify_mcp_setup() -> Result<McpVerification, String> {
+    let (godot, beads) = tokio::join!(
+        tokio::task::spawn_blocking(|| probe_mcp_server("npx", &["-y", "godot-mcp"])),
+        tokio::task::spawn_blocking(|| probe_mcp_server("beads-mcp", &[])),
+    );
+    let godot = godot.map_err(|e| format!("Godot MCP check failed: {}", e))?;
+    let beads = beads.map_err(|e| format!("Beads MCP check failed: {}", e))?;
+
+    Ok(McpVerification {
+        godot_mcp_runnable: godot.is_ok(),
+        beads_mcp_runnable: beads.is_ok(),
+        errors: [godot, beads].into_iter().filter_map(Result::err).collect(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpConfigSummary {
+    /// Extensions Kobold inserted
+    pub added: Vec<String>,
+    /// Extensions that already existed and were left as the user configured them
+    pub untouched: Vec<String>,
+}
+
+/// Add Kobold's extensions to the default Goose profile, keeping any existing
+/// extension entries (including custom args) exactly as they are
+fn merge_goose_extensions(profiles: &mut serde_yaml::Value) -> Result<McpConfigSummary, String> {
+    let extensions: [(&str, serde_yaml::Value); 2] = [
+        // Godot MCP
+        ("godot", serde_yaml::from_str("{type: stdio, cmd: npx, args: [-y, godot-mcp]}").unwrap()),
+        // Beads MCP for task tracking
+        ("beads", serde_yaml::from_str("{type: stdio, cmd: beads-mcp}").unwrap()),
+    ];
+
+    if profiles.is_null() {
+        *profiles = serde_yaml::Value::Mapping(serde_yaml::Mapping::new());
+    }
+    let ext_map = profiles.as_mapping_mut()
+        .ok_or("profiles.yaml is not a mapping")?
+        .entry("default".into())
+        .or_insert(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()))
+        .as_mapping_mut()
+        .ok_or("The default Goose profile is not a mapping")?
+        .entry("extensions".into())
+        .or_insert(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()))
+        .as_mapping_mut()
+        .ok_or("The default profile's extensions are not a mapping")?;
+
+    let mut summary = McpConfigSummary { added: vec![], untouched: vec![] };
+    for (name, config) in extensions {
+        if ext_map.contains_key(name) {
+            summary.untouched.push(name.to_string());
+        } else {
+            ext_map.insert(name.into(), config);
+            summary.added.push(name.to_string());
+        }
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+fn setup_godot_mcp_config() -> Result<McpConfigSummary, String> {
+    // Configure Goose's MCP settings for Godot and Beads
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    let goose_config_dir = home.join(".config").join("goose");
+    let goose_profiles_path = goose_config_dir.join("profiles.yaml");
+    
+    // Create .config/goose directory if it doesn't exist
+    fs::create_dir_all(&goose_config_dir).ok();
+    
+    // Read existing profiles or create new. An unreadable file is an error rather
+    // than something to overwrite.
+    let existing = if goose_profiles_path.exists() {
+        Some(fs::read_to_string(&goose_profiles_path)
+            .map_err(|e| format!("Failed to read Goose profiles: {}", e))?)
+    } else {
+        None
+    };
+    let mut profiles: serde_yaml::Value = match &existing {
+        Some(content) => serde_yaml::from_str(content)
+            .map_err(|e| format!("Failed to parse Goose profiles: {}", e))?,
+        None => serde_yaml::Value::Mapping(serde_yaml::Mapping::new()),
+    };
+    
+    let summary = merge_goose_extensions(&mut profiles)?;
+    if summary.added.is_empty() {
+        return Ok(summary);
+    }
+    
+    if let Some(content) = &existing {
+        fs::write(goose_config_dir.join("profiles.yaml.bak"), content)
+            .map_err(|e| format!("Failed to back up Goose profiles: {}", e))?;
+    }
+    
+    // Write back
+    let yaml_str = serde_yaml::to_string(&profiles)
+        .map_err(|e| format!("Failed to serialize profiles: {}", e))?;
+    fs::write(&goose_profiles_path, yaml_str)
+        .map_err(|e| format!("Failed to write Goose profiles: {}", e))?;
+    
+    println!("[Setup] Goose extensions added: {:?}, untouched: {:?}", summary.added, summary.untouched);
+    Ok(summary)
+}
+
+#[tauri::command]
+fn open_url(url: String) -> Result<(), String> {
+    open::that(&url).map_err(|e| format!("Failed to open URL: {}", e))
+}
+
+/// Platform command that opens the file manager with `path` selected. Linux file
+/// managers have no common "select" flag, so the containing folder is opened instead.
+fn reveal_command(os: &str, path: &Path) -> (String, Vec<String>) {
+    match os {
+        "windows" => ("explorer".to_string(), vec![format!("/select,{}", path.display())]),
+        "macos" => ("open".to_string(), vec!["-R".to_string(), path.display().to_string()]),
+        _ => {
+            let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(path);
+            ("xdg-open".to_string(), vec![parent.display().to_string()])
+        }
+    }
+}
+
+#[tauri::command]
+fn reveal_in_file_manager(path: String) -> Result<(), String> {
+    let path = Path::new(&path);
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.display()));
+    }
+    // Explorer's /select needs a canonical backslash path
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    #[cfg(windows)]
+    let path = PathBuf::from(path.to_string_lossy().trim_start_matches(r"\\?\").to_string());
+
+    let (program, args) = reveal_command(std::env::consts::OS, &path);
+    // Don't wait: explorer exits non-zero even on success
+    Command::new(&program)
+        .args(&args)
+        .spawn()
+        .map_err(|e| format!("Failed to open file manager: {}", e))?;
+    Ok(())
+}
+
+// ============================================================================
+// Project Templates
+// ============================================================================
+
+#[tauri::command]
+fn initialize_godot_project(
+    project_path: String,
+    dimension: String,
+    template: String,
+    mobile: Option<bool>,
+) -> Result<(), String> {
+    let path = Path::new(&project_path);
+    let name = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("game");
+    
+    // Create professional folder structure
     let dirs = [
         "scenes",
         "autoload",
@@ -1131,6 +2267,8 @@ fn initialize_godot_project(
     
     fs::write(path.join("assets/entities/player/player.gd"), main_script)
         .map_err(|e| format!("Failed to write player script: {}", e))?;
+    write_template_extra_files(path, &dimension, &template)?;
+    apply_mobile_option(path, &dimension, &template, mobile)?;
     
     // Create RULES.md for AI agents
     let _ = ensure_project_config(&project_path);
@@ -1144,6 +2282,7 @@ fn create_project_from_template(
     parent_path: String,
     dimension: String,
     template: String,
+    mobile: Option<bool>,
 ) -> Result<String, String> {
     let project_path = Path::new(&parent_path).join(&name);
     
@@ -1224,20 +2363,29 @@ fn create_project_from_template(
     // Generate main scene based on template
     let (main_scene, main_script) = generate_template_files(&dimension, &template);
     
-    fs::write(project_path.join("scenes/main.tscn"), main_scene)
+    fs::write(project_path.join("scenes/main.tscn"), &main_scene)
         .map_err(|e| format!("Failed to write main scene: {}", e))?;
     
-    fs::write(project_path.join("assets/entities/player/player.gd"), main_script)
+    fs::write(project_path.join("assets/entities/player/player.gd"), &main_script)
         .map_err(|e| format!("Failed to write player script: {}", e))?;
+    write_template_extra_files(&project_path, &dimension, &template)?;
+    // Hashes below stay those of the plain template, so template sync treats the
+    // touch-controls main scene as user-modified instead of overwriting it
+    let mobile = apply_mobile_option(&project_path, &dimension, &template, mobile)?;
     
     // Store template info for auto-sync on future exports
     let kobold_dir = project_path.join(".tav");
     fs::create_dir_all(&kobold_dir).ok();
-    let template_info = serde_json::json!({
+    let mut template_info = serde_json::json!({
         "template": &template,
         "dimension": &dimension,
-        "version": TEMPLATE_VERSION
+        "version": TEMPLATE_VERSION,
+        "mobile": mobile,
+        "file_hashes": template_file_hashes(&main_scene, &main_script)
     });
+    for (rel_path, content) in AUTOLOAD_FILES {
+        template_info["file_hashes"][rel_path] = serde_json::json!(content_hash(content.as_bytes()));
+    }
     fs::write(
         kobold_dir.join("template_info.json"),
         serde_json::to_string_pretty(&template_info).unwrap()
@@ -1249,6 +2397,94 @@ fn create_project_from_template(
     Ok(project_path.to_string_lossy().to_string())
 }
 
+/// Create a project by copying a user-supplied template directory
+#[tauri::command]
+fn create_project_from_template_dir(
+    name: String,
+    parent_path: String,
+    template_dir: String,
+) -> Result<String, String> {
+    let template_root = Path::new(&template_dir);
+    if !template_root.is_dir() {
+        return Err(format!("Template directory does not exist: {}", template_dir));
+    }
+
+    // Optional template.json metadata (same format as the built-in templates)
+    let template_meta: serde_json::Value = fs::read_to_string(template_root.join("template.json"))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(serde_json::json!({}));
+    let template_id = template_meta["id"].as_str()
+        .map(|s| s.to_string())
+        .or_else(|| template_root.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "custom".to_string());
+    let dimension = template_meta["dimension"].as_str().unwrap_or("3d").to_string();
+    let version = template_meta["version"].as_str().unwrap_or("0.0.0").to_string();
+
+    let project_path = Path::new(&parent_path).join(&name);
+    fs::create_dir_all(&project_path)
+        .map_err(|e| format!("Failed to create project directory: {}", e))?;
+
+    copy_template_dir(template_root, &project_path)?;
+
+    // Substitute template variables in project.godot, or generate one if missing
+    let project_godot = project_path.join("project.godot");
+    let content = match fs::read_to_string(&project_godot) {
+        Ok(content) => content
+            .replace("{name}", &name)
+            .replace("{dimension}", &dimension),
+        Err(_) => generate_project_godot(&name, &dimension),
+    };
+    fs::write(&project_godot, content)
+        .map_err(|e| format!("Failed to write project.godot: {}", e))?;
+
+    // Custom templates are never auto-synced (see sync_template_if_needed)
+    let kobold_dir = project_path.join(".tav");
+    fs::create_dir_all(&kobold_dir).ok();
+    let template_info = serde_json::json!({
+        "template": template_id,
+        "dimension": dimension,
+        "version": version,
+        "source": "custom",
+        "template_dir": template_dir
+    });
+    fs::write(
+        kobold_dir.join("template_info.json"),
+        serde_json::to_string_pretty(&template_info).unwrap()
+    ).ok();
+
+    let _ = ensure_project_config(project_path.to_string_lossy().as_ref());
+
+    Ok(project_path.to_string_lossy().to_string())
+}
+
+/// Recursively copy a template directory, skipping VCS, editor cache and template metadata
+fn copy_template_dir(src: &Path, dest: &Path) -> Result<(), String> {
+    let entries = fs::read_dir(src)
+        .map_err(|e| format!("Failed to read template directory {}: {}", src.display(), e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let name = file_name.to_string_lossy();
+        if name == ".git" || name == ".godot" || name == ".tav" || name == "template.json" {
+            continue;
+        }
+
+        let target = dest.join(&file_name);
+        if path.is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create dir {}: {}", target.display(), e))?;
+            copy_template_dir(&path, &target)?;
+        } else {
+            fs::copy(&path, &target)
+                .map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+    }
+
+    Ok(())
+}
+
 fn generate_project_godot(name: &str, dimension: &str) -> String {
     let renderer = if dimension == "3d" { "forward_plus" } else { "gl_compatibility" };
     format!(r#"; Engine configuration file.
@@ -1309,314 +2545,76 @@ renderer/rendering_method="{}"
 "#, name, if dimension == "3d" { "3D" } else { "2D" }, renderer)
 }
 
-// Embed template files from templates folder at compile time
-const THIRD_PERSON_SCENE: &str = include_str!("../../templates/third-person-3d/scene.tscn");
-const THIRD_PERSON_PLAYER: &str = include_str!("../../templates/third-person-3d/player.gd");
-// Note: Third-person uses AMSG addon (packages/amsg) for locomotion, camera, and states
-
-fn generate_template_files(dimension: &str, template: &str) -> (String, String) {
-    match (dimension, template) {
-        ("3d", "third-person") => (
-            THIRD_PERSON_SCENE.to_string(),
-            THIRD_PERSON_PLAYER.to_string()
-        ),
-        ("2d", "platformer") => (
-            r#"[gd_scene load_steps=3 format=3]
-
-[ext_resource type="Script" path="res://assets/entities/player/player.gd" id="1"]
-[ext_resource type="Script" path="res://src/components/health_component.gd" id="2"]
-
-[node name="Main" type="Node2D"]
-
-[node name="Player" type="CharacterBody2D" parent="."]
-position = Vector2(576, 300)
-script = ExtResource("1")
-
-[node name="CollisionShape2D" type="CollisionShape2D" parent="Player"]
-
-[node name="Sprite2D" type="Sprite2D" parent="Player"]
-
-[node name="Camera2D" type="Camera2D" parent="Player"]
-
-[node name="HealthComponent" type="Node" parent="Player"]
-script = ExtResource("2")
-"#.to_string(),
-            r#"extends CharacterBody2D
-class_name Player
-## 2D Platformer Player - Uses Entity-Component Pattern
-## HealthComponent attached as child handles damage/death
-
-@export var speed: float = 300.0
-@export var jump_force: float = -400.0
-
-var gravity: float = ProjectSettings.get_setting("physics/2d/default_gravity")
-@onready var health_comp: HealthComponent = $HealthComponent
-
-func _ready() -> void:
-	# Connect to component signals
-	if health_comp:
-		health_comp.died.connect(_on_died)
-		health_comp.health_changed.connect(_on_health_changed)
-	EventBus.player_spawned.emit(self)
-	print("Player ready! Use WASD/Arrows + Space to jump")
-
-func _physics_process(delta: float) -> void:
-	if not is_on_floor():
-		velocity.y += gravity * delta
-	
-	if Input.is_action_just_pressed("jump") and is_on_floor():
-		velocity.y = jump_force
-	
-	var direction := Input.get_axis("move_left", "move_right")
-	velocity.x = direction * speed if direction else move_toward(velocity.x, 0, speed)
-	
-	move_and_slide()
-
-func take_damage(amount: int) -> void:
-	if health_comp:
-		health_comp.take_damage(amount)
-
-func _on_health_changed(current: int, maximum: int) -> void:
-	EventBus.health_changed.emit(current, maximum)
-
-func _on_died() -> void:
-	EventBus.player_died.emit()
-	# Add death animation/respawn logic here
-	print("Player died!")
-"#.to_string()
-        ),
-        ("2d", "top-down") => (
-            r#"[gd_scene load_steps=3 format=3]
-
-[ext_resource type="Script" path="res://assets/entities/player/player.gd" id="1"]
-[ext_resource type="Script" path="res://src/components/health_component.gd" id="2"]
-
-[node name="Main" type="Node2D"]
-
-[node name="Player" type="CharacterBody2D" parent="."]
-position = Vector2(576, 324)
-script = ExtResource("1")
-
-[node name="CollisionShape2D" type="CollisionShape2D" parent="Player"]
-
-[node name="Sprite2D" type="Sprite2D" parent="Player"]
-
-[node name="Camera2D" type="Camera2D" parent="Player"]
-
-[node name="HealthComponent" type="Node" parent="Player"]
-script = ExtResource("2")
-"#.to_string(),
-            r#"extends CharacterBody2D
-class_name Player
-## 2D Top-Down Player - Uses Entity-Component Pattern
-## HealthComponent attached as child handles damage/death
-
-@export var speed: float = 200.0
-
-@onready var health_comp: HealthComponent = $HealthComponent
-
-func _ready() -> void:
-	if health_comp:
-		health_comp.died.connect(_on_died)
-		health_comp.health_changed.connect(_on_health_changed)
-	EventBus.player_spawned.emit(self)
-	print("Top-down ready! Use WASD/Arrows to move, E to interact")
+/// The `mobile` template option: mobile renderer (Compatibility for web builds),
+/// stretch-to-fit UI and on-screen touch controls instanced into the main scene
+fn apply_mobile_template(project: &Path) -> Result<(), String> {
+    for (rel_path, content) in TOUCH_CONTROLS_FILES {
+        let target = project.join(rel_path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&target, content).map_err(|e| format!("Failed to write {}: {}", rel_path, e))?;
+    }
+
+    let godot_path = project.join("project.godot");
+    let content = fs::read_to_string(&godot_path).map_err(|e| format!("Failed to read project.godot: {}", e))?;
+    let mut config = parse_godot_config(&content);
+    let display = config.section_or_insert("display", &["input", "rendering"]);
+    display.set("window/stretch/mode", "\"canvas_items\"");
+    let rendering = config.section_or_insert("rendering", &[]);
+    rendering.set("renderer/rendering_method", "\"mobile\"");
+    // Browsers only run the Compatibility renderer
+    rendering.set("renderer/rendering_method.web", "\"gl_compatibility\"");
+    fs::write(&godot_path, write_godot_config(&config))
+        .map_err(|e| format!("Failed to write project.godot: {}", e))?;
 
-func _physics_process(_delta: float) -> void:
-	var input_dir := Vector2(
-		Input.get_axis("move_left", "move_right"),
-		Input.get_axis("move_up", "move_down")
-	)
-	velocity = input_dir.normalized() * speed
-	move_and_slide()
-
-func take_damage(amount: int) -> void:
-	if health_comp:
-		health_comp.take_damage(amount)
-
-func interact() -> void:
-	# Override for interaction logic
-	print("Interact pressed!")
-
-func _on_health_changed(current: int, maximum: int) -> void:
-	EventBus.health_changed.emit(current, maximum)
-
-func _on_died() -> void:
-	EventBus.player_died.emit()
-	print("Player died!")
-"#.to_string()
-        ),
-        ("3d", "first-person") => (
-            r#"[gd_scene load_steps=6 format=3]
-
-[ext_resource type="Script" path="res://assets/entities/player/player.gd" id="1"]
-[ext_resource type="Script" path="res://src/components/health_component.gd" id="2"]
-
-[sub_resource type="CapsuleShape3D" id="1"]
-
-[sub_resource type="BoxMesh" id="2"]
-size = Vector3(20, 0.1, 20)
-
-[sub_resource type="BoxShape3D" id="3"]
-size = Vector3(20, 0.1, 20)
+    let scene_path = project.join("scenes/main.tscn");
+    let scene = fs::read_to_string(&scene_path).map_err(|e| format!("Failed to read main scene: {}", e))?;
+    if !parse_tscn_nodes(&scene).iter().any(|n| n.path == "TouchControls") {
+        let scene = add_instance_tscn(&scene, ".", "TouchControls", "res://scenes/ui/touch_controls.tscn");
+        fs::write(&scene_path, scene).map_err(|e| format!("Failed to write main scene: {}", e))?;
+    }
+    Ok(())
+}
 
-[node name="Main" type="Node3D"]
+/// Apply the mobile option when requested and the template supports it
+fn apply_mobile_option(project: &Path, dimension: &str, template: &str, mobile: Option<bool>) -> Result<bool, String> {
+    if !mobile.unwrap_or(false) {
+        return Ok(false);
+    }
+    if !find_template(template, dimension).is_some_and(|t| t.mobile) {
+        println!("[Templates] {} ({}) has no touch controls, ignoring mobile option", template, dimension);
+        return Ok(false);
+    }
+    apply_mobile_template(project)?;
+    Ok(true)
+}
 
-[node name="Player" type="CharacterBody3D" parent="."]
-transform = Transform3D(1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 1, 0)
-script = ExtResource("1")
-
-[node name="CollisionShape3D" type="CollisionShape3D" parent="Player"]
-shape = SubResource("1")
-
-[node name="Camera3D" type="Camera3D" parent="Player"]
-transform = Transform3D(1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0.5, 0)
-
-[node name="HealthComponent" type="Node" parent="Player"]
-script = ExtResource("2")
-
-[node name="DirectionalLight3D" type="DirectionalLight3D" parent="."]
-transform = Transform3D(1, 0, 0, 0, 0.707, 0.707, 0, -0.707, 0.707, 0, 10, 0)
-
-[node name="Floor" type="StaticBody3D" parent="."]
-
-[node name="FloorMesh" type="MeshInstance3D" parent="Floor"]
-mesh = SubResource("2")
-
-[node name="FloorCollision" type="CollisionShape3D" parent="Floor"]
-shape = SubResource("3")
-"#.to_string(),
-            r#"extends CharacterBody3D
-class_name Player
-## First Person Player - Uses Entity-Component Pattern
-
-@export var speed: float = 5.0
-@export var mouse_sensitivity: float = 0.002
-
-var gravity: float = ProjectSettings.get_setting("physics/3d/default_gravity")
-@onready var camera: Camera3D = $Camera3D
-@onready var health_comp: HealthComponent = $HealthComponent
-
-func _ready() -> void:
-	# Don't capture mouse in _ready - wait for click (required for web)
-	if health_comp:
-		health_comp.died.connect(_on_died)
-		health_comp.health_changed.connect(_on_health_changed)
-	EventBus.player_spawned.emit(self)
-	print("Click to capture mouse, WASD to move, ESC to release")
-
-func _input(event: InputEvent) -> void:
-	# Capture mouse on click (web-compatible)
-	if event is InputEventMouseButton and event.pressed and event.button_index == MOUSE_BUTTON_LEFT:
-		if Input.mouse_mode != Input.MOUSE_MODE_CAPTURED:
-			Input.mouse_mode = Input.MOUSE_MODE_CAPTURED
-	
-	if event is InputEventMouseMotion and Input.mouse_mode == Input.MOUSE_MODE_CAPTURED:
-		rotate_y(-event.relative.x * mouse_sensitivity)
-		camera.rotate_x(-event.relative.y * mouse_sensitivity)
-		camera.rotation.x = clamp(camera.rotation.x, -PI/2, PI/2)
-	
-	if event.is_action_pressed("ui_cancel"):
-		Input.mouse_mode = Input.MOUSE_MODE_VISIBLE
-
-func _physics_process(delta: float) -> void:
-	if not is_on_floor():
-		velocity.y -= gravity * delta
-	
-	var input_dir := Input.get_vector("move_left", "move_right", "move_up", "move_down")
-	var direction := (transform.basis * Vector3(input_dir.x, 0, input_dir.y)).normalized()
-	
-	velocity.x = direction.x * speed if direction else move_toward(velocity.x, 0, speed)
-	velocity.z = direction.z * speed if direction else move_toward(velocity.z, 0, speed)
-	
-	move_and_slide()
-
-func take_damage(amount: int) -> void:
-	if health_comp:
-		health_comp.take_damage(amount)
-
-func _on_health_changed(current: int, maximum: int) -> void:
-	EventBus.health_changed.emit(current, maximum)
-
-func _on_died() -> void:
-	EventBus.player_died.emit()
-"#.to_string()
-        ),
-        ("2d", "puzzle") | ("3d", "puzzle") => {
-            let node_type = if dimension == "3d" { "Node3D" } else { "Node2D" };
-            (
-                format!(r#"[gd_scene load_steps=2 format=3]
-
-[ext_resource type="Script" path="res://assets/entities/player/player.gd" id="1"]
-
-[node name="Main" type="{}"]
-script = ExtResource("1")
-
-[node name="UI" type="CanvasLayer" parent="."]
-
-[node name="ScoreLabel" type="Label" parent="UI"]
-offset_right = 200.0
-offset_bottom = 40.0
-text = "Score: 0"
-"#, node_type),
-                r#"extends Node
-## Puzzle Game - Uses EventBus for score tracking
-
-func _ready() -> void:
-	# Listen to score changes from GameState
-	EventBus.score_changed.connect(_on_score_changed)
-	EventBus.coin_collected.connect(_on_coin_collected)
-	_update_display()
-	print("Puzzle ready! Space to add points, uses EventBus + GameState")
-
-func _input(event: InputEvent) -> void:
-	if event.is_action_pressed("ui_accept"):
-		GameState.add_score(10)
-		EventBus.coin_collected.emit(10)
-
-func _on_score_changed(new_score: int) -> void:
-	_update_display()
-
-func _on_coin_collected(value: int) -> void:
-	print("Collected: %d points!" % value)
-
-func _update_display() -> void:
-	$UI/ScoreLabel.text = "Score: %d" % GameState.score
-"#.to_string()
-            )
-        },
-        _ => {
-            // Empty project with professional architecture
-            let node_type = if dimension == "3d" { "Node3D" } else { "Node2D" };
-            (
-                format!(r#"[gd_scene load_steps=2 format=3]
-
-[ext_resource type="Script" path="res://assets/entities/player/player.gd" id="1"]
-
-[node name="Main" type="{}"]
-script = ExtResource("1")
-"#, node_type),
-                format!(r#"extends {}
-## Empty {} Project - Professional Architecture Ready
-## EventBus, GameState, and Components are pre-configured
-
-func _ready() -> void:
-	# EventBus is ready for cross-system communication
-	# GameState persists data across scenes
-	# Components in src/components/ are ready to use
-	
-	# Example: Listen to game events
-	EventBus.player_spawned.connect(func(p): print("Player spawned: ", p))
-	EventBus.level_completed.connect(func(): print("Level done!"))
-	
-	print("Hello from Kobold! Architecture ready.")
-	print("- EventBus: Signal bus for decoupled communication")
-	print("- GameState: Persistent cross-scene data")
-	print("- Components: HealthComponent, MovementComponent2D")
-"#, node_type, if dimension == "3d" { "3D" } else { "2D" })
-            )
+/// Write any additional files a template needs (e.g. spawnable scenes)
+fn write_template_extra_files(project: &Path, dimension: &str, template: &str) -> Result<(), String> {
+    let Some(tpl) = find_template(template, dimension) else {
+        return Ok(());
+    };
+    for (rel_path, content) in &tpl.files {
+        let target = project.join(rel_path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
         }
+        fs::write(&target, content).map_err(|e| format!("Failed to write {}: {}", rel_path, e))?;
     }
+    Ok(())
+}
+
+/// Resolve a template's scene and player script from the registry, falling back
+/// to the empty project when the id/dimension combination is unknown
+fn generate_template_files(dimension: &str, template: &str) -> (String, String) {
+    let tpl = find_template(template, dimension).unwrap_or_else(|| empty_template(dimension));
+    (tpl.scene, tpl.player_script)
+}
+
+#[tauri::command]
+fn list_templates() -> Vec<TemplateMeta> {
+    list_template_meta()
 }
 
 // ============================================================================
@@ -1625,12 +2623,8 @@ func _ready() -> void:
 
 #[tauri::command]
 fn run_godot(project_path: String, state: tauri::State<AppState>) -> Result<String, String> {
-    let settings = state.settings.lock().unwrap();
-    let godot_cmd = settings
-        .godot_path
-        .clone()
-        .filter(|p| !p.is_empty() && Path::new(p).exists())
-        .or_else(|| find_godot_path())
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = project_godot_cmd(Path::new(&project_path), saved)?
         .ok_or("Godot not found. Please install Godot first.")?;
 
     // Run Godot with the project (opens game window)
@@ -1652,9 +2646,13 @@ fn get_godot_version(godot_cmd: &str) -> Result<String, String> {
         .output()
         .map_err(|e| format!("Failed to get Godot version: {}", e))?;
     
-    let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    // Version looks like "4.3.stable.official.77dcf97d8"
-    // We need "4.3.stable" for the templates folder
+    parse_godot_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// "4.3.stable" from `--version` output like "4.3.stable.official.77dcf97d8"
+/// (the form the export templates folder uses)
+fn parse_godot_version(output: &str) -> Result<String, String> {
+    let version_str = output.trim();
     let parts: Vec<&str> = version_str.split('.').collect();
     if parts.len() >= 3 {
         Ok(format!("{}.{}.{}", parts[0], parts[1], parts[2]))
@@ -1663,6 +2661,69 @@ fn get_godot_version(godot_cmd: &str) -> Result<String, String> {
     }
 }
 
+/// A version string from parse_godot_version, accepted only for Godot 4.x
+fn require_godot_4(version: &str) -> Result<(), String> {
+    let mut numbers = version.split('.').map(|part| part.parse::<u32>());
+    match (numbers.next(), numbers.next()) {
+        (Some(Ok(4)), Some(Ok(_))) => Ok(()),
+        (Some(Ok(_)), Some(Ok(_))) => Err(format!("Godot {} is not supported; Godot 4.x is required", version)),
+        _ => Err(format!("Not a Godot version: {}", version)),
+    }
+}
+
+/// Binary inside a macOS app bundle, so users can pick `Godot.app` itself
+fn resolve_godot_binary(path: &Path) -> PathBuf {
+    if path.extension().is_some_and(|e| e == "app") {
+        let binary = path.join("Contents/MacOS/Godot");
+        if binary.exists() {
+            return binary;
+        }
+    }
+    path.to_path_buf()
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Binary path and version of a runnable Godot 4.x at `path`
+fn validate_godot_binary(path: &str) -> Result<(String, String), String> {
+    let binary = resolve_godot_binary(Path::new(path.trim()));
+    if !binary.exists() {
+        return Err(format!("Path does not exist: {}", binary.display()));
+    }
+    if !is_executable(&binary) {
+        return Err(format!("Not an executable file: {}", binary.display()));
+    }
+    let binary = binary.to_string_lossy().to_string();
+    let version = get_godot_version(&binary)?;
+    require_godot_4(&version)?;
+    Ok((binary, version))
+}
+
+/// Check that `path` is a runnable Godot 4.x binary and save it in settings,
+/// overriding what detect_godot found. Returns the version it reports.
+#[tauri::command]
+fn set_godot_path(path: String, state: tauri::State<AppState>) -> Result<String, String> {
+    let (binary, version) = validate_godot_binary(&path)?;
+
+    state.settings.lock().unwrap().godot_path = Some(binary.clone());
+    save_settings_to_disk(&AppSettings {
+        godot_path: Some(binary.clone()),
+        ..Default::default()
+    })?;
+    println!("[Godot] Using {} (Godot {})", binary, version);
+    Ok(version)
+}
+
 fn get_export_templates_path(version: &str) -> Option<std::path::PathBuf> {
     #[cfg(windows)]
     {
@@ -1690,16 +2751,17 @@ struct SetupStatus {
     templates_installed: bool,
 }
 
+/// Godot and web template status, for `project_path`'s pinned Godot when given
 #[tauri::command]
-fn check_setup_status(state: tauri::State<AppState>) -> SetupStatus {
-    let settings = state.settings.lock().unwrap().clone();
-    let godot_path = settings
-        .godot_path
-        .filter(|p| !p.is_empty() && Path::new(p).exists())
-        .or_else(|| find_godot_path());
+fn check_setup_status(project_path: Option<String>, state: tauri::State<AppState>) -> SetupStatus {
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_path = optional_project_godot_cmd(project_path.as_deref(), saved).unwrap_or_else(|e| {
+        println!("[Setup] {}", e);
+        None
+    });
     
     let (godot_version, templates_installed) = if let Some(ref path) = godot_path {
-        match get_godot_version(path) {
+        match export_templates_version(project_path.as_deref(), path) {
             Ok(version) => {
                 let templates = check_web_templates_installed(&version);
                 (Some(version), templates)
@@ -1758,20 +2820,19 @@ fn check_web_templates_installed(version: &str) -> bool {
     }
 }
 
+/// Download web export templates for the Godot in use (`project_path`'s pinned
+/// version when given)
 #[tauri::command]
-async fn ensure_export_templates(state: tauri::State<'_, AppState>) -> Result<String, String> {
+async fn ensure_export_templates(project_path: Option<String>, app: tauri::AppHandle, state: tauri::State<'_, AppState>) -> Result<String, String> {
     println!("[ensure_export_templates] Starting...");
     
-    let settings = state.settings.lock().unwrap().clone();
-    let godot_cmd = settings
-        .godot_path
-        .filter(|p| !p.is_empty() && Path::new(p).exists())
-        .or_else(|| find_godot_path())
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = optional_project_godot_cmd(project_path.as_deref(), saved)?
         .ok_or("Godot not found")?;
     
     println!("[ensure_export_templates] Godot: {}", godot_cmd);
     
-    let version = get_godot_version(&godot_cmd)?;
+    let version = export_templates_version(project_path.as_deref(), &godot_cmd)?;
     println!("[ensure_export_templates] Version: {}", version);
     
     if check_web_templates_installed(&version) {
@@ -1789,10 +2850,9 @@ async fn ensure_export_templates(state: tauri::State<'_, AppState>) -> Result<St
     }
     
     let download_version = format!("{}.{}-{}", version_parts[0], version_parts[1], version_parts[2]);
-    let url = format!(
-        "https://github.com/godotengine/godot/releases/download/{}/Godot_v{}_export_templates.tpz",
-        download_version, download_version
-    );
+    let release_url = format!("https://github.com/godotengine/godot/releases/download/{}", download_version);
+    let tpz_name = format!("Godot_v{}_export_templates.tpz", download_version);
+    let url = format!("{}/{}", release_url, tpz_name);
     
     println!("[ensure_export_templates] Download URL: {}", url);
     
@@ -1840,18 +2900,28 @@ async fn ensure_export_templates(state: tauri::State<'_, AppState>) -> Result<St
     let mut downloaded: u64 = 0;
     let mut last_percent = 0u64;
     let mut stream = response.bytes_stream();
+    let mut sha512 = sha2::Sha512::new();
     
     use futures_util::StreamExt;
     while let Some(chunk) = stream.next().await {
         let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
         file.write_all(&chunk).await.map_err(|e| format!("Write error: {}", e))?;
+        sha512.update(&chunk);
         downloaded += chunk.len() as u64;
         
         if total_size > 0 {
             let percent = (downloaded * 100) / total_size;
-            if percent > last_percent && percent % 10 == 0 {
-                println!("[ensure_export_templates] Downloaded {}%", percent);
+            if percent > last_percent {
+                if percent % 10 == 0 {
+                    println!("[ensure_export_templates] Downloaded {}%", percent);
+                }
                 last_percent = percent;
+                let _ = app.emit("download-progress", DownloadProgress {
+                    asset: "export_templates".to_string(),
+                    downloaded,
+                    total: total_size,
+                    percent: percent as u8,
+                });
             }
         }
     }
@@ -1859,6 +2929,28 @@ async fn ensure_export_templates(state: tauri::State<'_, AppState>) -> Result<St
     file.flush().await.map_err(|e| format!("Flush error: {}", e))?;
     drop(file);
     
+    // Verify the download before extracting; the temp file is kept on failure
+    if total_size > 0 && downloaded != total_size {
+        return Err(format!(
+            "Template download incomplete: got {} of {} bytes. Please retry.",
+            downloaded, total_size
+        ));
+    }
+    let digest = format!("{:x}", sha512.finalize());
+    match client.get(format!("{}/SHA512-SUMS.txt", release_url)).send().await {
+        Ok(resp) if resp.status().is_success() => {
+            let sums = resp.text().await.unwrap_or_default();
+            match expected_sha512(&sums, &tpz_name) {
+                Some(expected) if !expected.eq_ignore_ascii_case(&digest) => {
+                    return Err(format!("Template download is corrupt (SHA-512 mismatch for {}). Please retry.", tpz_name));
+                }
+                Some(_) => println!("[ensure_export_templates] Checksum verified"),
+                None => println!("[ensure_export_templates] No checksum listed for {}", tpz_name),
+            }
+        }
+        other => println!("[ensure_export_templates] Checksums unavailable, skipping: {:?}", other.map(|r| r.status())),
+    }
+    
     println!("[ensure_export_templates] Download complete, extracting...");
     
     // Extract the .tpz (it's a zip file)
@@ -1868,7 +2960,21 @@ async fn ensure_export_templates(state: tauri::State<'_, AppState>) -> Result<St
     let mut archive = zip::ZipArchive::new(file)
         .map_err(|e| format!("Failed to read templates archive: {}", e))?;
     
+    // Extraction progress counts archive entries rather than bytes
+    let entry_count = archive.len() as u64;
+    let mut last_percent = 0u8;
     for i in 0..archive.len() {
+        let percent = ((i as u64 + 1) * 100 / entry_count.max(1)) as u8;
+        if percent > last_percent {
+            last_percent = percent;
+            let _ = app.emit("download-progress", DownloadProgress {
+                asset: "export_templates".to_string(),
+                downloaded: i as u64 + 1,
+                total: entry_count,
+                percent,
+            });
+        }
+        
         let mut file = archive.by_index(i)
             .map_err(|e| format!("Failed to read archive entry: {}", e))?;
         
@@ -1892,57 +2998,416 @@ async fn ensure_export_templates(state: tauri::State<'_, AppState>) -> Result<St
         }
     }
     
+    if !check_web_templates_installed(&version) {
+        return Err(format!(
+            "Export templates for {} were extracted but the web templates are missing from {}",
+            version,
+            templates_dir.display()
+        ));
+    }
+    
     // Clean up temp file
     fs::remove_file(&temp_path).ok();
     
     Ok(format!("Export templates installed for {}", version))
 }
 
-#[tauri::command]
-fn clear_export_cache(project_path: String) -> Result<(), String> {
-    let kobold_dir = Path::new(&project_path).join(".tav");
-    if kobold_dir.exists() {
-        fs::remove_dir_all(&kobold_dir)
-            .map_err(|e| format!("Failed to clear cache: {}", e))?;
-        println!("[Cache] Cleared .tav directory");
-    }
-    Ok(())
+/// Look up a file's digest in a `sha512sum`-style listing ("<hex>  <file>")
+fn expected_sha512(sums: &str, file_name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (hash, name) = line.trim().split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == file_name).then(|| hash.to_string())
+    })
 }
 
-fn inject_kobold_bridge(project: &Path) -> Result<(), String> {
-    // Write Kobold Bridge script to .tav folder
-    let kobold_dir = project.join(".tav");
-    fs::create_dir_all(&kobold_dir).ok();
-    
-    let bridge_path = kobold_dir.join("kobold_bridge.gd");
-    fs::write(&bridge_path, KOBOLD_BRIDGE_GD)
-        .map_err(|e| format!("Failed to write Kobold Bridge: {}", e))?;
-    
-    // Add to project.godot autoloads if not already present
+const DISK_USAGE_LARGEST_FILES: usize = 10;
+
+const SOURCE_EXTENSIONS: &[&str] = &["gd", "tscn", "tres", "godot", "cfg", "gdshader", "cs", "json", "md"];
+const ASSET_EXTENSIONS: &[&str] = &["glb", "gltf", "fbx", "obj", "wav", "ogg", "mp3", "ttf", "otf"];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsageByCategory {
+    pub assets: u64,
+    /// .tav: web/release exports, thumbnails, template updates
+    pub exports: u64,
+    pub godot_cache: u64,
+    pub source: u64,
+    pub other: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsageFile {
+    pub path: String,
+    pub size: u64,
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDiskUsage {
+    pub total: u64,
+    pub by_category: DiskUsageByCategory,
+    /// Part of `total` under entries the file tree hides (.godot, .tav, .git, node_modules, ...)
+    pub ignored: u64,
+    pub largest_files: Vec<DiskUsageFile>,
+}
+
+/// Category of a file by its project-relative path
+fn disk_category(rel: &Path) -> &'static str {
+    match rel.components().next().and_then(|c| c.as_os_str().to_str()) {
+        Some(".godot") => return "godotCache",
+        Some(".tav") => return "exports",
+        Some("assets") => return "assets",
+        _ => {}
+    }
+    let ext = rel.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    if SOURCE_EXTENSIONS.contains(&ext.as_str()) && !rel.components().any(|c| is_ignored_entry(&c.as_os_str().to_string_lossy())) {
+        "source"
+    } else if THUMBNAIL_IMAGE_EXTS.contains(&ext.as_str()) || ASSET_EXTENSIONS.contains(&ext.as_str()) {
+        "assets"
+    } else {
+        "other"
+    }
+}
+
+/// Every regular file under `dir` with its size, skipping entries whose name
+/// matches `skip`. Uses symlink_metadata so linked folders aren't counted twice
+/// (or looped through).
+fn walk_files(dir: &Path, skip: impl Fn(&str) -> bool) -> Vec<(PathBuf, u64)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            if skip(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let path = entry.path();
+            let Ok(meta) = fs::symlink_metadata(&path) else { continue };
+            if meta.is_dir() {
+                pending.push(path);
+            } else if meta.is_file() {
+                files.push((path, meta.len()));
+            }
+        }
+    }
+    files
+}
+
+/// Sort biggest first (ties by path) and keep the first `limit`
+fn keep_largest<T>(items: &mut Vec<T>, key: fn(&T) -> (u64, &str), limit: usize) {
+    items.sort_by(|a, b| {
+        let (a, b) = (key(a), key(b));
+        b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1))
+    });
+    items.truncate(limit);
+}
+
+/// Bytes used by the project, split by category, with its largest files
+#[tauri::command]
+fn get_project_disk_usage(project_path: String) -> Result<ProjectDiskUsage, String> {
+    let root = Path::new(&project_path);
+    if !root.is_dir() {
+        return Err(format!("Project not found: {}", project_path));
+    }
+    let mut usage = ProjectDiskUsage {
+        total: 0,
+        by_category: DiskUsageByCategory::default(),
+        ignored: 0,
+        largest_files: vec![],
+    };
+    let mut files = Vec::new();
+    for (path, size) in walk_files(root, |_| false) {
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        let category = disk_category(rel);
+        let by = &mut usage.by_category;
+        *match category {
+            "assets" => &mut by.assets,
+            "exports" => &mut by.exports,
+            "godotCache" => &mut by.godot_cache,
+            "source" => &mut by.source,
+            _ => &mut by.other,
+        } += size;
+        usage.total += size;
+        if rel.components().any(|c| is_ignored_entry(&c.as_os_str().to_string_lossy())) {
+            usage.ignored += size;
+        }
+        files.push(DiskUsageFile {
+            path: rel.to_string_lossy().replace('\\', "/"),
+            size,
+            category: category.to_string(),
+        });
+    }
+    keep_largest(&mut files, |f| (f.size, &f.path), DISK_USAGE_LARGEST_FILES);
+    usage.largest_files = files;
+    Ok(usage)
+}
+
+#[tauri::command]
+fn clear_export_cache(project_path: String) -> Result<(), String> {
+    let kobold_dir = Path::new(&project_path).join(".tav");
+    if kobold_dir.exists() {
+        fs::remove_dir_all(&kobold_dir)
+            .map_err(|e| format!("Failed to clear cache: {}", e))?;
+        println!("[Cache] Cleared .tav directory");
+    }
+    Ok(())
+}
+
+/// Import caches under .godot/ that are safe to drop; the rest (editor layout,
+/// uid cache, global class list) is left alone
+const GODOT_CACHE_DIRS: [&str; 2] = [".godot/imported", ".godot/shader_cache"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GodotCacheCleared {
+    pub removed: Vec<String>,
+    pub freed_bytes: u64,
+    pub reimported: bool,
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    walk_files(dir, |_| false).iter().map(|(_, size)| size).sum()
+}
+
+/// Delete Godot's import and shader caches (fixes corrupted imports) and
+/// optionally reimport headlessly right away
+#[tauri::command]
+fn clear_godot_cache(project_path: String, reimport: Option<bool>, state: tauri::State<AppState>) -> Result<GodotCacheCleared, String> {
+    let project = Path::new(&project_path);
+    if !project.join("project.godot").is_file() {
+        return Err(format!("Not a Godot project (project.godot missing): {}", project_path));
+    }
+
+    let mut removed = Vec::new();
+    let mut freed_bytes = 0;
+    for rel in GODOT_CACHE_DIRS {
+        let dir = project.join(rel);
+        if !dir.exists() {
+            continue;
+        }
+        let size = dir_size(&dir);
+        fs::remove_dir_all(&dir).map_err(|e| format!("Failed to remove {}: {}", rel, e))?;
+        freed_bytes += size;
+        removed.push(rel.to_string());
+    }
+    println!("[Cache] Cleared {:?} ({} bytes)", removed, freed_bytes);
+
+    let mut reimported = false;
+    if reimport.unwrap_or(false) {
+        let saved = state.settings.lock().unwrap().godot_path.clone();
+        let godot_cmd = project_godot_cmd(project, saved)?.ok_or("Godot not found")?;
+        let output = silent_cmd(&godot_cmd, &["--headless", "--path", &project_path, "--import"])
+            .map_err(|e| format!("Failed to run Godot: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("Reimport failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        reimported = true;
+        println!("[Cache] Reimported {}", project_path);
+    }
+
+    Ok(GodotCacheCleared { removed, freed_bytes, reimported })
+}
+
+/// One `[section]` of project.godot with its raw lines; the unnamed section
+/// holds anything before the first header (e.g. `config_version=5`)
+pub struct GodotSection {
+    pub name: String,
+    lines: Vec<String>,
+}
+
+/// project.godot split into sections so entries can be edited without touching
+/// unrelated text. Values are kept verbatim, including multi-line `{...}` blocks.
+pub struct GodotConfig {
+    pub sections: Vec<GodotSection>,
+}
+
+/// Net bracket depth change for a line, ignoring brackets inside strings
+fn godot_bracket_delta(line: &str) -> i32 {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in line.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' | '[' | '(' if !in_string => depth += 1,
+            '}' | ']' | ')' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+fn is_godot_comment(line: &str) -> bool {
+    let t = line.trim_start();
+    t.starts_with(';') || t.starts_with('#')
+}
+
+fn parse_godot_config(content: &str) -> GodotConfig {
+    let mut sections = vec![GodotSection { name: String::new(), lines: Vec::new() }];
+    let mut depth = 0;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        // Headers only count outside multi-line values and comments
+        if depth == 0 && !is_godot_comment(line) && trimmed.starts_with('[') && trimmed.ends_with(']') {
+            sections.push(GodotSection {
+                name: trimmed[1..trimmed.len() - 1].trim().to_string(),
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        if !is_godot_comment(line) {
+            depth = (depth + godot_bracket_delta(line)).max(0);
+        }
+        sections.last_mut().unwrap().lines.push(line.to_string());
+    }
+    GodotConfig { sections }
+}
+
+fn write_godot_config(config: &GodotConfig) -> String {
+    let mut out = String::new();
+    for section in &config.sections {
+        if !section.name.is_empty() {
+            out.push_str(&format!("[{}]\n", section.name));
+        }
+        for line in &section.lines {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+impl GodotConfig {
+    pub fn section(&self, name: &str) -> Option<&GodotSection> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Get a section, creating it before the first of `before` that exists (or at the end)
+    pub fn section_or_insert(&mut self, name: &str, before: &[&str]) -> &mut GodotSection {
+        if let Some(index) = self.sections.iter().position(|s| s.name == name) {
+            return &mut self.sections[index];
+        }
+        let index = self.sections.iter()
+            .position(|s| before.contains(&s.name.as_str()))
+            .unwrap_or(self.sections.len());
+
+        // Keep a blank line between the previous section and the new header
+        if let Some(prev) = index.checked_sub(1).and_then(|i| self.sections.get_mut(i)) {
+            if prev.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                prev.lines.push(String::new());
+            }
+        }
+        self.sections.insert(index, GodotSection {
+            name: name.to_string(),
+            lines: vec![String::new()],
+        });
+        &mut self.sections[index]
+    }
+}
+
+impl GodotSection {
+    /// Line range of a `key=value` entry, including continuation lines of multi-line values
+    fn entry_range(&self, key: &str) -> Option<(usize, usize)> {
+        let prefix = format!("{}=", key);
+        let mut depth = 0;
+        let mut start = None;
+        for (i, line) in self.lines.iter().enumerate() {
+            if depth == 0 {
+                if let Some(start) = start {
+                    return Some((start, i));
+                }
+                if !is_godot_comment(line) && line.starts_with(&prefix) {
+                    start = Some(i);
+                }
+            }
+            if !is_godot_comment(line) {
+                depth = (depth + godot_bracket_delta(line)).max(0);
+            }
+        }
+        start.map(|start| (start, self.lines.len()))
+    }
+
+    /// Raw value of an entry, e.g. `"*res://autoload/event_bus.gd"`
+    pub fn get(&self, key: &str) -> Option<String> {
+        let (start, end) = self.entry_range(key)?;
+        let text = self.lines[start..end].join("\n");
+        Some(text[key.len() + 1..].to_string())
+    }
+
+    /// Replace an entry in place, or append it after the section's last non-blank line
+    pub fn set(&mut self, key: &str, value: &str) {
+        let entry: Vec<String> = format!("{}={}", key, value).lines().map(str::to_string).collect();
+        match self.entry_range(key) {
+            Some((start, end)) => {
+                self.lines.splice(start..end, entry);
+            }
+            None => {
+                let insert_at = self.lines.iter()
+                    .rposition(|l| !l.trim().is_empty())
+                    .map(|i| i + 1)
+                    .unwrap_or(self.lines.len().min(1));
+                self.lines.splice(insert_at..insert_at, entry);
+                // Sections are separated by a blank line
+                if self.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                    self.lines.push(String::new());
+                }
+            }
+        }
+    }
+
+    /// Remove an entry, returning whether it existed
+    pub fn remove(&mut self, key: &str) -> bool {
+        match self.entry_range(key) {
+            Some((start, end)) => {
+                self.lines.drain(start..end);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Register an autoload in project.godot content if it isn't already present
+fn add_autoload(content: &str, name: &str, script_path: &str) -> String {
+    let mut config = parse_godot_config(content);
+    let section = config.section_or_insert("autoload", &["input", "rendering"]);
+    if section.get(name).is_none() {
+        section.set(name, &format!("\"*{}\"", script_path));
+    }
+    write_godot_config(&config)
+}
+
+/// Remove an autoload from project.godot content, leaving the rest untouched
+fn remove_autoload(content: &str, name: &str) -> String {
+    let mut config = parse_godot_config(content);
+    let removed = config.sections.iter_mut()
+        .find(|s| s.name == "autoload")
+        .is_some_and(|section| section.remove(name));
+    if removed { write_godot_config(&config) } else { content.to_string() }
+}
+
+fn inject_kobold_bridge(project: &Path) -> Result<(), String> {
+    // Write Kobold Bridge script to .tav folder
+    let kobold_dir = project.join(".tav");
+    fs::create_dir_all(&kobold_dir).ok();
+    
+    let bridge_path = kobold_dir.join("kobold_bridge.gd");
+    fs::write(&bridge_path, KOBOLD_BRIDGE_GD)
+        .map_err(|e| format!("Failed to write Kobold Bridge: {}", e))?;
+    
+    // Add to project.godot autoloads if not already present
     let project_file = project.join("project.godot");
     if project_file.exists() {
         let content = fs::read_to_string(&project_file)
             .map_err(|e| format!("Failed to read project.godot: {}", e))?;
         
-        if !content.contains("KoboldBridge") {
-            // Find [autoload] section and add our bridge
-            let new_content = if content.contains("[autoload]") {
-                content.replace(
-                    "[autoload]",
-                    "[autoload]\n\nKoboldBridge=\"*res://.tav/kobold_bridge.gd\""
-                )
-            } else {
-                // Add autoload section before [input] or at end
-                if content.contains("[input]") {
-                    content.replace(
-                        "[input]",
-                        "[autoload]\n\nKoboldBridge=\"*res://.tav/kobold_bridge.gd\"\n\n[input]"
-                    )
-                } else {
-                    format!("{}\n\n[autoload]\n\nKoboldBridge=\"*res://.tav/kobold_bridge.gd\"\n", content)
-                }
-            };
-            
+        let new_content = add_autoload(&content, "KoboldBridge", "res://.tav/kobold_bridge.gd");
+        if new_content != content {
             fs::write(&project_file, new_content)
                 .map_err(|e| format!("Failed to update project.godot: {}", e))?;
             
@@ -1953,14 +3418,95 @@ fn inject_kobold_bridge(project: &Path) -> Result<(), String> {
     Ok(())
 }
 
+/// Undo `inject_kobold_bridge` so a release export ships without testing instrumentation
+#[tauri::command]
+fn strip_kobold_bridge(project_path: String) -> Result<(), String> {
+    let project = Path::new(&project_path);
+    let project_file = project.join("project.godot");
+    if project_file.exists() {
+        let content = fs::read_to_string(&project_file)
+            .map_err(|e| format!("Failed to read project.godot: {}", e))?;
+
+        let new_content = remove_autoload(&content, "KoboldBridge");
+        if new_content != content {
+            fs::write(&project_file, new_content)
+                .map_err(|e| format!("Failed to update project.godot: {}", e))?;
+            println!("[Export] Removed KoboldBridge autoload");
+        }
+    }
+
+    // Bridge script plus any leftover capture/input exchange files
+    let kobold_dir = project.join(".tav");
+    for file in ["kobold_bridge.gd", "agent_input.json", "capture_request.json", "capture_result.json"] {
+        let path = kobold_dir.join(file);
+        if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove {}: {}", file, e))?;
+        }
+    }
+
+    Ok(())
+}
+
 // Version bump this when bridge code changes to invalidate caches
-const KOBOLD_BRIDGE_VERSION: u32 = 4;
+const KOBOLD_BRIDGE_VERSION: u32 = 11;
 
 // Template version - bump when template files change to trigger auto-sync
 const TEMPLATE_VERSION: &str = "1.0.0";
 
-/// Check if project's template needs updating and sync if so
-fn sync_template_if_needed(project: &Path) -> Result<(), String> {
+/// Parse a "major.minor.patch" version; missing components default to 0 and
+/// pre-release/build suffixes ("1.2.0-beta") are ignored
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next().unwrap_or("");
+    let mut parts = core.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map(|p| p.parse().ok()).unwrap_or(Some(0))?;
+    let patch = parts.next().map(|p| p.parse().ok()).unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// Compare two semantic versions, `None` if either can't be parsed
+fn compare_versions(a: &str, b: &str) -> Option<std::cmp::Ordering> {
+    Some(parse_version(a)?.cmp(&parse_version(b)?))
+}
+
+/// Template-managed files that `sync_template_if_needed` keeps up to date
+const TEMPLATE_SYNCED_FILES: [&str; 2] = ["scenes/main.tscn", "assets/entities/player/player.gd"];
+
+/// SHA-256 hex digest of file content, used to detect user edits to template files
+fn content_hash(content: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hashes of freshly written template files, stored in template_info.json
+fn template_file_hashes(scene: &str, player: &str) -> serde_json::Value {
+    serde_json::json!({
+        TEMPLATE_SYNCED_FILES[0]: content_hash(scene.as_bytes()),
+        TEMPLATE_SYNCED_FILES[1]: content_hash(player.as_bytes()),
+    })
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TemplateUpdate {
+    project_path: String,
+    template: String,
+    from_version: String,
+    to_version: String,
+    files: Vec<String>,
+    updates_dir: String,
+}
+
+/// Check if project's template needs updating and sync if so.
+/// Files the user has edited since they were generated are left alone; the new
+/// version goes to .tav/template_updates/ and a `template-update-available` event is emitted.
+fn sync_template_if_needed(project: &Path, app: &tauri::AppHandle) -> Result<(), String> {
     let template_info_path = project.join(".tav/template_info.json");
     
     // Read existing template info
@@ -1968,65 +3514,220 @@ fn sync_template_if_needed(project: &Path) -> Result<(), String> {
         .ok()
         .and_then(|s| serde_json::from_str(&s).ok());
     
-    let (template_id, dimension, stored_version) = match &template_info {
-        Some(info) => (
-            info["template"].as_str().unwrap_or("").to_string(),
-            info["dimension"].as_str().unwrap_or("3d").to_string(),
-            info["version"].as_str().unwrap_or("0.0.0"),
-        ),
+    let mut info = match template_info {
+        Some(info) => info,
         None => return Ok(()), // No template info, skip sync (legacy project)
     };
-    
-    // Check if update needed
-    if stored_version == TEMPLATE_VERSION {
+    let template_id = info["template"].as_str().unwrap_or("").to_string();
+    let dimension = info["dimension"].as_str().unwrap_or("3d").to_string();
+    let stored_version = info["version"].as_str().unwrap_or("0.0.0").to_string();
+
+    // Projects created from a custom template directory are owned by the user
+    if info["source"].as_str() == Some("custom") {
         return Ok(());
     }
+
+    // Only sync when the project's template is older than the app's
+    match compare_versions(&stored_version, TEMPLATE_VERSION) {
+        Some(std::cmp::Ordering::Less) => {}
+        Some(std::cmp::Ordering::Equal) => return Ok(()),
+        Some(std::cmp::Ordering::Greater) => {
+            println!(
+                "[Template] Project template v{} is newer than app template v{} - update the app",
+                stored_version, TEMPLATE_VERSION
+            );
+            return Ok(());
+        }
+        None => {
+            println!("[Template] Unrecognized template version '{}', skipping sync", stored_version);
+            return Ok(());
+        }
+    }
     
     println!("[Template] Auto-syncing {} from v{} to v{}", template_id, stored_version, TEMPLATE_VERSION);
     
     // Get template files
     let (scene_content, player_content) = generate_template_files(&dimension, &template_id);
-    
-    // Sync scene and player files
-    fs::write(project.join("scenes/main.tscn"), &scene_content)
-        .map_err(|e| format!("Failed to sync main.tscn: {}", e))?;
-    fs::write(project.join("assets/entities/player/player.gd"), &player_content)
-        .map_err(|e| format!("Failed to sync player.gd: {}", e))?;
+    let new_hashes = template_file_hashes(&scene_content, &player_content);
+    let mut stored_hashes = info["file_hashes"].clone();
+    if !stored_hashes.is_object() {
+        stored_hashes = serde_json::json!({});
+    }
+    
+    // Sync scene and player files unless the user has modified them
+    let updates_dir = project.join(".tav/template_updates");
+    let mut deferred: Vec<String> = Vec::new();
+    for (rel_path, new_content) in TEMPLATE_SYNCED_FILES.iter().zip([&scene_content, &player_content]) {
+        let target = project.join(rel_path);
+        let on_disk = fs::read(&target).ok();
+        let on_disk_hash = on_disk.as_deref().map(content_hash);
+        
+        if on_disk_hash.as_deref() == new_hashes[*rel_path].as_str() {
+            stored_hashes[*rel_path] = new_hashes[*rel_path].clone();
+            continue;
+        }
+        
+        // Unmodified = matches the hash recorded when it was generated (or missing entirely)
+        let unmodified = match (&on_disk_hash, stored_hashes[*rel_path].as_str()) {
+            (None, _) => true,
+            (Some(current), Some(stored)) => current == stored,
+            (Some(_), None) => false,
+        };
+        
+        if unmodified {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).ok();
+            }
+            fs::write(&target, new_content)
+                .map_err(|e| format!("Failed to sync {}: {}", rel_path, e))?;
+            stored_hashes[*rel_path] = new_hashes[*rel_path].clone();
+        } else {
+            let update_path = updates_dir.join(rel_path);
+            if let Some(parent) = update_path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create template_updates: {}", e))?;
+            }
+            fs::write(&update_path, new_content)
+                .map_err(|e| format!("Failed to write template update for {}: {}", rel_path, e))?;
+            println!("[Template] {} has local changes, new version written to {}", rel_path, update_path.display());
+            deferred.push(rel_path.to_string());
+        }
+    }
     
     // Ensure critical inputs exist in project.godot
     let project_godot_path = project.join("project.godot");
     if project_godot_path.exists() {
         let content = fs::read_to_string(&project_godot_path).unwrap_or_default();
-        if !content.contains("sprint=") {
-            // Add sprint input before [rendering] section
-            let sprint_input = r#"sprint={
-"deadzone": 0.5,
-"events": [Object(InputEventKey,"resource_local_to_scene":false,"resource_name":"","device":-1,"window_id":0,"alt_pressed":false,"shift_pressed":false,"ctrl_pressed":false,"meta_pressed":false,"pressed":false,"keycode":0,"physical_keycode":4194325,"key_label":0,"unicode":0,"location":0,"echo":false,"script":null)]
-}
-"#;
-            let new_content = if content.contains("[rendering]") {
-                content.replace("[rendering]", &format!("{}\n[rendering]", sprint_input))
-            } else {
-                format!("{}\n{}", content, sprint_input)
-            };
-            fs::write(&project_godot_path, new_content).ok();
-            println!("[Template] Added missing 'sprint' input");
+        let has_sprint = get_input_mappings(project.to_string_lossy().to_string())
+            .map(|mappings| mappings.iter().any(|m| m.action == "sprint"))
+            .unwrap_or(false);
+        if !has_sprint {
+            if let Ok(block) = input_action_block("sprint", &["Shift".to_string()]) {
+                fs::write(&project_godot_path, upsert_input_action(&content, "sprint", &block)).ok();
+                println!("[Template] Added missing 'sprint' input");
+            }
         }
     }
     
-    // Update version in template info
-    let new_info = serde_json::json!({
-        "template": template_id,
-        "dimension": dimension,
-        "version": TEMPLATE_VERSION
-    });
-    fs::write(&template_info_path, serde_json::to_string_pretty(&new_info).unwrap())
+    // Update version and file hashes in template info
+    info["version"] = serde_json::json!(TEMPLATE_VERSION);
+    info["file_hashes"] = stored_hashes;
+    fs::write(&template_info_path, serde_json::to_string_pretty(&info).unwrap())
         .map_err(|e| format!("Failed to update template_info.json: {}", e))?;
     
-    println!("[Template] Sync complete - scene and player updated");
+    if deferred.is_empty() {
+        println!("[Template] Sync complete - scene and player updated");
+    } else {
+        let _ = app.emit("template-update-available", TemplateUpdate {
+            project_path: project.to_string_lossy().to_string(),
+            template: template_id,
+            from_version: stored_version,
+            to_version: TEMPLATE_VERSION.to_string(),
+            files: deferred,
+            updates_dir: updates_dir.to_string_lossy().to_string(),
+        });
+    }
     Ok(())
 }
 
+/// Autoload scripts written at project creation, with their current embedded versions
+const AUTOLOAD_FILES: [(&str, &str); 3] = [
+    ("autoload/event_bus.gd", EVENT_BUS_GD),
+    ("autoload/game_state.gd", GAME_STATE_GD),
+    ("autoload/ai_controller.gd", AI_CONTROLLER_GD),
+];
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoloadUpdate {
+    pub updated: Vec<String>,
+    pub unchanged: Vec<String>,
+    /// User-modified files left alone; their new versions are in .tav/template_updates/
+    pub skipped: Vec<String>,
+    /// Backups of user-modified files that were replaced because `overwrite` was set
+    pub backups: Vec<String>,
+}
+
+/// Rewrite the autoload scripts from the embedded templates so existing projects pick up
+/// new signals and helpers. Files edited since they were generated (per the hashes in
+/// template_info.json) are only replaced with `overwrite`, after being backed up.
+#[tauri::command]
+fn update_autoloads(project_path: String, overwrite: bool) -> Result<AutoloadUpdate, String> {
+    let project = Path::new(&project_path);
+    if !project.join("project.godot").exists() {
+        return Err(format!("Not a Godot project: {}", project_path));
+    }
+    let info_path = project.join(".tav/template_info.json");
+    let mut info: Option<serde_json::Value> = fs::read_to_string(&info_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok());
+
+    let backup_dir = project.join(".tav/autoload_backups").join(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+            .to_string(),
+    );
+    let mut result = AutoloadUpdate::default();
+    for (rel_path, new_content) in AUTOLOAD_FILES {
+        let target = project.join(rel_path);
+        let new_hash = content_hash(new_content.as_bytes());
+        let on_disk_hash = fs::read(&target).ok().map(|data| content_hash(&data));
+        let stored_hash = info.as_ref().and_then(|i| i["file_hashes"][rel_path].as_str());
+
+        if on_disk_hash.as_deref() == Some(new_hash.as_str()) {
+            result.unchanged.push(rel_path.to_string());
+        } else {
+            // Without a stored hash there is no telling whether the user edited the file
+            let modified = on_disk_hash.is_some() && on_disk_hash.as_deref() != stored_hash;
+            if modified && !overwrite {
+                let update_path = project.join(".tav/template_updates").join(rel_path);
+                if let Some(parent) = update_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("Failed to create template_updates: {}", e))?;
+                }
+                fs::write(&update_path, new_content)
+                    .map_err(|e| format!("Failed to write template update for {}: {}", rel_path, e))?;
+                result.skipped.push(rel_path.to_string());
+                continue;
+            }
+            if modified {
+                let backup = backup_dir.join(rel_path);
+                if let Some(parent) = backup.parent() {
+                    fs::create_dir_all(parent).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+                }
+                fs::copy(&target, &backup).map_err(|e| format!("Failed to back up {}: {}", rel_path, e))?;
+                result.backups.push(backup.to_string_lossy().to_string());
+            }
+            write_file_atomic(&target, new_content)
+                .map_err(|e| format!("Failed to write {}: {}", rel_path, e))?;
+            result.updated.push(rel_path.to_string());
+        }
+
+        if let Some(info) = info.as_mut() {
+            if !info["file_hashes"].is_object() {
+                info["file_hashes"] = serde_json::json!({});
+            }
+            info["file_hashes"][rel_path] = serde_json::json!(new_hash);
+        }
+    }
+
+    if let Some(info) = info {
+        fs::write(&info_path, serde_json::to_string_pretty(&info).unwrap())
+            .map_err(|e| format!("Failed to update template_info.json: {}", e))?;
+    }
+    println!(
+        "[Template] Autoloads: {} updated, {} unchanged, {} skipped",
+        result.updated.len(), result.unchanged.len(), result.skipped.len()
+    );
+    Ok(result)
+}
+
+/// Text files that shape the build are hashed by content, so a checkout or touch
+/// that leaves them unchanged keeps the export cache valid
+const CONTENT_HASHED_EXTENSIONS: &[&str] = &["gd", "tscn", "tres", "godot"];
+const CONTENT_HASH_MAX_BYTES: u64 = 1024 * 1024;
+
 fn get_project_hash(project_path: &Path) -> u64 {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
@@ -2036,11 +3737,28 @@ fn get_project_hash(project_path: &Path) -> u64 {
     // Include bridge version so template changes invalidate cache
     KOBOLD_BRIDGE_VERSION.hash(&mut hasher);
     
-    // Hash modification times of key files
+    // Hash content of small text files, size + modification time of everything else
+    fn hash_file(path: &Path, meta: &fs::Metadata, hasher: &mut DefaultHasher) {
+        path.to_string_lossy().hash(hasher);
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        if meta.len() <= CONTENT_HASH_MAX_BYTES && CONTENT_HASHED_EXTENSIONS.contains(&ext) {
+            if let Ok(content) = fs::read(path) {
+                content.hash(hasher);
+                return;
+            }
+        }
+        meta.len().hash(hasher);
+        if let Ok(modified) = meta.modified() {
+            modified.hash(hasher);
+        }
+    }
+
     fn hash_dir(path: &Path, hasher: &mut DefaultHasher) {
         if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let path = entry.path();
+            // Sorted so the hash doesn't depend on directory listing order
+            let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+            paths.sort();
+            for path in paths {
                 let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
                 
                 // Skip hidden and generated directories
@@ -2051,10 +3769,7 @@ fn get_project_hash(project_path: &Path) -> u64 {
                 if path.is_dir() {
                     hash_dir(&path, hasher);
                 } else if let Ok(meta) = path.metadata() {
-                    if let Ok(modified) = meta.modified() {
-                        modified.hash(hasher);
-                        path.to_string_lossy().hash(hasher);
-                    }
+                    hash_file(&path, &meta, hasher);
                 }
             }
         }
@@ -2064,44 +3779,550 @@ fn get_project_hash(project_path: &Path) -> u64 {
     hasher.finish()
 }
 
-#[tauri::command]
-fn export_project_web(project_path: String, force: Option<bool>, state: tauri::State<AppState>) -> Result<String, String> {
-    let settings = state.settings.lock().unwrap();
-    let godot_cmd = settings
-        .godot_path
-        .clone()
-        .filter(|p| !p.is_empty() && Path::new(p).exists())
-        .or_else(|| find_godot_path())
-        .ok_or("Godot not found")?;
-
-    let project = Path::new(&project_path);
-    let export_dir = project.join(".tav/web");
-    let hash_file = export_dir.join(".export_hash");
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectIssue {
+    /// "error" blocks an export, "warning" is informational
+    pub severity: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectValidation {
+    pub issues: Vec<ProjectIssue>,
+}
+
+/// Autoloads every Kobold project template registers
+const CORE_AUTOLOADS: [&str; 3] = ["EventBus", "GameState", "AIController"];
+
+fn project_issue(severity: &str, message: String, file: Option<&str>) -> ProjectIssue {
+    ProjectIssue { severity: severity.to_string(), message, file: file.map(str::to_string) }
+}
+
+/// Map a `res://` path to the filesystem; other schemes (uid://, user://) return None
+fn res_path(project: &Path, path: &str) -> Option<PathBuf> {
+    path.strip_prefix("res://").map(|rel| project.join(rel))
+}
+
+/// `res://` path for a `res://`, project-relative or absolute path inside `project`
+fn to_res_path(project: &Path, path: &str) -> Result<String, String> {
+    if path.starts_with("res://") {
+        return Ok(path.to_string());
+    }
+    let file = Path::new(path);
+    let relative = if file.is_absolute() {
+        file.strip_prefix(project)
+            .map_err(|_| format!("Not inside the project: {}", path))?
+    } else {
+        file
+    };
+    Ok(format!("res://{}", relative.to_string_lossy().replace('\\', "/")))
+}
+
+/// `path="res://..."` values of a scene's `[ext_resource]` entries
+fn scene_ext_resource_paths(scene: &str) -> Vec<String> {
+    scene.lines()
+        .filter(|line| line.starts_with("[ext_resource"))
+        .filter_map(|line| {
+            let start = line.find(" path=\"")? + " path=\"".len();
+            let end = line[start..].find('"')?;
+            Some(line[start..start + end].to_string())
+        })
+        .collect()
+}
+
+/// Static checks on project.godot and the main scene; no Godot needed
+fn validate_project_files(project: &Path) -> Vec<ProjectIssue> {
+    let mut issues = Vec::new();
+    let Ok(content) = fs::read_to_string(project.join("project.godot")) else {
+        issues.push(project_issue("error", "project.godot not found".to_string(), Some("project.godot")));
+        return issues;
+    };
+    let config = parse_godot_config(&content);
+    let unquote = |value: String| value.trim().trim_matches('"').to_string();
+
+    // Main scene and what it references
+    let main_scene = config.section("application")
+        .and_then(|section| section.get("run/main_scene"))
+        .map(unquote)
+        .filter(|scene| !scene.is_empty());
+    match main_scene {
+        None => issues.push(project_issue("error", "No main scene set (application/run/main_scene)".to_string(), Some("project.godot"))),
+        Some(scene) => match res_path(project, &scene).map(fs::read_to_string) {
+            Some(Ok(scene_content)) => {
+                for resource in scene_ext_resource_paths(&scene_content) {
+                    if res_path(project, &resource).is_some_and(|path| !path.exists()) {
+                        issues.push(project_issue("error", format!("Missing resource {}", resource), Some(&scene)));
+                    }
+                }
+            }
+            _ => issues.push(project_issue("error", format!("Main scene {} does not exist", scene), Some("project.godot"))),
+        },
+    }
+
+    // Autoload scripts must exist; Kobold's core autoloads should be registered
+    let autoload = config.section("autoload");
+    for name in CORE_AUTOLOADS {
+        if autoload.and_then(|section| section.get(name)).is_none() {
+            issues.push(project_issue("warning", format!("Autoload {} is not registered", name), Some("project.godot")));
+        }
+    }
+    for line in autoload.map(|section| section.lines.as_slice()).unwrap_or_default() {
+        let Some((name, value)) = line.split_once('=') else { continue };
+        let script = unquote(value.to_string()).trim_start_matches('*').to_string();
+        if res_path(project, &script).is_some_and(|path| !path.exists()) {
+            issues.push(project_issue("error", format!("Autoload {} points to missing {}", name.trim(), script), Some("project.godot")));
+        }
+    }
+
+    issues
+}
+
+const RENDERING_METHODS: [&str; 3] = ["forward_plus", "mobile", "gl_compatibility"];
+
+/// Renderer a web export uses: the `.web` override if set, else Godot's web
+/// default, gl_compatibility (the project-wide rendering method doesn't apply)
+fn web_rendering_method(config: &GodotConfig) -> String {
+    config.section("rendering")
+        .and_then(|s| s.get("renderer/rendering_method.web"))
+        .map(|v| v.trim().trim_matches('"').to_string())
+        .unwrap_or_else(|| "gl_compatibility".to_string())
+}
+
+/// Warning for projects whose `.web` override picks a renderer other than
+/// Compatibility; browsers without WebGPU show a blank canvas otherwise
+fn web_renderer_issue(project: &Path) -> Option<ProjectIssue> {
+    let content = fs::read_to_string(project.join("project.godot")).ok()?;
+    let method = web_rendering_method(&parse_godot_config(&content));
+    (method != "gl_compatibility").then(|| project_issue(
+        "warning",
+        format!("Rendering method {} is not supported on the web; web builds may render blank. Switch to gl_compatibility.", method),
+        Some("project.godot"),
+    ))
+}
+
+/// Set the project's rendering method, dropping any `.web` override so web
+/// exports fall back to Godot's gl_compatibility default
+#[tauri::command]
+fn set_rendering_method(project_path: String, method: String) -> Result<(), String> {
+    if !RENDERING_METHODS.contains(&method.as_str()) {
+        return Err(format!("Unknown rendering method: {} (expected one of {})", method, RENDERING_METHODS.join(", ")));
+    }
+    let path = Path::new(&project_path).join("project.godot");
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read project.godot: {}", e))?;
+    let mut config = parse_godot_config(&content);
+    let section = config.section_or_insert("rendering", &[]);
+    section.set("renderer/rendering_method", &format!("\"{}\"", method));
+    section.remove("renderer/rendering_method.web");
+    write_file_atomic(&path, &write_godot_config(&config))
+        .map_err(|e| format!("Failed to write project.godot: {}", e))?;
+    println!("[Project] Rendering method set to {}", method);
+    Ok(())
+}
+
+/// Pre-flight check run before exporting, so failures show up as specific issues
+/// instead of a late "index.html not found"
+#[tauri::command]
+fn validate_project(project_path: String, state: tauri::State<AppState>) -> ProjectValidation {
+    let mut issues = validate_project_files(Path::new(&project_path));
+    issues.extend(web_renderer_issue(Path::new(&project_path)));
+
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_path = match project_godot_cmd(Path::new(&project_path), saved) {
+        Ok(path) => path,
+        Err(e) => {
+            issues.push(project_issue("error", e, Some(".tav/godot.json")));
+            return ProjectValidation { issues };
+        }
+    };
+    match godot_path.map(|path| get_godot_version(&path)) {
+        None => issues.push(project_issue("error", "Godot not found".to_string(), None)),
+        Some(Err(e)) => issues.push(project_issue("warning", e, None)),
+        Some(Ok(version)) if !check_web_templates_installed(&version) => issues.push(project_issue(
+            "error",
+            format!("Web export templates for Godot {} are not installed", version),
+            None,
+        )),
+        Some(Ok(_)) => {}
+    }
+
+    ProjectValidation { issues }
+}
+
+/// Text resources scanned for `res://` references by analyze_unused_assets
+const REFERENCE_SOURCE_EXTS: [&str; 5] = ["tscn", "tres", "gd", "gdshader", "godot"];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedAsset {
+    /// Absolute path, usable with delete_file
+    pub path: String,
+    pub size: u64,
+}
+
+/// Every `res://` path mentioned in a text resource: ext_resources,
+/// preload()/load() calls, project.godot entries, ...
+fn res_references(text: &str) -> Vec<String> {
+    text.match_indices("res://")
+        .map(|(start, _)| {
+            let rest = &text[start..];
+            let end = rest.find(|c: char| matches!(c, '"' | '\'' | ')' | ',' | ']') || c.is_whitespace()).unwrap_or(rest.len());
+            rest[..end].to_string()
+        })
+        .collect()
+}
+
+/// Files a .gltf loads by relative `uri` (external textures and buffers)
+fn gltf_uri_references(gltf: &Path) -> Vec<PathBuf> {
+    let Some(json) = fs::read_to_string(gltf).ok().and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok()) else {
+        return Vec::new();
+    };
+    let dir = gltf.parent().unwrap_or(Path::new(""));
+    ["images", "buffers"].iter()
+        .filter_map(|key| json[*key].as_array())
+        .flatten()
+        .filter_map(|item| item["uri"].as_str())
+        .filter(|uri| !uri.starts_with("data:"))
+        .map(|uri| dir.join(urlencoding::decode(uri).map(|u| u.into_owned()).unwrap_or_else(|_| uri.to_string())))
+        .collect()
+}
+
+/// Files under `assets/` that nothing reachable from project.godot (main scene,
+/// autoloads, icon, ...) references, biggest first. A reference to a directory
+/// counts for everything in it, since scripts often load assets by folder.
+fn find_unused_assets(project: &Path) -> Result<Vec<UnusedAsset>, String> {
+    let project_godot = project.join("project.godot");
+    if !project_godot.exists() {
+        return Err("Not a Godot project (project.godot not found)".to_string());
+    }
+
+    let mut used_files = std::collections::HashSet::new();
+    let mut used_dirs = Vec::new();
+    let mut queue = vec![project_godot];
+    while let Some(file) = queue.pop() {
+        if !used_files.insert(file.clone()) {
+            continue;
+        }
+        let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+        if ext == "gltf" {
+            queue.extend(gltf_uri_references(&file));
+            continue;
+        }
+        if !REFERENCE_SOURCE_EXTS.contains(&ext.as_str()) {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(&file) else { continue };
+        for reference in res_references(&text) {
+            let Some(path) = res_path(project, reference.trim_end_matches('/')) else { continue };
+            if path.is_dir() {
+                used_dirs.push(path);
+            } else if path.is_file() {
+                queue.push(path);
+            }
+        }
+    }
+
+    let assets = walk_files(&project.join("assets"), |name| name.starts_with('.') || name.ends_with(".import"));
+
+    let mut unused: Vec<UnusedAsset> = assets.into_iter()
+        .filter(|(path, _)| !used_files.contains(path) && !used_dirs.iter().any(|dir| path.starts_with(dir)))
+        .map(|(path, size)| UnusedAsset { path: path.to_string_lossy().to_string(), size })
+        .collect();
+    keep_largest(&mut unused, |a| (a.size, &a.path), usize::MAX);
+    Ok(unused)
+}
+
+/// Report assets that are never referenced so users can trim the .pck.
+/// Read-only; deleting is left to the user via delete_file.
+#[tauri::command]
+fn analyze_unused_assets(project_path: String) -> Result<Vec<UnusedAsset>, String> {
+    let unused = find_unused_assets(Path::new(&project_path))?;
+    println!("[Assets] {} unused assets ({} bytes) in {}", unused.len(), unused.iter().map(|a| a.size).sum::<u64>(), project_path);
+    Ok(unused)
+}
+
+#[tauri::command]
+async fn export_project_web(
+    project_path: String,
+    force: Option<bool>,
+    preset_name: Option<String>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<WebExport, String> {
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = project_godot_cmd(Path::new(&project_path), saved)?
+        .ok_or("Godot not found")?;
+
+    // Off the main thread so export-log lines reach the UI while Godot runs
+    tokio::task::spawn_blocking(move || {
+        web_export_blocking(&app, &godot_cmd, &project_path, force.unwrap_or(false), preset_name)
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+fn web_export_blocking(app: &tauri::AppHandle, godot_cmd: &str, project_path: &str, force: bool, preset_name: Option<String>) -> Result<WebExport, String> {
+    let project = Path::new(project_path);
+    // Watcher-triggered and manual exports must not write .tav/web at the same time
+    let state = app.state::<AppState>();
+    let _guard = ExportGuard::acquire(&state.exporting, project)?;
+    let export_dir = project.join(".tav/web");
+    let hash_file = export_dir.join(".export_hash");
+    let preset = preset_name.filter(|p| !p.trim().is_empty()).unwrap_or_else(|| DEFAULT_EXPORT_PRESET.to_string());
     
-    // Calculate current project hash
-    let current_hash = get_project_hash(project);
+    // Calculate current project hash (a different preset needs a fresh export)
+    let current_hash = format!("{}:{}", get_project_hash(project), preset);
     
     // Auto-sync template files if version mismatch
-    sync_template_if_needed(project)?;
+    sync_template_if_needed(project, app)?;
     
     // Always inject/update Kobold Bridge first (even for cached exports)
     inject_kobold_bridge(project)?;
     
     // Check if we can use cached export
-    if !force.unwrap_or(false) && export_dir.join("index.html").exists() {
+    if !force && export_dir.join("index.html").exists() {
         if let Ok(cached_hash) = fs::read_to_string(&hash_file) {
-            if let Ok(cached) = cached_hash.trim().parse::<u64>() {
-                if cached == current_hash {
-                    // Still need to re-inject JS into cached HTML
-                    inject_js_helper(&export_dir)?;
-                    return Ok(format!("CACHED:{}", export_dir.to_string_lossy()));
-                }
+            if cached_hash.trim() == current_hash {
+                // Still need to re-inject JS into cached HTML
+                inject_js_helper(&export_dir)?;
+                return Ok(WebExport {
+                    export_dir: export_dir.to_string_lossy().to_string(),
+                    cached: true,
+                    size: check_export_size(app, project, &export_dir),
+                });
             }
         }
     }
     
+    if let Some(issue) = web_renderer_issue(project) {
+        println!("[Export] Warning: {}", issue.message);
+        let _ = app.emit("export-log", ExportLogLine { project: project_path.to_string(), line: format!("WARNING: {}", issue.message) });
+    }
+    
+    // Run Godot export (debug mode is faster)
+    run_web_export(app, godot_cmd, project, "--export-debug", &preset, &export_dir)?;
+    
+    // Inject JS helper into exported HTML
+    inject_js_helper(&export_dir)?;
+    
+    // Save hash for caching
+    fs::write(&hash_file, &current_hash).ok();
+    
+    Ok(WebExport {
+        export_dir: export_dir.to_string_lossy().to_string(),
+        cached: false,
+        size: check_export_size(app, project, &export_dir),
+    })
+}
+
+/// Marks a project as exporting until dropped
+struct ExportGuard<'a> {
+    exporting: &'a Mutex<std::collections::HashSet<String>>,
+    project: String,
+}
+
+impl<'a> ExportGuard<'a> {
+    fn acquire(exporting: &'a Mutex<std::collections::HashSet<String>>, project: &Path) -> Result<Self, String> {
+        let project = fs::canonicalize(project)
+            .unwrap_or_else(|_| project.to_path_buf())
+            .to_string_lossy()
+            .to_string();
+        if !exporting.lock().unwrap().insert(project.clone()) {
+            return Err("Export already in progress for this project".to_string());
+        }
+        Ok(Self { exporting, project })
+    }
+}
+
+impl Drop for ExportGuard<'_> {
+    fn drop(&mut self) {
+        self.exporting.lock().unwrap().remove(&self.project);
+    }
+}
+
+/// Preset name written by WEB_EXPORT_PRESET
+const DEFAULT_EXPORT_PRESET: &str = "Web";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreset {
+    pub name: String,
+    pub platform: String,
+    pub runnable: bool,
+}
+
+/// Presets defined in an export_presets.cfg, in file order
+fn parse_export_presets(cfg: &str) -> Vec<ExportPreset> {
+    let mut presets: Vec<ExportPreset> = Vec::new();
+    let mut in_preset = false;
+    for line in cfg.lines().map(str::trim) {
+        if line.starts_with('[') {
+            // [preset.N] starts a preset; [preset.N.options] holds platform settings
+            let section = line.trim_start_matches('[').trim_end_matches(']');
+            in_preset = section.strip_prefix("preset.").is_some_and(|id| id.parse::<u32>().is_ok());
+            if in_preset {
+                presets.push(ExportPreset { name: String::new(), platform: String::new(), runnable: false });
+            }
+            continue;
+        }
+        if !in_preset {
+            continue;
+        }
+        let (Some(preset), Some((key, value))) = (presets.last_mut(), line.split_once('=')) else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"');
+        match key.trim() {
+            "name" => preset.name = value.to_string(),
+            "platform" => preset.platform = value.to_string(),
+            "runnable" => preset.runnable = value == "true",
+            _ => {}
+        }
+    }
+    presets
+}
+
+/// Export presets defined in the project, so the UI can offer the right one for export_project_web
+#[tauri::command]
+fn list_export_presets(project_path: String) -> Result<Vec<ExportPreset>, String> {
+    let presets_path = Path::new(&project_path).join("export_presets.cfg");
+    if !presets_path.exists() {
+        return Ok(Vec::new());
+    }
+    let cfg = fs::read_to_string(&presets_path)
+        .map_err(|e| format!("Failed to read export presets: {}", e))?;
+    Ok(parse_export_presets(&cfg))
+}
+
+/// Streamed as `export-log` for each line Godot prints during an export
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportLogLine {
+    project: String,
+    line: String,
+}
+
+/// Sent as `export-complete` (with `export_dir`) or `export-error` (with `error`)
+/// when a Godot export finishes
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportFinished {
+    project: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    export_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// How long a Godot export may run before it is killed, unless overridden in settings
+const DEFAULT_EXPORT_TIMEOUT_SECS: u64 = 300;
+
+fn export_timeout(settings: &AppSettings) -> std::time::Duration {
+    std::time::Duration::from_secs(settings.export_timeout_secs.filter(|s| *s > 0).unwrap_or(DEFAULT_EXPORT_TIMEOUT_SECS))
+}
+
+/// Web builds above this size load slowly and some hosts reject them
+const DEFAULT_EXPORT_SIZE_WARNING_MB: u64 = 50;
+/// Number of files listed in ExportSize::largest_files
+const EXPORT_SIZE_LARGEST_FILES: usize = 5;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportFileSize {
+    /// Relative to the export directory
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportSize {
+    pub total: u64,
+    /// Biggest files first (usually the .pck and .wasm)
+    pub largest_files: Vec<ExportFileSize>,
+    /// Warning threshold in bytes
+    pub threshold: u64,
+    pub over_threshold: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebExport {
+    pub export_dir: String,
+    /// Reused the previous export because nothing changed
+    pub cached: bool,
+    pub size: ExportSize,
+}
+
+/// Sent as `export-size-warning` when a build exceeds the size threshold
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportSizeWarning {
+    project: String,
+    total: u64,
+    threshold: u64,
+}
+
+/// Total size and largest files of an export directory, leaving out the
+/// hidden files (like .export_hash) that are never uploaded
+fn measure_export(export_dir: &Path, threshold: u64) -> ExportSize {
+    let mut files: Vec<ExportFileSize> = walk_files(export_dir, |name| name.starts_with('.'))
+        .into_iter()
+        .map(|(path, size)| {
+            let rel = path.strip_prefix(export_dir).unwrap_or(&path);
+            ExportFileSize { path: rel.to_string_lossy().replace('\\', "/"), size }
+        })
+        .collect();
+    let total = files.iter().map(|f| f.size).sum();
+    keep_largest(&mut files, |f| (f.size, &f.path), EXPORT_SIZE_LARGEST_FILES);
+    ExportSize { total, largest_files: files, threshold, over_threshold: total > threshold }
+}
+
+/// Measure a finished export and emit `export-size-warning` when it is over the
+/// configured threshold
+fn check_export_size(app: &tauri::AppHandle, project: &Path, export_dir: &Path) -> ExportSize {
+    let threshold_mb = app.state::<AppState>().settings.lock().unwrap()
+        .export_size_warning_mb
+        .filter(|mb| *mb > 0)
+        .unwrap_or(DEFAULT_EXPORT_SIZE_WARNING_MB);
+    let size = measure_export(export_dir, threshold_mb * 1024 * 1024);
+    if size.over_threshold {
+        println!("[Export] Warning: build is {} bytes, over the {} MB limit", size.total, threshold_mb);
+        let _ = app.emit("export-size-warning", ExportSizeWarning {
+            project: project.to_string_lossy().to_string(),
+            total: size.total,
+            threshold: size.threshold,
+        });
+    }
+    size
+}
+
+/// Run one of the project's export presets (normally "Web") into `export_dir`.
+/// `export_flag` is `--export-debug` or `--export-release`.
+fn run_web_export(app: &tauri::AppHandle, godot_cmd: &str, project: &Path, export_flag: &str, preset: &str, export_dir: &Path) -> Result<(), String> {
+    let result = run_godot_export(app, godot_cmd, project, export_flag, preset, export_dir);
+    let (event, finished) = match &result {
+        Ok(()) => ("export-complete", ExportFinished {
+            project: project.to_string_lossy().to_string(),
+            export_dir: Some(export_dir.to_string_lossy().to_string()),
+            error: None,
+        }),
+        Err(e) => ("export-error", ExportFinished {
+            project: project.to_string_lossy().to_string(),
+            export_dir: None,
+            error: Some(e.clone()),
+        }),
+    };
+    let _ = app.emit(event, finished);
+    result
+}
+
+fn run_godot_export(app: &tauri::AppHandle, godot_cmd: &str, project: &Path, export_flag: &str, preset: &str, export_dir: &Path) -> Result<(), String> {
     // Create export directory
-    fs::create_dir_all(&export_dir)
+    fs::create_dir_all(export_dir)
         .map_err(|e| format!("Failed to create export directory: {}", e))?;
     
     // Create export_presets.cfg if it doesn't exist
@@ -2110,54 +4331,48 @@ fn export_project_web(project_path: String, force: Option<bool>, state: tauri::S
         fs::write(&presets_path, WEB_EXPORT_PRESET)
             .map_err(|e| format!("Failed to write export presets: {}", e))?;
     }
+    let presets = fs::read_to_string(&presets_path).map(|cfg| parse_export_presets(&cfg)).unwrap_or_default();
+    if !presets.is_empty() && !presets.iter().any(|p| p.name == preset) {
+        let names: Vec<_> = presets.iter().map(|p| p.name.as_str()).collect();
+        return Err(format!("Export preset \"{}\" not found. Available presets: {}", preset, names.join(", ")));
+    }
     
-    // Run Godot export (debug mode is faster)
-    println!("[Export] Running: {} --headless --path {} --export-debug Web", godot_cmd, project_path);
+    let project_path = project.to_string_lossy();
+    println!("[Export] Running: {} --headless --path {} {} {}", godot_cmd, project_path, export_flag, preset);
     
-    let output = Command::new(&godot_cmd)
+    let mut command = Command::new(godot_cmd);
+    command
         .args([
             "--headless",
             "--path", &project_path,
-            "--export-debug", "Web",
+            export_flag, preset,
             &export_dir.join("index.html").to_string_lossy(),
         ])
-        .current_dir(&project_path)
-        .output()
-        .map_err(|e| format!("Export failed: {}", e))?;
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    
-    if !stdout.is_empty() {
-        println!("[Export] stdout: {}", stdout);
-    }
-    if !stderr.is_empty() {
-        println!("[Export] stderr: {}", stderr);
-    }
-    
-    if !output.status.success() {
-        return Err(format!("Export failed (exit {}): {}", output.status, stderr));
-    }
+        .current_dir(project);
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+    
+    let log_app = app.clone();
+    let log_project = project_path.to_string();
+    let timeout = export_timeout(&app.state::<AppState>().settings.lock().unwrap());
+    run_streamed(command, "Godot", Some(timeout), move |line| {
+        println!("[Export] {}", line);
+        let _ = log_app.emit("export-log", ExportLogLine { project: log_project.clone(), line });
+    })
+    .map_err(|e| format!("Export failed: {}", e))?;
     
     // Verify export succeeded
     if !export_dir.join("index.html").exists() {
         // List what files were created
         if export_dir.exists() {
-            let files: Vec<_> = fs::read_dir(&export_dir)
+            let files: Vec<_> = fs::read_dir(export_dir)
                 .map(|entries| entries.filter_map(|e| e.ok().map(|f| f.file_name().to_string_lossy().to_string())).collect())
                 .unwrap_or_default();
             return Err(format!("Export completed but index.html not found. Files in export dir: {:?}", files));
         }
         return Err("Export completed but index.html not found. Make sure Godot Web export templates are installed.".to_string());
     }
-    
-    // Inject JS helper into exported HTML
-    inject_js_helper(&export_dir)?;
-    
-    // Save hash for caching
-    fs::write(&hash_file, current_hash.to_string()).ok();
-    
-    Ok(export_dir.to_string_lossy().to_string())
+    Ok(())
 }
 
 fn inject_js_helper(export_dir: &Path) -> Result<(), String> {
@@ -2165,13 +4380,7 @@ fn inject_js_helper(export_dir: &Path) -> Result<(), String> {
     let html = fs::read_to_string(&index_path)
         .map_err(|e| format!("Failed to read index.html: {}", e))?;
     
-    // Skip if already injected
-    if html.contains("KoboldBridge") {
-        return Ok(());
-    }
-    
-    let capture_script = r#"
-<script>
+    let capture_script = r#"<script>
 // Kobold Bridge Helper - Uses native Godot API when available, falls back to canvas
 (function() {
     const canvas = () => document.querySelector('canvas');
@@ -2381,122 +4590,844 @@ fn inject_js_helper(export_dir: &Path) -> Result<(), String> {
     
     console.log('[Kobold] Helper loaded, waiting for bridge...');
 })();
-</script>
-</head>"#;
+</script>"#;
     
-    let modified_html = html.replace("</head>", capture_script);
+    // Skip if the current bridge version is already injected
+    let Some(modified_html) = inject_helper_html(&html, capture_script) else {
+        return Ok(());
+    };
     fs::write(&index_path, modified_html)
         .map_err(|e| format!("Failed to write index.html: {}", e))?;
     
-    println!("[Export] Injected Kobold JS helper");
+    println!("[Export] Injected Kobold JS helper (v{})", KOBOLD_BRIDGE_VERSION);
     Ok(())
 }
 
-#[tauri::command]
-fn start_preview_server(export_path: String) -> Result<u16, String> {
-    use std::thread;
-    
-    // Verify export path exists
-    let export_dir = Path::new(&export_path);
-    if !export_dir.exists() {
-        return Err(format!("Export directory does not exist: {}", export_path));
-    }
-    if !export_dir.join("index.html").exists() {
-        return Err(format!("index.html not found in: {}", export_path));
-    }
-    
-    println!("[PreviewServer] Starting server for: {}", export_path);
-    
-    // Find an available port
-    let port = (8080..9000)
-        .find(|p| std::net::TcpListener::bind(("127.0.0.1", *p)).is_ok())
-        .ok_or("No available port found")?;
-    
-    println!("[PreviewServer] Using port: {}", port);
-    
-    let export_path_clone = export_path.clone();
-    thread::spawn(move || {
-        let server = match tiny_http::Server::http(format!("127.0.0.1:{}", port)) {
-            Ok(s) => s,
-            Err(e) => {
-                eprintln!("[PreviewServer] Failed to start: {}", e);
-                return;
-            }
-        };
-        
-        println!("[PreviewServer] Server running on http://127.0.0.1:{}", port);
-        
-        for request in server.incoming_requests() {
-            let url = request.url().to_string();
-            let file_path = if url == "/" || url.is_empty() {
-                Path::new(&export_path_clone).join("index.html")
-            } else {
-                Path::new(&export_path_clone).join(url.trim_start_matches('/'))
-            };
-            
-            println!("[PreviewServer] Request: {} -> {:?}", url, file_path);
-            
-            let response = if file_path.exists() {
-                let content = match fs::read(&file_path) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        eprintln!("[PreviewServer] Failed to read file: {}", e);
-                        let r = tiny_http::Response::from_string("Read error")
-                            .with_status_code(500);
-                        let _ = request.respond(r);
-                        continue;
-                    }
-                };
-                
-                let mime = match file_path.extension().and_then(|e| e.to_str()) {
-                    Some("html") => "text/html; charset=utf-8",
-                    Some("js") => "application/javascript",
-                    Some("wasm") => "application/wasm",
-                    Some("png") => "image/png",
-                    Some("ico") => "image/x-icon",
-                    Some("pck") => "application/octet-stream",
-                    Some("css") => "text/css",
-                    _ => "application/octet-stream",
-                };
-                
-                tiny_http::Response::from_data(content)
-                    .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], mime.as_bytes()).unwrap())
-                    .with_header(tiny_http::Header::from_bytes(&b"Cross-Origin-Opener-Policy"[..], &b"same-origin"[..]).unwrap())
-                    .with_header(tiny_http::Header::from_bytes(&b"Cross-Origin-Embedder-Policy"[..], &b"require-corp"[..]).unwrap())
-                    .with_header(tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], &b"*"[..]).unwrap())
-            } else {
-                println!("[PreviewServer] 404: {:?}", file_path);
-                tiny_http::Response::from_string("Not found").with_status_code(404)
-            };
-            
-            let _ = request.respond(response);
-        }
-    });
-    
-    // Give the server a moment to start
-    std::thread::sleep(std::time::Duration::from_millis(100));
-    
-    Ok(port)
+const JS_HELPER_END_MARKER: &str = "<!-- /kobold-helper -->";
+
+fn js_helper_start_marker() -> String {
+    format!("<!-- kobold-helper v{} -->", KOBOLD_BRIDGE_VERSION)
 }
 
-// ============================================================================
-// Game Playing Commands
-// ============================================================================
+/// Insert the helper script before `</head>`, replacing any helper from an older
+/// bridge version. Returns None when the current version is already present.
+fn inject_helper_html(html: &str, script: &str) -> Option<String> {
+    let start_marker = js_helper_start_marker();
+    if html.contains(&start_marker) {
+        return None;
+    }
+
+    let html = strip_helper_html(html);
+    let block = format!("{}\n{}\n{}\n</head>", start_marker, script, JS_HELPER_END_MARKER);
+    Some(html.replacen("</head>", &block, 1))
+}
+
+/// Remove an injected helper script (any version) from exported HTML
+fn strip_helper_html(html: &str) -> String {
+    let mut html = html.to_string();
+    if let Some(start) = html.find("<!-- kobold-helper v") {
+        if let Some(end) = html[start..].find(JS_HELPER_END_MARKER) {
+            let end = start + end + JS_HELPER_END_MARKER.len();
+            let end = if html[end..].starts_with('\n') { end + 1 } else { end };
+            html.replace_range(start..end, "");
+        }
+    } else if let Some(start) = html.find("<script>\n// Kobold Bridge Helper") {
+        // Injected before version markers existed
+        if let Some(end) = html[start..].find("</script>") {
+            html.replace_range(start..start + end + "</script>".len(), "");
+        }
+    }
+    html
+}
+
+/// A zipped web build ready for upload (e.g. to itch.io)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebBuildPackage {
+    pub zip_path: String,
+    pub size: u64,
+    /// Unzipped size of the release export
+    pub export_size: ExportSize,
+}
+
+/// Whether a file in a web export belongs in an upload: the export hash, temp
+/// and hidden files are left out
+fn is_web_build_file(name: &str) -> bool {
+    !name.starts_with('.') && !name.ends_with(".tmp")
+}
+
+/// Copy the uploadable files of a web export into a fresh `staging` directory
+fn stage_web_build(build_dir: &Path, staging: &Path) -> Result<(), String> {
+    if staging.exists() {
+        fs::remove_dir_all(staging).map_err(|e| format!("Failed to clear {}: {}", staging.display(), e))?;
+    }
+    let mut pending = vec![build_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            if !is_web_build_file(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let path = entry.path();
+            let target = staging.join(path.strip_prefix(build_dir).unwrap_or(&path));
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            fs::copy(&path, &target).map_err(|e| format!("Failed to copy {}: {}", path.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Zip a web export directory, leaving out the export hash, temp and hidden files.
+/// Returns the zip's size in bytes.
+fn zip_web_build(build_dir: &Path, output_zip: &Path) -> Result<u64, String> {
+    use std::io::Write;
+
+    let file = fs::File::create(output_zip)
+        .map_err(|e| format!("Failed to create {}: {}", output_zip.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut pending = vec![build_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if !is_web_build_file(name) {
+                continue;
+            }
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(build_dir).unwrap_or(&path);
+            let data = fs::read(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            zip.start_file(relative.to_string_lossy().replace('\\', "/"), options)
+                .map_err(|e| format!("Failed to add {} to zip: {}", relative.display(), e))?;
+            zip.write_all(&data)
+                .map_err(|e| format!("Failed to add {} to zip: {}", relative.display(), e))?;
+        }
+    }
+
+    zip.finish().map_err(|e| format!("Failed to finish zip: {}", e))?;
+    fs::metadata(output_zip)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read zip size: {}", e))
+}
+
+/// Streamed as `backup-progress` / `restore-progress` while a project backup is written or restored
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupProgress {
+    zip_path: String,
+    done: usize,
+    total: usize,
+    percent: u8,
+}
+
+/// Zip `project` into `output_zip`, skipping the file tree's ignore list (.godot,
+/// .tav, .git, node_modules, target). `on_file(done, total)` runs after each file.
+/// Returns the number of files added.
+fn zip_project(project: &Path, output_zip: &Path, mut on_file: impl FnMut(usize, usize)) -> Result<usize, String> {
+    use std::io::Write;
+
+    let project = &project.canonicalize()
+        .map_err(|e| format!("Failed to read {}: {}", project.display(), e))?;
+    // Canonical too, so a zip written inside the project is recognised and skipped
+    let output_dir = output_zip.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let output_path = output_dir.canonicalize()
+        .map_err(|e| format!("Failed to read {}: {}", output_dir.display(), e))?
+        .join(output_zip.file_name().unwrap_or_default());
+
+    let mut files: Vec<PathBuf> = walk_files(project, is_ignored_entry)
+        .into_iter()
+        .map(|(path, _)| path)
+        .filter(|path| *path != output_path)
+        .collect();
+    files.sort();
+
+    let file = fs::File::create(output_zip)
+        .map_err(|e| format!("Failed to create {}: {}", output_zip.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    for (i, path) in files.iter().enumerate() {
+        let relative = path.strip_prefix(project).unwrap_or(path);
+        let mut source = fs::File::open(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        zip.start_file(relative.to_string_lossy().replace('\\', "/"), options)
+            .map_err(|e| format!("Failed to add {} to zip: {}", relative.display(), e))?;
+        std::io::copy(&mut source, &mut zip)
+            .map_err(|e| format!("Failed to add {} to zip: {}", relative.display(), e))?;
+        on_file(i + 1, files.len());
+    }
+
+    zip.finish().map_err(|e| format!("Failed to finish zip: {}", e))?
+        .flush().map_err(|e| format!("Failed to finish zip: {}", e))?;
+    Ok(files.len())
+}
+
+/// Snapshot the project to a zip (default `<project>/../<name>-backup-<timestamp>.zip`)
+/// and return its path. Progress streams as `backup-progress`.
+#[tauri::command]
+async fn backup_project(project_path: String, output_zip: Option<String>, app: tauri::AppHandle) -> Result<String, String> {
+    let project = Path::new(&project_path).canonicalize()
+        .map_err(|e| format!("Project not found: {}", e))?;
+    let output = match output_zip.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let name = project.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| "project".to_string());
+            let timestamp = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            project.parent().unwrap_or(&project).join(format!("{}-backup-{}.zip", name, timestamp))
+        }
+    };
+    if let Some(dir) = output.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create backup directory: {}", e))?;
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let zip_path = output.to_string_lossy().to_string();
+        let count = zip_project(&project, &output, |done, total| {
+            let _ = app.emit("backup-progress", BackupProgress {
+                zip_path: zip_path.clone(),
+                done,
+                total,
+                percent: (done * 100 / total.max(1)) as u8,
+            });
+        })?;
+        println!("[Backup] Wrote {} files to {}", count, zip_path);
+        Ok(zip_path)
+    })
+    .await
+    .map_err(|e| format!("Backup task failed: {}", e))?
+}
+
+/// Extract a backup from backup_project into `destination`, overwriting files
+/// it contains. Progress streams as `restore-progress`.
+#[tauri::command]
+async fn restore_project_backup(zip_path: String, destination: String, app: tauri::AppHandle) -> Result<String, String> {
+    fs::create_dir_all(&destination)
+        .map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    tokio::task::spawn_blocking(move || {
+        let count = extract_zip_safe(Path::new(&zip_path), Path::new(&destination), |done, total| {
+            let _ = app.emit("restore-progress", BackupProgress {
+                zip_path: zip_path.clone(),
+                done,
+                total,
+                percent: (done * 100 / total.max(1)) as u8,
+            });
+        })?;
+        println!("[Backup] Restored {} files from {} to {}", count, zip_path, destination);
+        Ok(destination)
+    })
+    .await
+    .map_err(|e| format!("Restore task failed: {}", e))?
+}
+
+/// Release-export the project to `.tav/release`, reusing the export when nothing
+/// changed, with the Kobold JS helper stripped from index.html
+fn release_web_build(godot_cmd: &str, project: &Path, app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let release_dir = project.join(".tav/release");
+    let hash_file = release_dir.join(".export_hash");
+    // Tagged so release exports made while the bridge was still included are redone
+    let current_hash = format!("{}:release", get_project_hash(project));
+
+    let state = app.state::<AppState>();
+    let _guard = ExportGuard::acquire(&state.exporting, project)?;
+
+    sync_template_if_needed(project, app)?;
+
+    let cached = release_dir.join("index.html").exists()
+        && fs::read_to_string(&hash_file).is_ok_and(|h| h.trim() == current_hash);
+    if cached {
+        println!("[Package] Reusing release export in {}", release_dir.display());
+    } else {
+        // Release builds must not ship the preview's test instrumentation; the
+        // bridge is put back afterwards so previews keep working
+        let bridged = fs::read_to_string(project.join("project.godot"))
+            .is_ok_and(|c| parse_godot_config(&c).section("autoload").and_then(|s| s.get("KoboldBridge")).is_some());
+        if bridged {
+            strip_kobold_bridge(project.to_string_lossy().to_string())?;
+        }
+        let result = run_web_export(app, godot_cmd, project, "--export-release", DEFAULT_EXPORT_PRESET, &release_dir);
+        if bridged {
+            inject_kobold_bridge(project)?;
+        }
+        result?;
+        fs::write(&hash_file, &current_hash).ok();
+    }
+
+    // The preview helper only makes sense inside Kobold's iframe
+    let index_path = release_dir.join("index.html");
+    let html = fs::read_to_string(&index_path)
+        .map_err(|e| format!("Failed to read index.html: {}", e))?;
+    let stripped = strip_helper_html(&html);
+    if stripped != html {
+        fs::write(&index_path, stripped)
+            .map_err(|e| format!("Failed to write index.html: {}", e))?;
+    }
+    Ok(release_dir)
+}
+
+/// Release-export the project and zip it into a single uploadable file
+#[tauri::command]
+fn package_web_build(project_path: String, output_zip: String, app: tauri::AppHandle, state: tauri::State<AppState>) -> Result<WebBuildPackage, String> {
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = project_godot_cmd(Path::new(&project_path), saved)?
+        .ok_or("Godot not found")?;
+
+    let release_dir = release_web_build(&godot_cmd, Path::new(&project_path), &app)?;
+    let export_size = check_export_size(&app, Path::new(&project_path), &release_dir);
+
+    let zip_path = Path::new(&output_zip);
+    if let Some(parent) = zip_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+    let size = zip_web_build(&release_dir, zip_path)?;
+    println!("[Package] Wrote {} ({} bytes)", output_zip, size);
+
+    Ok(WebBuildPackage { zip_path: output_zip, size, export_size })
+}
+
+// ============================================================================
+// itch.io Publishing (butler)
+// ============================================================================
+
+/// Cached result of looking up `butler` on the PATH, set once per app run
+static BUTLER_INSTALLED: Mutex<Option<bool>> = Mutex::new(None);
+
+#[tauri::command]
+fn detect_butler() -> bool {
+    let mut cached = BUTLER_INSTALLED.lock().unwrap();
+    *cached.get_or_insert_with(|| tool_installed("butler"))
+}
+
+/// Progress of publish_to_itch, emitted as `publish-progress` events
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishProgress {
+    /// "export", "upload" or "done"
+    pub stage: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Upload progress, 0-100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+    /// Set on butler errors
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub error: bool,
+}
+
+/// Parse one line of `butler --json` output into an upload progress update
+fn parse_butler_line(line: &str) -> Option<PublishProgress> {
+    let value: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    let mut progress = PublishProgress {
+        stage: "upload".to_string(),
+        message: None,
+        percent: None,
+        error: false,
+    };
+    match value["type"].as_str()? {
+        "progress" => progress.percent = Some((value["progress"].as_f64()? * 100.0).clamp(0.0, 100.0)),
+        "log" => progress.message = Some(value["message"].as_str()?.to_string()),
+        "error" => {
+            progress.message = Some(value["message"].as_str()?.to_string());
+            progress.error = true;
+        }
+        _ => return None,
+    }
+    Some(progress)
+}
+
+/// Run `butler push`, streaming its progress; fails with butler's last error
+fn butler_push(app: &tauri::AppHandle, build_dir: &Path, destination: &str) -> Result<(), String> {
+    let build_dir = build_dir.to_string_lossy();
+    println!("[Publish] butler push {} {}", build_dir, destination);
+    let mut child = tool_process("butler", &["--json", "push", &build_dir, destination])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run butler: {}", e))?;
+
+    let stderr = child.stderr.take();
+    let stderr_thread = std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(mut stderr) = stderr {
+            let _ = std::io::Read::read_to_string(&mut stderr, &mut text);
+        }
+        text
+    });
+
+    let mut last_error = None;
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let Some(progress) = parse_butler_line(&line) else { continue };
+            if progress.error {
+                last_error = progress.message.clone();
+            }
+            let _ = app.emit("publish-progress", progress);
+        }
+    }
+
+    let status = child.wait().map_err(|e| format!("Failed to wait for butler: {}", e))?;
+    let stderr = stderr_thread.join().unwrap_or_default();
+    if status.success() {
+        return Ok(());
+    }
+    Err(last_error
+        .or_else(|| stderr.lines().rev().find(|l| !l.trim().is_empty()).map(|l| l.trim().to_string()))
+        .unwrap_or_else(|| format!("butler exited with {}", status)))
+}
+
+/// Release-export the project and push it to itch.io with butler.
+/// `target` is "user/game"; `channel` defaults to "html5".
+#[tauri::command]
+async fn publish_to_itch(
+    project_path: String,
+    target: String,
+    channel: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let target = target.trim().to_string();
+    if target.split('/').filter(|part| !part.is_empty()).count() != 2 {
+        return Err(format!("Invalid itch.io target '{}': expected \"user/game\"", target));
+    }
+    let channel = match channel.trim() {
+        "" => "html5".to_string(),
+        channel => channel.to_string(),
+    };
+
+    if !tokio::task::spawn_blocking(detect_butler).await.unwrap_or(false) {
+        return Err("butler (itch.io's upload tool) is not installed.\n\n\
+            1. Download it from: https://itch.io/docs/butler/installing.html\n\
+            2. Run `butler login` once to authorize your itch.io account\n\
+            3. Make sure `butler` is on your PATH, then publish again.".to_string());
+    }
+
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = project_godot_cmd(Path::new(&project_path), saved)?
+        .ok_or("Godot not found")?;
+
+    let destination = format!("{}:{}", target, channel);
+    tokio::task::spawn_blocking(move || {
+        let emit = |stage: &str, message: String| {
+            let _ = app.emit("publish-progress", PublishProgress {
+                stage: stage.to_string(),
+                message: Some(message),
+                percent: None,
+                error: false,
+            });
+        };
+
+        emit("export", "Exporting release build...".to_string());
+        let build_dir = release_web_build(&godot_cmd, Path::new(&project_path), &app)?;
+        check_export_size(&app, Path::new(&project_path), &build_dir);
+
+        emit("upload", format!("Pushing to {}", destination));
+        // butler uploads whole directories, so push a copy without the export hash
+        let staging = std::env::temp_dir().join(format!("tav-publish-{}", uuid::Uuid::new_v4()));
+        stage_web_build(&build_dir, &staging)?;
+        let pushed = butler_push(&app, &staging, &destination);
+        let _ = fs::remove_dir_all(&staging);
+        pushed?;
+
+        emit("done", format!("Published to {}", destination));
+        Ok(destination)
+    })
+    .await
+    .map_err(|e| format!("Publish task failed: {}", e))?
+}
+
+/// Content-Type for files served by the preview server: Godot web exports plus
+/// the assets games commonly load alongside them
+fn preview_mime_type(path: &Path) -> &'static str {
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "js" | "mjs" => "application/javascript",
+        "wasm" => "application/wasm",
+        "json" => "application/json",
+        "css" => "text/css",
+        "txt" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "ogg" => "audio/ogg",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "webm" => "video/webm",
+        "mp4" => "video/mp4",
+        "glb" => "model/gltf-binary",
+        "gltf" => "model/gltf+json",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Build outputs keep their names across re-exports, so they must be refetched;
+/// other assets can be cached briefly
+fn preview_cache_control(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "html" | "js" | "wasm" | "pck" | "json" => "no-cache",
+        _ => "public, max-age=3600",
+    }
+}
+
+fn preview_not_found_page(url: &str) -> String {
+    let url = url.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    format!(
+        r#"<!DOCTYPE html>
+<html><head><meta charset="utf-8"><title>404 - Not found</title>
+<style>body{{margin:0;height:100vh;display:flex;align-items:center;justify-content:center;background:#111;color:#ccc;font-family:system-ui,sans-serif}}h1{{margin:0 0 8px;font-size:20px;color:#fff}}code{{color:#f90}}</style>
+</head><body><div><h1>404 - Not found</h1><p><code>{}</code> is not part of this export.</p></div></body></html>"#,
+        url
+    )
+}
+
+/// File under `root` (canonicalized) that a request URL refers to. Fails with the
+/// HTTP status to send: 403 for paths that would escape `root`, 404 for missing files.
+fn resolve_preview_path(root: &Path, url: &str) -> Result<PathBuf, u16> {
+    let path = url.split(['?', '#']).next().unwrap_or("");
+    let path = urlencoding::decode(path).map_err(|_| 400u16)?;
+    let relative = Path::new(path.trim_start_matches('/'));
+    // Only plain names: no `..`, drive prefixes or roots that would replace `root` on join
+    if !relative.components().all(|c| matches!(c, std::path::Component::Normal(_) | std::path::Component::CurDir)) {
+        return Err(403);
+    }
+    let candidate = if relative.as_os_str().is_empty() {
+        root.join("index.html")
+    } else {
+        root.join(relative)
+    };
+    // Symlinks inside the export could still point outside it
+    let resolved = candidate.canonicalize().map_err(|_| 404u16)?;
+    if !resolved.starts_with(root) {
+        return Err(403);
+    }
+    if !resolved.is_file() {
+        return Err(404);
+    }
+    Ok(resolved)
+}
+
+/// A running static file server (web preview or raw assets)
+struct StaticServer {
+    port: u16,
+    root: PathBuf,
+    https: bool,
+    server: std::sync::Arc<tiny_http::Server>,
+}
+
+/// PEM certificate and private key for serving HTTPS
+#[derive(Clone)]
+struct TlsIdentity {
+    certificate: Vec<u8>,
+    private_key: Vec<u8>,
+}
+
+/// Self-signed certificate for localhost and 127.0.0.1, valid for a year
+fn generate_localhost_identity() -> Result<TlsIdentity, String> {
+    use openssl::asn1::Asn1Time;
+    use openssl::bn::{BigNum, MsbOption};
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::rsa::Rsa;
+    use openssl::x509::extension::SubjectAlternativeName;
+    use openssl::x509::{X509NameBuilder, X509};
+
+    let build = || -> Result<TlsIdentity, openssl::error::ErrorStack> {
+        let key = PKey::from_rsa(Rsa::generate(2048)?)?;
+        let mut name = X509NameBuilder::new()?;
+        name.append_entry_by_text("CN", "localhost")?;
+        let name = name.build();
+
+        let mut builder = X509::builder()?;
+        builder.set_version(2)?;
+        let mut serial = BigNum::new()?;
+        serial.rand(64, MsbOption::MAYBE_ZERO, false)?;
+        let serial = serial.to_asn1_integer()?;
+        builder.set_serial_number(&serial)?;
+        builder.set_subject_name(&name)?;
+        builder.set_issuer_name(&name)?;
+        builder.set_pubkey(&key)?;
+        let not_before = Asn1Time::days_from_now(0)?;
+        let not_after = Asn1Time::days_from_now(365)?;
+        builder.set_not_before(&not_before)?;
+        builder.set_not_after(&not_after)?;
+        let san = SubjectAlternativeName::new()
+            .dns("localhost")
+            .ip("127.0.0.1")
+            .build(&builder.x509v3_context(None, None))?;
+        builder.append_extension(san)?;
+        builder.sign(&key, MessageDigest::sha256())?;
+
+        Ok(TlsIdentity {
+            certificate: builder.build().to_pem()?,
+            private_key: key.private_key_to_pem_pkcs8()?,
+        })
+    };
+    build().map_err(|e| format!("Failed to generate certificate: {}", e))
+}
+
+/// Certificate for HTTPS previews: the given cert/key files, or a self-signed
+/// localhost certificate generated once and kept in the app config dir
+fn preview_tls_identity(cert_path: Option<&str>, key_path: Option<&str>) -> Result<TlsIdentity, String> {
+    match (cert_path, key_path) {
+        (Some(cert), Some(key)) => Ok(TlsIdentity {
+            certificate: fs::read(cert).map_err(|e| format!("Failed to read certificate {}: {}", cert, e))?,
+            private_key: fs::read(key).map_err(|e| format!("Failed to read private key {}: {}", key, e))?,
+        }),
+        (None, None) => {
+            let tls_dir = get_settings_path().with_file_name("tls");
+            let cert = tls_dir.join("localhost.crt");
+            let key = tls_dir.join("localhost.key");
+            if let (Ok(certificate), Ok(private_key)) = (fs::read(&cert), fs::read(&key)) {
+                return Ok(TlsIdentity { certificate, private_key });
+            }
+            let identity = generate_localhost_identity()?;
+            fs::create_dir_all(&tls_dir).map_err(|e| format!("Failed to create {}: {}", tls_dir.display(), e))?;
+            fs::write(&cert, &identity.certificate).map_err(|e| format!("Failed to save certificate: {}", e))?;
+            fs::write(&key, &identity.private_key).map_err(|e| format!("Failed to save private key: {}", e))?;
+            println!("[PreviewServer] Generated self-signed certificate: {}", cert.display());
+            Ok(identity)
+        }
+        _ => Err("Both a certificate and a private key path are needed for HTTPS".to_string()),
+    }
+}
+
+static STATIC_SERVERS: Mutex<Vec<StaticServer>> = Mutex::new(Vec::new());
+
+/// Serve one request from `root`, with traversal protection and preview headers
+fn handle_static_request(root: &Path, request: tiny_http::Request, tag: &str) {
+    let url = request.url().to_string();
+    let resolved = resolve_preview_path(root, &url);
+    
+    println!("[{}] Request: {} -> {:?}", tag, url, resolved);
+    
+    let response = match resolved {
+        Ok(file_path) => {
+            let content = match fs::read(&file_path) {
+                Ok(c) => c,
+                Err(e) => {
+                    eprintln!("[{}] Failed to read file: {}", tag, e);
+                    let r = tiny_http::Response::from_string("Read error")
+                        .with_status_code(500);
+                    let _ = request.respond(r);
+                    return;
+                }
+            };
+            
+            let mime = preview_mime_type(&file_path);
+            
+            tiny_http::Response::from_data(content)
+                .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], mime.as_bytes()).unwrap())
+                .with_header(tiny_http::Header::from_bytes(&b"Cache-Control"[..], preview_cache_control(&file_path).as_bytes()).unwrap())
+                .with_header(tiny_http::Header::from_bytes(&b"Cross-Origin-Opener-Policy"[..], &b"same-origin"[..]).unwrap())
+                .with_header(tiny_http::Header::from_bytes(&b"Cross-Origin-Embedder-Policy"[..], &b"require-corp"[..]).unwrap())
+                .with_header(tiny_http::Header::from_bytes(&b"Access-Control-Allow-Origin"[..], &b"*"[..]).unwrap())
+        }
+        Err(404) => {
+            println!("[{}] 404: {}", tag, url);
+            tiny_http::Response::from_string(preview_not_found_page(&url))
+                .with_header(tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html; charset=utf-8"[..]).unwrap())
+                .with_status_code(404)
+        }
+        Err(status) => {
+            println!("[{}] Rejected ({}): {}", tag, status, url);
+            tiny_http::Response::from_string(if status == 403 { "Forbidden" } else { "Bad request" })
+                .with_status_code(status)
+        }
+    };
+    
+    let _ = request.respond(response);
+}
+
+/// Serve `root` read-only until stopped with stop_preview_server. A server already
+/// running for `root` is reused; otherwise `preferred_port` is tried before 8080..9000.
+fn start_static_server(root: PathBuf, tag: &'static str, preferred_port: Option<u16>, tls: Option<TlsIdentity>) -> Result<u16, String> {
+    let root = root.canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", root.display(), e))?;
+    let https = tls.is_some();
+    
+    if let Some(running) = STATIC_SERVERS.lock().unwrap().iter().find(|s| s.root == root && s.https == https) {
+        println!("[{}] Reusing server on port {} for: {}", tag, running.port, root.display());
+        return Ok(running.port);
+    }
+    
+    println!("[{}] Starting server for: {}", tag, root.display());
+    
+    // Binding directly (instead of probing first) means no other process can take the port in between
+    let bind = |p: u16| match &tls {
+        Some(tls) => tiny_http::Server::https(("127.0.0.1", p), tiny_http::SslConfig {
+            certificate: tls.certificate.clone(),
+            private_key: tls.private_key.clone(),
+        }),
+        None => tiny_http::Server::http(("127.0.0.1", p)),
+    };
+    let (port, server) = preferred_port.into_iter().chain(8080..9000)
+        .find_map(|p| bind(p).ok().map(|s| (p, s)))
+        .ok_or("No available port found")?;
+    let server = std::sync::Arc::new(server);
+    
+    println!("[{}] Server running on {}://127.0.0.1:{}", tag, if https { "https" } else { "http" }, port);
+    
+    let thread_server = server.clone();
+    let thread_root = root.clone();
+    std::thread::spawn(move || {
+        for request in thread_server.incoming_requests() {
+            handle_static_request(&thread_root, request, tag);
+        }
+        println!("[{}] Server on port {} stopped", tag, port);
+    });
+    
+    STATIC_SERVERS.lock().unwrap().push(StaticServer { port, root, https, server });
+    Ok(port)
+}
+
+/// Serve an exported web build. With `https`, a self-signed localhost certificate
+/// (or `cert_path`/`key_path`) is used, since some browsers only allow
+/// SharedArrayBuffer (threaded exports) in a secure context.
+#[tauri::command]
+fn start_preview_server(
+    export_path: String,
+    preferred_port: Option<u16>,
+    https: Option<bool>,
+    cert_path: Option<String>,
+    key_path: Option<String>,
+    state: tauri::State<AppState>,
+) -> Result<u16, String> {
+    // Verify export path exists
+    let export_dir = Path::new(&export_path);
+    if !export_dir.exists() {
+        return Err(format!("Export directory does not exist: {}", export_path));
+    }
+    if !export_dir.join("index.html").exists() {
+        return Err(format!("index.html not found in: {}", export_path));
+    }
+    
+    let tls = if https.unwrap_or(false) {
+        Some(preview_tls_identity(cert_path.as_deref(), key_path.as_deref())?)
+    } else {
+        None
+    };
+    
+    let saved_port = state.settings.lock().unwrap().preview_port;
+    let port = start_static_server(export_dir.to_path_buf(), "PreviewServer", preferred_port.or(saved_port), tls)?;
+    
+    // Remember the port so the preview URL survives restarts
+    if saved_port.is_none() || preferred_port == Some(port) {
+        let mut settings = state.settings.lock().unwrap();
+        settings.preview_port = Some(port);
+        let _ = save_settings_to_disk(&settings);
+    }
+    Ok(port)
+}
+
+/// Serve a project's raw `assets/` folder over HTTP (e.g. for external viewers)
+#[tauri::command]
+fn start_asset_server(project_path: String) -> Result<u16, String> {
+    let assets_dir = Path::new(&project_path).join("assets");
+    if !assets_dir.is_dir() {
+        return Err(format!("Assets directory does not exist: {}", assets_dir.display()));
+    }
+    start_static_server(assets_dir, "AssetServer", None, None)
+}
+
+/// Stop a preview or asset server by port
+#[tauri::command]
+fn stop_preview_server(port: u16) -> Result<(), String> {
+    let mut servers = STATIC_SERVERS.lock().unwrap();
+    let index = servers.iter().position(|s| s.port == port)
+        .ok_or_else(|| format!("No server running on port {}", port))?;
+    let stopped = servers.remove(index);
+    stopped.server.unblock();
+    println!("[PreviewServer] Stopping server for {} on port {}", stopped.root.display(), port);
+    Ok(())
+}
+
+// ============================================================================
+// Game Playing Commands
+// ============================================================================
+
+/// Frames between AIController screenshots when a session doesn't set one
+const DEFAULT_CAPTURE_INTERVAL: u32 = 10;
+
+/// `AGENT_CAPTURE_INTERVAL` value for the AIController; lower captures more often
+/// at some cost to game performance
+fn capture_interval_env(interval: Option<u32>) -> String {
+    interval.unwrap_or(DEFAULT_CAPTURE_INTERVAL).max(1).to_string()
+}
+
+/// Godot `--resolution` value ("WIDTHxHEIGHT"), defaulting to 768x768
+fn window_resolution_arg(resolution: Option<&str>) -> Result<String, String> {
+    let Some(resolution) = resolution.map(str::trim).filter(|r| !r.is_empty()) else {
+        return Ok("768x768".to_string());
+    };
+    let valid = resolution
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+        .is_some_and(|(w, h)| w > 0 && h > 0);
+    if !valid {
+        return Err(format!("Invalid resolution '{}': expected WIDTHxHEIGHT, e.g. 1280x720", resolution));
+    }
+    Ok(resolution.to_string())
+}
+
+/// Godot `--position` value ("X,Y"), defaulting to 0,0. Negative values place the
+/// window on monitors left of or above the primary one.
+fn window_position_arg(position: Option<&str>) -> Result<String, String> {
+    let Some(position) = position.map(str::trim).filter(|p| !p.is_empty()) else {
+        return Ok("0,0".to_string());
+    };
+    let valid = position
+        .split_once(',')
+        .is_some_and(|(x, y)| x.trim().parse::<i32>().is_ok() && y.trim().parse::<i32>().is_ok());
+    if !valid {
+        return Err(format!("Invalid window position '{}': expected X,Y, e.g. 1920,0", position));
+    }
+    Ok(position.replace(' ', ""))
+}
 
 #[tauri::command]
 fn start_game_session(
     project_path: String,
     scene_path: String,
+    capture_interval: Option<u32>,
+    resolution: Option<String>,
+    position: Option<String>,
+    instrumentation: Option<bool>,
     state: tauri::State<AppState>,
 ) -> Result<String, String> {
-    let settings = state.settings.lock().unwrap();
-    let godot_cmd = settings
-        .godot_path
-        .clone()
-        .filter(|p| !p.is_empty() && Path::new(p).exists())
-        .or_else(|| find_godot_path())
+    let resolution = window_resolution_arg(resolution.as_deref())?;
+    let position = window_position_arg(position.as_deref())?;
+
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = project_godot_cmd(Path::new(&project_path), saved)?
         .ok_or("Godot not found")?;
-    drop(settings);
 
     // Create screenshots directory
     let screenshots_dir = Path::new(&project_path).join("user_screenshots");
@@ -2506,16 +5437,26 @@ fn start_game_session(
     let input_file = Path::new(&project_path).join("agent_input.json");
     fs::write(&input_file, "{}").ok();
 
+    // Native sessions use the bridge for file-based node captures
+    if let Err(e) = inject_kobold_bridge(Path::new(&project_path)) {
+        println!("[Session] Could not inject KoboldBridge: {}", e);
+    }
+
+    // Without instrumentation the AIController stays idle: no screenshots or state dumps.
+    // Set explicitly so an AGENT_ENABLED inherited from our own environment can't leak in.
+    let agent_enabled = if instrumentation.unwrap_or(true) { "true" } else { "false" };
+
     // Launch Godot windowed (not headless - we need rendering for screenshots)
     let child = Command::new(&godot_cmd)
         .args([
             "--path", &project_path,
-            "--resolution", "768x768",
-            "--position", "0,0",
+            "--resolution", &resolution,
+            "--position", &position,
             "--fixed-fps", "10",
             &scene_path
         ])
-        .env("AGENT_ENABLED", "true")
+        .env("AGENT_ENABLED", agent_enabled)
+        .env("AGENT_CAPTURE_INTERVAL", capture_interval_env(capture_interval))
         .current_dir(&project_path)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -2530,6 +5471,8 @@ fn start_game_session(
         project_path,
         scene_path,
         frame_count: 0,
+        log_tail: LogTail::default(),
+        started_at: std::time::Instant::now(),
     };
 
     state.game_sessions.lock().unwrap().insert(session_id.clone(), session);
@@ -2540,6 +5483,22 @@ fn start_game_session(
     Ok(session_id)
 }
 
+/// Newest `frame_NNNNNN.png` written by the AIController, with its frame number
+fn latest_screenshot(screenshots_dir: &Path) -> Option<(u32, PathBuf)> {
+    fs::read_dir(screenshots_dir).ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let num = name.to_str()?
+                .strip_prefix("frame_")?
+                .strip_suffix(".png")?
+                .parse::<u32>()
+                .ok()?;
+            Some((num, entry.path()))
+        })
+        .max_by_key(|(num, _)| *num)
+}
+
 #[tauri::command]
 fn get_game_frame(session_id: String, state: tauri::State<AppState>) -> Result<GameFrame, String> {
     let mut sessions = state.game_sessions.lock().unwrap();
@@ -2547,57 +5506,35 @@ fn get_game_frame(session_id: String, state: tauri::State<AppState>) -> Result<G
 
     let screenshots_dir = Path::new(&session.project_path).join("user_screenshots");
     
-    // Find latest screenshot
-    let mut latest_screenshot = String::new();
-    let mut latest_num = 0;
-
-    if let Ok(entries) = fs::read_dir(&screenshots_dir) {
-        for entry in entries.flatten() {
-            if let Some(name) = entry.file_name().to_str() {
-                if name.starts_with("frame_") && name.ends_with(".png") {
-                    if let Ok(num) = name
-                        .strip_prefix("frame_")
-                        .and_then(|s| s.strip_suffix(".png"))
-                        .unwrap_or("0")
-                        .parse::<u32>()
-                    {
-                        if num > latest_num {
-                            latest_num = num;
-                            latest_screenshot = entry.path().to_string_lossy().to_string();
-                        }
-                    }
-                }
-            }
+    // Find latest screenshot and read it as base64
+    let (latest_num, screenshot_b64) = match latest_screenshot(&screenshots_dir) {
+        Some((num, path)) => {
+            let data = fs::read(&path).unwrap_or_default();
+            (num, base64::engine::general_purpose::STANDARD.encode(&data))
         }
-    }
-
-    // Read screenshot as base64
-    let screenshot_b64 = if !latest_screenshot.is_empty() && Path::new(&latest_screenshot).exists() {
-        let data = fs::read(&latest_screenshot).unwrap_or_default();
-        base64::engine::general_purpose::STANDARD.encode(&data)
-    } else {
-        String::new()
+        None => (0, String::new()),
     };
 
-    // Read game state
+    // Read game state (retry once in case an older bridge was mid-write)
     let state_path = Path::new(&session.project_path).join("game_state.json");
+    let read_state = || -> Option<serde_json::Value> {
+        serde_json::from_str(&fs::read_to_string(&state_path).ok()?).ok()
+    };
     let game_state = if state_path.exists() {
-        let content = fs::read_to_string(&state_path).unwrap_or_else(|_| "{}".to_string());
-        serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
+        read_state()
+            .or_else(|| {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                read_state()
+            })
+            .unwrap_or(serde_json::json!({}))
     } else {
         serde_json::json!({})
     };
 
     // Read logs
     let logs_path = Path::new(&session.project_path).join("game.log");
-    let logs: Vec<String> = if logs_path.exists() {
-        fs::read_to_string(&logs_path)
-            .unwrap_or_default()
-            .lines()
-            .rev()
-            .take(20)
-            .map(|s| s.to_string())
-            .collect()
+    let logs: Vec<String> = if session.log_tail.read_new(&logs_path).is_ok() {
+        session.log_tail.lines.iter().rev().take(20).cloned().collect()
     } else {
         vec![]
     };
@@ -2612,18 +5549,52 @@ fn get_game_frame(session_id: String, state: tauri::State<AppState>) -> Result<G
     })
 }
 
+/// Write via a temp file and rename so the game never reads a partial file.
+/// `agent_input.json` must always be written this way: the AIController polls it
+/// every frame and would otherwise drop or misparse a half-written action.
+fn write_file_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)
+}
+
 #[tauri::command]
-fn send_game_action(
+fn get_game_logs(
     session_id: String,
-    action: GameAction,
+    max_lines: usize,
+    level_filter: Option<String>,
+    since_line: Option<usize>,
     state: tauri::State<AppState>,
-) -> Result<(), String> {
-    let sessions = state.game_sessions.lock().unwrap();
-    let session = sessions.get(&session_id).ok_or("Session not found")?;
+) -> Result<GameLogs, String> {
+    let mut sessions = state.game_sessions.lock().unwrap();
+    let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
+
+    let logs_path = Path::new(&session.project_path).join("game.log");
+    if logs_path.exists() {
+        session.log_tail.read_new(&logs_path)
+            .map_err(|e| format!("Failed to read game log: {}", e))?;
+    }
+
+    Ok(GameLogs {
+        lines: session.log_tail.query(since_line.unwrap_or(0), level_filter.as_deref(), max_lines),
+        next_line: session.log_tail.next_line(),
+    })
+}
+
+#[tauri::command]
+fn send_game_action(
+    session_id: String,
+    action: GameAction,
+    state: tauri::State<AppState>,
+) -> Result<(), String> {
+    let sessions = state.game_sessions.lock().unwrap();
+    let session = sessions.get(&session_id).ok_or("Session not found")?;
 
     let input_path = Path::new(&session.project_path).join("agent_input.json");
     let action_json = serde_json::to_string(&action).map_err(|e| e.to_string())?;
-    fs::write(&input_path, action_json).map_err(|e| format!("Failed to write action: {}", e))?;
+    write_file_atomic(&input_path, &action_json).map_err(|e| format!("Failed to write action: {}", e))?;
 
     // Wait for game to process
     std::thread::sleep(std::time::Duration::from_millis(50));
@@ -2647,7 +5618,7 @@ fn execute_actions(
 
     for action in actions {
         let action_json = serde_json::to_string(&action).map_err(|e| e.to_string())?;
-        fs::write(&input_path, action_json).map_err(|e| format!("Failed to write action: {}", e))?;
+        write_file_atomic(&input_path, &action_json).map_err(|e| format!("Failed to write action: {}", e))?;
         executed += 1;
         std::thread::sleep(std::time::Duration::from_millis(50));
     }
@@ -2655,6 +5626,219 @@ fn execute_actions(
     Ok(executed)
 }
 
+/// Move and resize a running session's window without restarting it.
+/// KoboldBridge applies the change on its next poll.
+#[tauri::command]
+fn move_game_window(session_id: String, x: i32, y: i32, w: u32, h: u32, state: tauri::State<AppState>) -> Result<(), String> {
+    if w == 0 || h == 0 {
+        return Err("Window width and height must be greater than zero".to_string());
+    }
+    let project_path = {
+        let sessions = state.game_sessions.lock().unwrap();
+        sessions.get(&session_id).ok_or("Session not found")?.project_path.clone()
+    };
+
+    let tav_dir = Path::new(&project_path).join(".tav");
+    fs::create_dir_all(&tav_dir).map_err(|e| format!("Failed to create .tav directory: {}", e))?;
+    let request = serde_json::json!({"x": x, "y": y, "width": w, "height": h});
+    write_file_atomic(&tav_dir.join("window_request.json"), &request.to_string())
+        .map_err(|e| format!("Failed to write window request: {}", e))
+}
+
+/// A frame grabbed by capture_now
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedFrame {
+    /// Base64 PNG
+    pub screenshot: String,
+    /// True when the session didn't answer and this is the latest auto-captured frame
+    pub stale: bool,
+}
+
+/// How long capture_now waits for the AIController before falling back
+const CAPTURE_NOW_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Grab the current frame of a running session right away (e.g. for a bug report)
+#[tauri::command]
+async fn capture_now(session_id: String, state: tauri::State<'_, AppState>) -> Result<CapturedFrame, String> {
+    let project_path = {
+        let sessions = state.game_sessions.lock().unwrap();
+        sessions.get(&session_id).ok_or("Session not found")?.project_path.clone()
+    };
+    let project = Path::new(&project_path);
+    let screenshots_dir = project.join("user_screenshots");
+    let input_path = project.join("agent_input.json");
+
+    let capture_id = uuid::Uuid::new_v4().simple().to_string();
+    let capture_path = screenshots_dir.join(format!("capture_{}.png", capture_id));
+    let action = serde_json::json!({"function": "capture", "args": [capture_id]}).to_string();
+    write_file_atomic(&input_path, &action).map_err(|e| format!("Failed to write action: {}", e))?;
+
+    let deadline = std::time::Instant::now() + CAPTURE_NOW_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        if let Ok(data) = fs::read(&capture_path) {
+            let _ = fs::remove_file(&capture_path);
+            return Ok(CapturedFrame {
+                screenshot: base64::engine::general_purpose::STANDARD.encode(&data),
+                stale: false,
+            });
+        }
+    }
+
+    // Not instrumented (or too busy): withdraw the request and use the last auto-capture
+    if fs::read_to_string(&input_path).is_ok_and(|content| content == action) {
+        let _ = write_file_atomic(&input_path, "{}");
+    }
+    let (_, path) = latest_screenshot(&screenshots_dir)
+        .ok_or("Session did not respond to the capture request and has no frames yet")?;
+    let data = fs::read(&path).map_err(|e| format!("Failed to read frame: {}", e))?;
+    Ok(CapturedFrame {
+        screenshot: base64::engine::general_purpose::STANDARD.encode(&data),
+        stale: true,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ControlsTestResult {
+    pub analysis: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Wait for the AIController to write a screenshot newer than `after_frame`
+async fn wait_for_screenshot(screenshots_dir: &Path, after_frame: u32) -> Result<(u32, String), String> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(3);
+    loop {
+        if let Some((num, path)) = latest_screenshot(screenshots_dir) {
+            if num > after_frame {
+                // Give the game a moment to finish writing the PNG
+                tokio::time::sleep(tokio::time::Duration::from_millis(30)).await;
+                let data = fs::read(&path).map_err(|e| format!("Failed to read frame: {}", e))?;
+                return Ok((num, base64::engine::general_purpose::STANDARD.encode(&data)));
+            }
+        }
+        if std::time::Instant::now() >= deadline {
+            return Err("Timed out waiting for a game frame (is the AIController enabled?)".to_string());
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+}
+
+/// Native equivalent of the bridge's test-controls flow: capture, hold the given
+/// input actions for `duration_ms`, capture again, release, and compare with Gemini.
+#[tauri::command]
+async fn test_controls_in_session(
+    session_id: String,
+    keys: Vec<String>,
+    duration_ms: u32,
+    prompt: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<ControlsTestResult, String> {
+    let project_path = {
+        let sessions = state.game_sessions.lock().unwrap();
+        sessions.get(&session_id).ok_or("Session not found")?.project_path.clone()
+    };
+    let project = Path::new(&project_path);
+    let screenshots_dir = project.join("user_screenshots");
+    let input_path = project.join("agent_input.json");
+
+    let send = |function: &str, action: &str| {
+        let json = serde_json::json!({"function": function, "args": [action]}).to_string();
+        write_file_atomic(&input_path, &json).map_err(|e| format!("Failed to write action: {}", e))
+    };
+
+    let current = latest_screenshot(&screenshots_dir).map(|(num, _)| num).unwrap_or(0);
+    let (before_frame, before) = wait_for_screenshot(&screenshots_dir, current).await?;
+
+    // The controller consumes one action per read, so space the writes out
+    for key in &keys {
+        send("press", key)?;
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(duration_ms as u64)).await;
+    let after = wait_for_screenshot(&screenshots_dir, before_frame).await;
+
+    // Always release, even if the after frame never arrived
+    for key in &keys {
+        send("release", key)?;
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+    let (_, after) = after?;
+
+    let prompt = prompt.unwrap_or_else(|| format!("Did pressing {} have the expected effect?", keys.join(", ")));
+    let analysis = test_game_controls(
+        before.clone(), after.clone(), keys, duration_ms, prompt, None, None, state,
+    ).await?;
+
+    Ok(ControlsTestResult { analysis, before, after })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStatus {
+    pub running: bool,
+    pub frame_count: u32,
+    pub uptime_secs: u64,
+    pub pid: u32,
+}
+
+/// Cheap liveness check for polling, e.g. to detect crashes. Exited sessions are
+/// removed and reported once with `running: false`.
+#[tauri::command]
+fn get_session_status(session_id: String, state: tauri::State<AppState>) -> Result<SessionStatus, String> {
+    let mut sessions = state.game_sessions.lock().unwrap();
+    let session = sessions.get_mut(&session_id).ok_or("Session not found")?;
+
+    let running = session.is_running();
+    let screenshots_dir = Path::new(&session.project_path).join("user_screenshots");
+    if let Some((num, _)) = latest_screenshot(&screenshots_dir) {
+        session.frame_count = session.frame_count.max(num);
+    }
+    let status = SessionStatus {
+        running,
+        frame_count: session.frame_count,
+        uptime_secs: session.started_at.elapsed().as_secs(),
+        pid: session.process.as_ref().map_or(0, |p| p.id()),
+    };
+
+    if !running {
+        println!("[Session] {} is no longer running, removing it", session_id);
+        sessions.remove(&session_id);
+    }
+    Ok(status)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub id: String,
+    pub project_path: String,
+    pub scene_path: String,
+    pub frame_count: u32,
+    pub running: bool,
+}
+
+/// Every tracked session, so the UI can reconnect after a reload or clean up
+/// exited ones with stop_game_session
+#[tauri::command]
+fn list_game_sessions(state: tauri::State<AppState>) -> Vec<SessionSummary> {
+    let mut sessions = state.game_sessions.lock().unwrap();
+    let mut summaries: Vec<SessionSummary> = sessions
+        .values_mut()
+        .map(|session| SessionSummary {
+            running: session.is_running(),
+            id: session.id.clone(),
+            project_path: session.project_path.clone(),
+            scene_path: session.scene_path.clone(),
+            frame_count: session.frame_count,
+        })
+        .collect();
+    summaries.sort_by(|a, b| a.project_path.cmp(&b.project_path).then_with(|| a.id.cmp(&b.id)));
+    summaries
+}
+
 #[tauri::command]
 fn stop_game_session(session_id: String, state: tauri::State<AppState>) -> Result<(), String> {
     let mut sessions = state.game_sessions.lock().unwrap();
@@ -2686,6 +5870,87 @@ pub struct PlaytestEvent {
 pub struct PlaytestConfig {
     pub objective: String,
     pub max_duration_secs: Option<u64>,
+    /// Frames between AIController screenshots (default 10)
+    pub capture_interval: Option<u32>,
+    /// "png" (default) or "jpeg"; JPEG frames are much smaller to upload
+    pub image_format: Option<String>,
+    /// JPEG quality 1-100 (default 80)
+    pub jpeg_quality: Option<u8>,
+}
+
+const DEFAULT_JPEG_QUALITY: u8 = 80;
+/// 768x768 is the optimal input size for Gemini vision
+const DEFAULT_FRAME_MAX_SIZE: u32 = 768;
+
+/// Encoding applied to screenshots before they are sent to Gemini
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FrameFormat {
+    Png,
+    Jpeg { quality: u8 },
+}
+
+impl FrameFormat {
+    fn parse(format: Option<&str>, quality: Option<u8>) -> Result<Self, String> {
+        match format.map(|f| f.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("png") => Ok(FrameFormat::Png),
+            Some("jpeg") | Some("jpg") => Ok(FrameFormat::Jpeg {
+                quality: quality.unwrap_or(DEFAULT_JPEG_QUALITY).clamp(1, 100),
+            }),
+            Some(other) => Err(format!("Unsupported image format: {} (expected png or jpeg)", other)),
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            FrameFormat::Png => "image/png",
+            FrameFormat::Jpeg { .. } => "image/jpeg",
+        }
+    }
+}
+
+/// Size that fits `width`x`height` within `max_size` on the long edge, keeping the
+/// aspect ratio. None when the frame is already small enough (or `max_size` is 0).
+fn fit_frame_size(width: u32, height: u32, max_size: u32) -> Option<(u32, u32)> {
+    let long_edge = width.max(height);
+    if max_size == 0 || long_edge <= max_size {
+        return None;
+    }
+    let scale = |side: u32| ((side as u64 * max_size as u64 + long_edge as u64 / 2) / long_edge as u64).max(1) as u32;
+    Some((scale(width), scale(height)))
+}
+
+/// Downscales and re-encodes a base64 PNG frame for upload, returning its mime type
+/// and base64 data. Small PNG frames are passed through untouched.
+fn prepare_frame(png_b64: String, format: FrameFormat, max_size: u32) -> Result<(&'static str, String), String> {
+    let png = base64::engine::general_purpose::STANDARD.decode(png_b64.trim())
+        .map_err(|e| format!("Failed to decode frame: {}", e))?;
+    let fits = png_dimensions(&png).is_some_and(|(w, h)| fit_frame_size(w, h, max_size).is_none());
+    if format == FrameFormat::Png && fits {
+        return Ok((format.mime_type(), png_b64));
+    }
+
+    let mut image = image::load_from_memory(&png)
+        .map_err(|e| format!("Failed to read frame: {}", e))?;
+    if let Some((width, height)) = fit_frame_size(image.width(), image.height(), max_size) {
+        image = image.resize_exact(width, height, image::imageops::FilterType::Triangle);
+    }
+
+    let mut encoded = Vec::new();
+    match format {
+        FrameFormat::Png => image
+            .write_to(&mut std::io::Cursor::new(&mut encoded), image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode PNG: {}", e))?,
+        FrameFormat::Jpeg { quality } => image::codecs::jpeg::JpegEncoder::new_with_quality(&mut encoded, quality)
+            .encode_image(&image.to_rgb8())
+            .map_err(|e| format!("Failed to encode JPEG: {}", e))?,
+    }
+    Ok((format.mime_type(), base64::engine::general_purpose::STANDARD.encode(&encoded)))
+}
+
+/// Gemini `inlineData` part for a base64 PNG frame, downscaled and re-encoded by prepare_frame
+fn frame_part(png_b64: String, format: FrameFormat, max_size: u32) -> Result<serde_json::Value, String> {
+    let (mime_type, data) = prepare_frame(png_b64, format, max_size)?;
+    Ok(serde_json::json!({"inlineData": {"mimeType": mime_type, "data": data}}))
 }
 
 /// Game action tools for Gemini to call
@@ -2770,17 +6035,17 @@ async fn run_playtest(
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     let settings = state.settings.lock().unwrap().clone();
-    let godot_cmd = settings.godot_path.clone()
-        .filter(|p| !p.is_empty() && Path::new(p).exists())
-        .or_else(|| find_godot_path())
+    let godot_cmd = project_godot_cmd(Path::new(&project_path), settings.godot_path.clone())?
         .ok_or("Godot not found")?;
     let api_key = settings.gemini_key.clone()
-        .or_else(|| read_env_file_key(&project_path, "GEMINI_API_KEY"))
+        .or_else(|| read_env_file_key(&project_path, "GEMINI_API_KEY", settings.env_path.as_deref()))
         .ok_or("Gemini API key required. Add it in Settings or .env.local")?;
     
     println!("[Playtest] Starting with API key: {}...", &api_key[..12.min(api_key.len())]);
     
     let max_steps = config.max_duration_secs.unwrap_or(30) as u32;
+    let frame_format = FrameFormat::parse(config.image_format.as_deref(), config.jpeg_quality)?;
+    let frame_max_size = settings.frame_max_size.unwrap_or(DEFAULT_FRAME_MAX_SIZE);
     
     let _ = app.emit("playtest-event", PlaytestEvent {
         event_type: "start".to_string(),
@@ -2810,6 +6075,7 @@ async fn run_playtest(
             "res://scenes/main.tscn"
         ])
         .env("AGENT_ENABLED", "true")
+        .env("AGENT_CAPTURE_INTERVAL", capture_interval_env(config.capture_interval))
         .current_dir(&project_path)
         .spawn()
         .map_err(|e| format!("Failed to start Godot: {}", e))?;
@@ -2905,6 +6171,13 @@ RESPOND WITH JSON ONLY:
             }
             _ => continue,
         };
+        let (mime_type, screenshot_b64) = match prepare_frame(screenshot_b64, frame_format, frame_max_size) {
+            Ok(frame) => frame,
+            Err(e) => {
+                println!("[Playtest] Step {}: {}", step, e);
+                continue;
+            }
+        };
 
         // Build prompt with history
         let history = if observations.len() > 3 {
@@ -2927,7 +6200,7 @@ RESPOND WITH JSON ONLY:
                 "contents": [{
                     "parts": [
                         {"text": prompt},
-                        {"inlineData": {"mimeType": "image/png", "data": screenshot_b64}}
+                        {"inlineData": {"mimeType": mime_type, "data": screenshot_b64}}
                     ]
                 }],
                 "generationConfig": {
@@ -2951,10 +6224,10 @@ RESPOND WITH JSON ONLY:
                     });
                     continue;
                 }
-                let text = json["candidates"][0]["content"]["parts"][0]["text"]
-                    .as_str()
-                    .unwrap_or("")
-                    .to_string();
+                let text = gemini_response_text(&json).unwrap_or_else(|e| {
+                    println!("[Playtest] {}", e);
+                    String::new()
+                });
                 println!("[Playtest] Got response: {}...", &text[..50.min(text.len())]);
                 text
             }
@@ -2965,11 +6238,7 @@ RESPOND WITH JSON ONLY:
         };
 
         // Parse response
-        let clean = ai_text.trim()
-            .trim_start_matches("```json")
-            .trim_start_matches("```")
-            .trim_end_matches("```")
-            .trim();
+        let clean = extract_json(&ai_text);
 
         if let Ok(data) = serde_json::from_str::<serde_json::Value>(clean) {
             let observation = data["observation"].as_str().unwrap_or("").to_string();
@@ -3058,6 +6327,62 @@ pub struct NitrogenStatus {
     pub nitrogen_path: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStatus {
+    /// Path to the Godot executable, if found
+    pub godot: Option<String>,
+    pub goose: bool,
+    pub beads: bool,
+    pub godot_mcp: bool,
+    pub nitrogen: bool,
+}
+
+/// How long detect_all_tools reuses its last result before shelling out again
+const TOOL_STATUS_TTL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Detect every external tool at once. Checks run concurrently and the result is
+/// cached for TOOL_STATUS_TTL so repeated UI polls stay cheap; pass `force` to
+/// re-check right after an install.
+#[tauri::command]
+async fn detect_all_tools(
+    force: Option<bool>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<ToolStatus, String> {
+    if !force.unwrap_or(false) {
+        if let Some((checked_at, status)) = state.tool_status.lock().unwrap().as_ref() {
+            if checked_at.elapsed() < TOOL_STATUS_TTL {
+                return Ok(status.clone());
+            }
+        }
+    }
+
+    let saved_godot = state.settings.lock().unwrap().godot_path.clone();
+    let blocking = |check: fn() -> bool| tokio::task::spawn_blocking(check);
+    let nitrogen_app = app.clone();
+
+    let (godot, goose, beads, godot_mcp, nitrogen) = tokio::join!(
+        tokio::task::spawn_blocking(move || saved_godot_cmd(saved_godot)),
+        blocking(detect_goose),
+        blocking(detect_beads),
+        blocking(detect_godot_mcp),
+        tokio::task::spawn_blocking(move || {
+            find_checkpoint_path(&nitrogen_app).0 || find_nitrogen_path().is_some()
+        }),
+    );
+    let status = ToolStatus {
+        godot: godot.map_err(|e| format!("Godot detection failed: {}", e))?,
+        goose: goose.unwrap_or(false),
+        beads: beads.unwrap_or(false),
+        godot_mcp: godot_mcp.unwrap_or(false),
+        nitrogen: nitrogen.unwrap_or(false),
+    };
+
+    *state.tool_status.lock().unwrap() = Some((std::time::Instant::now(), status.clone()));
+    Ok(status)
+}
+
 #[tauri::command]
 fn check_nitrogen_installed(app: tauri::AppHandle) -> NitrogenStatus {
     let python_path = which_python();
@@ -3281,9 +6606,7 @@ async fn run_playtest_nitrogen(
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     let settings = state.settings.lock().unwrap().clone();
-    let godot_cmd = settings.godot_path.clone()
-        .filter(|p| !p.is_empty() && Path::new(p).exists())
-        .or_else(|| find_godot_path())
+    let godot_cmd = project_godot_cmd(Path::new(&project_path), settings.godot_path.clone())?
         .ok_or("Godot not found")?;
     
     let max_steps = config.max_duration_secs.unwrap_or(60) as u32;
@@ -3330,6 +6653,7 @@ async fn run_playtest_nitrogen(
     let mut godot = Command::new(&godot_cmd)
         .args(["--path", &project_path, "--resolution", "768x768", "--position", "0,0", "res://scenes/main.tscn"])
         .env("AGENT_ENABLED", "true")
+        .env("AGENT_CAPTURE_INTERVAL", capture_interval_env(config.capture_interval))
         .current_dir(&project_path)
         .spawn()
         .map_err(|e| format!("Failed to start Godot: {}", e))?;
@@ -3550,6 +6874,83 @@ async fn run_playtest_nitrogen(
     Ok(summary)
 }
 
+/// Join the text parts of the first Gemini candidate. When there is no text,
+/// report why (safety block, token limit, ...) instead of a bare "no text".
+fn gemini_response_text(response: &serde_json::Value) -> Result<String, String> {
+    let candidate = &response["candidates"][0];
+    let text: String = candidate["content"]["parts"].as_array()
+        .map(|parts| parts.iter()
+            .filter(|part| !part["thought"].as_bool().unwrap_or(false))
+            .filter_map(|part| part["text"].as_str())
+            .collect())
+        .unwrap_or_default();
+    if !text.is_empty() {
+        return Ok(text);
+    }
+
+    if let Some(reason) = response["promptFeedback"]["blockReason"].as_str() {
+        return Err(format!("Gemini blocked the prompt ({})", reason));
+    }
+    match candidate["finishReason"].as_str() {
+        Some(reason) if reason != "STOP" => Err(format!("Gemini returned no text (finishReason: {})", reason)),
+        _ => Err("No text in Gemini response".to_string()),
+    }
+}
+
+/// Pull the JSON payload out of a model reply, dropping code fences and surrounding prose
+fn extract_json(text: &str) -> &str {
+    let trimmed = text.trim();
+    let start = trimmed.find(['{', '[']);
+    let end = trimmed.rfind(['}', ']']);
+    match (start, end) {
+        (Some(start), Some(end)) if start < end => &trimmed[start..=end],
+        _ => trimmed,
+    }
+}
+
+/// Model for the frame analysis commands, chosen for its spatial reasoning in games
+const GEMINI_VISION_MODEL: &str = "gemini-robotics-er-1.5-preview";
+
+/// One generateContent call; returns the reply text
+async fn gemini_generate_contents(api_key: &str, model: &str, contents: &serde_json::Value, config: serde_json::Value) -> Result<String, String> {
+    let request_body = serde_json::json!({
+        "contents": contents,
+        "generationConfig": config
+    });
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent?key={}",
+            model, api_key
+        ))
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| format!("API request failed: {}", e))?;
+
+    let response_json: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))?;
+
+    if let Some(error) = response_json.get("error") {
+        return Err(format!("Gemini API error: {}", error));
+    }
+
+    gemini_response_text(&response_json)
+}
+
+/// Single-turn request with the given parts (images and text) to the vision model
+async fn gemini_generate(api_key: &str, parts: Vec<serde_json::Value>, config: serde_json::Value) -> Result<String, String> {
+    gemini_generate_contents(api_key, GEMINI_VISION_MODEL, &serde_json::json!([{"parts": parts}]), config).await
+}
+
+async fn request_trajectory_text(api_key: &str, contents: &serde_json::Value) -> Result<String, String> {
+    gemini_generate_contents(api_key, GEMINI_VISION_MODEL, contents, generation_config(None, Some(0), 0)).await
+}
+
 #[tauri::command]
 async fn plan_trajectory(
     screenshot_b64: String,
@@ -3559,6 +6960,8 @@ async fn plan_trajectory(
 ) -> Result<Trajectory, String> {
     let settings = state.settings.lock().unwrap().clone();
     let api_key = settings.gemini_key.ok_or("Gemini API key not set")?;
+    let max_size = settings.frame_max_size.unwrap_or(DEFAULT_FRAME_MAX_SIZE);
+    let image = frame_part(screenshot_b64, FrameFormat::Png, max_size)?;
 
     let prompt = format!(
         r#"You control a game character. Available functions:
@@ -3573,61 +6976,55 @@ Respond ONLY with valid JSON in this exact format:
         game_functions, objective
     );
 
-    let request_body = serde_json::json!({
-        "contents": [{
-            "parts": [
-                {"text": prompt},
-                {
-                    "inlineData": {
-                        "mimeType": "image/png",
-                        "data": screenshot_b64
-                    }
-                }
-            ]
-        }],
-        "generationConfig": {
-            "temperature": 0.5,
-            "thinkingConfig": {"thinkingBudget": 0}
-        }
-    });
+    let mut contents = serde_json::json!([{
+        "role": "user",
+        "parts": [{"text": prompt}, image]
+    }]);
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-robotics-er-1.5-preview:generateContent?key={}",
-            api_key
-        ))
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("API request failed: {}", e))?;
-
-    let response_json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    // Extract text from Gemini response
-    let text = response_json["candidates"][0]["content"]["parts"][0]["text"]
-        .as_str()
-        .ok_or("No text in response")?;
+    let text = request_trajectory_text(&api_key, &contents).await?;
+    if let Ok(trajectory) = serde_json::from_str::<Trajectory>(extract_json(&text)) {
+        return Ok(trajectory);
+    }
 
-    // Parse the JSON response
-    let trajectory: Trajectory = serde_json::from_str(text)
-        .map_err(|e| format!("Failed to parse trajectory: {} - Response: {}", e, text))?;
+    // Ask once more for bare JSON before giving up
+    println!("[Trajectory] Response was not valid JSON, retrying");
+    if let Some(turns) = contents.as_array_mut() {
+        turns.push(serde_json::json!({"role": "model", "parts": [{"text": text}]}));
+        turns.push(serde_json::json!({"role": "user", "parts": [{
+            "text": "That response was not valid JSON. Return only valid JSON in the requested format, with no code fences or extra text."
+        }]}));
+    }
+    let retry_text = request_trajectory_text(&api_key, &contents).await?;
+    let trajectory: Trajectory = serde_json::from_str(extract_json(&retry_text))
+        .map_err(|e| format!("Failed to parse trajectory: {} - Response: {}", e, retry_text))?;
 
     Ok(trajectory)
 }
 
+/// Gemini generationConfig for the analysis commands; callers can trade quality for
+/// latency/cost per request. A thinking budget of 0 turns thinking off.
+fn generation_config(temperature: Option<f64>, thinking_budget: Option<u32>, default_budget: u32) -> serde_json::Value {
+    serde_json::json!({
+        "temperature": temperature.unwrap_or(0.5),
+        "thinkingConfig": {"thinkingBudget": thinking_budget.unwrap_or(default_budget)}
+    })
+}
+
 #[tauri::command]
 async fn analyze_game_frame(
     screenshot_b64: String,
     prompt: String,
+    temperature: Option<f64>,
+    thinking_budget: Option<u32>,
+    image_format: Option<String>,
+    jpeg_quality: Option<u8>,
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     let settings = state.settings.lock().unwrap().clone();
     let api_key = settings.gemini_key.ok_or("Gemini API key not set. Please add your Gemini API key in Settings.")?;
+    let format = FrameFormat::parse(image_format.as_deref(), jpeg_quality)?;
+    let max_size = settings.frame_max_size.unwrap_or(DEFAULT_FRAME_MAX_SIZE);
+    let image = frame_part(screenshot_b64, format, max_size)?;
 
     let full_prompt = format!(
         r#"You are analyzing a video game screenshot to validate and test gameplay.
@@ -3644,66 +7041,151 @@ Be specific about locations (left/right/center, foreground/background) and refer
         prompt
     );
 
-    let request_body = serde_json::json!({
-        "contents": [{
-            "parts": [
-                {
-                    "inlineData": {
-                        "mimeType": "image/png",
-                        "data": screenshot_b64
-                    }
-                },
-                {"text": full_prompt}
-            ]
-        }],
-        "generationConfig": {
-            "temperature": 0.5,
-            "thinkingConfig": {"thinkingBudget": 1024}
-        }
-    });
+    let parts = vec![image, serde_json::json!({"text": full_prompt})];
+    gemini_generate(&api_key, parts, generation_config(temperature, thinking_budget, 1024)).await
+}
 
-    // Use robotics model for superior spatial reasoning in games
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-robotics-er-1.5-preview:generateContent?key={}",
-            api_key
-        ))
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedObject {
+    pub label: String,
+    /// [x, y, width, height] normalized to 0-1000 of the frame
+    pub bbox: [f64; 4],
+    #[serde(default)]
+    pub confidence: f64,
+}
 
-    let response_json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameDetections {
+    #[serde(default)]
+    pub objects: Vec<DetectedObject>,
+    #[serde(default)]
+    pub summary: String,
+}
 
-    // Check for API errors
-    if let Some(error) = response_json.get("error") {
-        return Err(format!("Gemini API error: {}", error));
+fn parse_frame_detections(text: &str) -> Result<FrameDetections, String> {
+    let json = extract_json(text);
+    // Some replies are just the object list without a summary
+    if json.starts_with('[') {
+        let objects = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse detections: {}", e))?;
+        return Ok(FrameDetections { objects, summary: String::new() });
     }
+    serde_json::from_str(json).map_err(|e| format!("Failed to parse detections: {}", e))
+}
 
-    // Extract text from Gemini response
-    let text = response_json["candidates"][0]["content"]["parts"][0]["text"]
-        .as_str()
-        .ok_or_else(|| format!("No text in response: {:?}", response_json))?;
+/// Like analyze_game_frame, but returns detected objects with bounding boxes for UI overlays
+#[tauri::command]
+async fn analyze_game_frame_structured(
+    screenshot_b64: String,
+    prompt: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<FrameDetections, String> {
+    let settings = state.settings.lock().unwrap().clone();
+    let api_key = settings.gemini_key.ok_or("Gemini API key not set. Please add your Gemini API key in Settings.")?;
+    let max_size = settings.frame_max_size.unwrap_or(DEFAULT_FRAME_MAX_SIZE);
+    let image = frame_part(screenshot_b64, FrameFormat::Png, max_size)?;
 
-    Ok(text.to_string())
+    let full_prompt = format!(
+        r#"You are analyzing a video game screenshot to detect objects.
+
+User request: {}
+
+Detect the key objects (player, NPCs, items, UI elements) and respond with ONLY this JSON:
+{{
+  "objects": [{{"label": "player", "bbox": [x, y, width, height], "confidence": 0.9}}],
+  "summary": "One or two sentences describing the scene and anything wrong with it"
+}}
+
+bbox values are integers normalized to 0-1000, with [0, 0] at the top-left of the image.
+confidence is between 0 and 1."#,
+        prompt
+    );
+
+    let parts = vec![image, serde_json::json!({"text": full_prompt})];
+    let text = gemini_generate(&api_key, parts, generation_config(None, None, 1024)).await?;
+
+    parse_frame_detections(&text)
 }
 
+/// Max screenshots sent in a single Gemini request by analyze_game_frames
+const MAX_FRAMES_PER_REQUEST: usize = 6;
+
+/// Combine per-chunk analyses; a single chunk is returned as-is
+fn merge_frame_analyses(chunks: &[(usize, usize, String)]) -> String {
+    if let [(_, _, text)] = chunks {
+        return text.clone();
+    }
+    chunks.iter()
+        .map(|(first, last, text)| format!("## Frames {}-{}\n\n{}", first, last, text.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Analyze a sequence of screenshots (e.g. a cutscene) in as few Gemini calls as possible.
+/// Frames are sent in order, chunked by MAX_FRAMES_PER_REQUEST.
 #[tauri::command]
+async fn analyze_game_frames(
+    frames: Vec<String>,
+    prompt: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    if frames.is_empty() {
+        return Err("No frames to analyze".to_string());
+    }
+    let settings = state.settings.lock().unwrap().clone();
+    let api_key = settings.gemini_key.ok_or("Gemini API key not set. Please add your Gemini API key in Settings.")?;
+    let max_size = settings.frame_max_size.unwrap_or(DEFAULT_FRAME_MAX_SIZE);
+
+    let mut results = Vec::new();
+    for (i, chunk) in frames.chunks(MAX_FRAMES_PER_REQUEST).enumerate() {
+        let first = i * MAX_FRAMES_PER_REQUEST + 1;
+        let last = first + chunk.len() - 1;
+
+        let full_prompt = format!(
+            r#"You are analyzing a sequence of video game screenshots to validate and test gameplay.
+
+The {} images above are frames {}-{} of {}, in chronological order (Image 1 is frame {}).
+
+User request: {}
+
+Analyze the sequence and provide:
+1. **What happens**: Describe how the scene changes from frame to frame
+2. **Issues found**: Any visual bugs, clipping, popping, missing elements, or unexpected behavior, with the frame number
+3. **Validation result**: Does the sequence match what was requested? What works, what doesn't?
+
+Reference frames by number and be specific about locations."#,
+            chunk.len(), first, last, frames.len(), first, prompt
+        );
+
+        let mut parts = chunk.iter()
+            .map(|data| frame_part(data.clone(), FrameFormat::Png, max_size))
+            .collect::<Result<Vec<_>, _>>()?;
+        parts.push(serde_json::json!({"text": full_prompt}));
+
+        let text = gemini_generate(&api_key, parts, generation_config(None, None, 1024)).await?;
+        results.push((first, last, text));
+    }
+
+    Ok(merge_frame_analyses(&results))
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn test_game_controls(
     before_b64: String,
     after_b64: String,
     keys: Vec<String>,
     duration_ms: u32,
     prompt: String,
+    temperature: Option<f64>,
+    thinking_budget: Option<u32>,
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     let settings = state.settings.lock().unwrap().clone();
     let api_key = settings.gemini_key.ok_or("Gemini API key not set")?;
+    let max_size = settings.frame_max_size.unwrap_or(DEFAULT_FRAME_MAX_SIZE);
+    let before = frame_part(before_b64, FrameFormat::Png, max_size)?;
+    let after = frame_part(after_b64, FrameFormat::Png, max_size)?;
 
     let keys_desc = keys.join(", ");
     let full_prompt = format!(
@@ -3727,56 +7209,809 @@ Be specific about what changed between the frames."#,
         prompt = prompt
     );
 
-    let request_body = serde_json::json!({
-        "contents": [{
-            "parts": [
-                {
-                    "inlineData": {
-                        "mimeType": "image/png",
-                        "data": before_b64
-                    }
-                },
-                {
-                    "inlineData": {
-                        "mimeType": "image/png",
-                        "data": after_b64
-                    }
-                },
-                {"text": full_prompt}
-            ]
-        }],
-        "generationConfig": {
-            "temperature": 0.5,
-            "thinkingConfig": {"thinkingBudget": 2048}
+    let parts = vec![before, after, serde_json::json!({"text": full_prompt})];
+    gemini_generate(&api_key, parts, generation_config(temperature, thinking_budget, 2048)).await
+}
+
+/// Per-channel difference below which a pixel counts as unchanged, so PNG/JPEG
+/// noise and dithering don't register as regressions
+const FRAME_DIFF_DEFAULT_TOLERANCE: u8 = 16;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameDiff {
+    pub pixel_diff_percent: f64,
+    pub changed_pixels: u64,
+    /// PNG: changed pixels in red over a dimmed grayscale of the after frame
+    pub diff_image_b64: String,
+}
+
+/// Compare two same-sized RGBA buffers, returning the number of changed pixels
+/// and the highlighted diff image
+fn diff_rgba(before: &[u8], after: &[u8], tolerance: u8) -> (u64, Vec<u8>) {
+    let mut changed = 0;
+    let mut diff = Vec::with_capacity(after.len());
+    for (b, a) in before.chunks_exact(4).zip(after.chunks_exact(4)) {
+        if b.iter().zip(a).any(|(x, y)| x.abs_diff(*y) > tolerance) {
+            changed += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let luma = (a[0] as u32 * 299 + a[1] as u32 * 587 + a[2] as u32 * 114) / 1000;
+            let dimmed = (luma / 3) as u8;
+            diff.extend_from_slice(&[dimmed, dimmed, dimmed, 255]);
         }
+    }
+    (changed, diff)
+}
+
+fn decode_frame(b64: &str) -> Result<image::RgbaImage, String> {
+    // Accept data URLs as produced by canvas.toDataURL()
+    let b64 = b64.trim();
+    let b64 = b64.split_once(";base64,").map_or(b64, |(_, data)| data);
+    let bytes = base64::engine::general_purpose::STANDARD.decode(b64)
+        .map_err(|e| format!("Failed to decode frame: {}", e))?;
+    Ok(image::load_from_memory(&bytes)
+        .map_err(|e| format!("Failed to read frame: {}", e))?
+        .to_rgba8())
+}
+
+/// Deterministic pixel diff of two captured frames for visual regression checks;
+/// no API key or network needed, unlike test_game_controls
+#[tauri::command]
+fn compare_frames(before_b64: String, after_b64: String, tolerance: Option<u8>) -> Result<FrameDiff, String> {
+    let before = decode_frame(&before_b64)?;
+    let after = decode_frame(&after_b64)?;
+    let (width, height) = (after.width(), after.height());
+    if (before.width(), before.height()) != (width, height) {
+        return Err(format!(
+            "Frames have different sizes ({}x{} vs {}x{})",
+            before.width(), before.height(), width, height
+        ));
+    }
+
+    let (changed, diff) = diff_rgba(&before.into_raw(), &after.into_raw(), tolerance.unwrap_or(FRAME_DIFF_DEFAULT_TOLERANCE));
+    let total = (width as u64 * height as u64).max(1);
+    let mut encoded = Vec::new();
+    image::RgbaImage::from_raw(width, height, diff)
+        .ok_or("Failed to build diff image")?
+        .write_to(&mut std::io::Cursor::new(&mut encoded), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode diff image: {}", e))?;
+
+    Ok(FrameDiff {
+        pixel_diff_percent: changed as f64 * 100.0 / total as f64,
+        changed_pixels: changed,
+        diff_image_b64: base64::engine::general_purpose::STANDARD.encode(&encoded),
+    })
+}
+
+/// Locate a capture PNG written by the bridge's `to_disk` mode. The game writes
+/// under its user data dir; fall back to the project's `user_screenshots/captures`.
+fn resolve_capture_path(project: &Path, capture: &str) -> PathBuf {
+    let path = PathBuf::from(capture);
+    if path.is_absolute() && path.exists() {
+        return path;
+    }
+    let file_name = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    project.join("user_screenshots/captures").join(file_name)
+}
+
+/// How long request_node_capture waits for the bridge to write its results
+const NODE_CAPTURE_TIMEOUT_SECS: u64 = 20;
+
+/// Capture a node from multiple angles in a native game session.
+/// Writes `.tav/capture_request.json` for KoboldBridge to pick up, then polls
+/// for `.tav/capture_result.json` and returns the angle -> base64 PNG map.
+#[tauri::command]
+async fn request_node_capture(
+    session_id: String,
+    node_name: String,
+    options: Option<serde_json::Value>,
+    state: tauri::State<'_, AppState>,
+) -> Result<std::collections::HashMap<String, String>, String> {
+    let project_path = {
+        let sessions = state.game_sessions.lock().unwrap();
+        let session = sessions.get(&session_id).ok_or("Session not found")?;
+        session.project_path.clone()
+    };
+
+    let tav_dir = Path::new(&project_path).join(".tav");
+    fs::create_dir_all(&tav_dir).map_err(|e| format!("Failed to create .tav directory: {}", e))?;
+    let result_path = tav_dir.join("capture_result.json");
+    let _ = fs::remove_file(&result_path);
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let request = serde_json::json!({
+        "request_id": request_id,
+        "node": node_name,
+        "options": options.unwrap_or_else(|| serde_json::json!({})),
     });
+    write_file_atomic(&tav_dir.join("capture_request.json"), &request.to_string())
+        .map_err(|e| format!("Failed to write capture request: {}", e))?;
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-robotics-er-1.5-preview:generateContent?key={}",
-            api_key
-        ))
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+    let Some(result) = await_bridge_result(&result_path, &request_id, NODE_CAPTURE_TIMEOUT_SECS).await else {
+        let _ = fs::remove_file(tav_dir.join("capture_request.json"));
+        return Err(format!("Timed out waiting for capture of {} (is KoboldBridge running?)", node_name));
+    };
 
-    let response_json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    if let Some(error) = result["error"].as_str() {
+        return Err(format!("Capture failed: {}", error));
+    }
+    let to_disk = result["mode"].as_str() == Some("disk");
+    let mut captures = std::collections::HashMap::new();
+    for (angle, data) in result["captures"].as_object().into_iter().flatten() {
+        let Some(data) = data.as_str() else { continue };
+        // Disk mode returns PNG paths; read them back like session frames
+        let data = if to_disk {
+            let path = resolve_capture_path(Path::new(&project_path), data);
+            let bytes = fs::read(&path)
+                .map_err(|e| format!("Failed to read capture {}: {}", path.display(), e))?;
+            base64::engine::general_purpose::STANDARD.encode(&bytes)
+        } else {
+            data.to_string()
+        };
+        captures.insert(angle.clone(), data);
+    }
+    Ok(captures)
+}
+
+/// Capture a node with explicit resolution and lighting, e.g. for higher-quality analysis
+#[tauri::command]
+async fn capture_node(
+    session_id: String,
+    node_name: String,
+    angles: Option<Vec<String>>,
+    resolution: Option<u32>,
+    add_light: Option<bool>,
+    state: tauri::State<'_, AppState>,
+) -> Result<std::collections::HashMap<String, String>, String> {
+    let mut options = serde_json::json!({
+        "resolution": resolution.unwrap_or(512),
+        "add_light": add_light.unwrap_or(false),
+    });
+    if let Some(angles) = angles {
+        options["angles"] = serde_json::json!(angles);
+    }
+    request_node_capture(session_id, node_name, Some(options), state).await
+}
+
+/// How long hot_reload_script waits for the bridge to report back
+const SCRIPT_RELOAD_TIMEOUT_SECS: u64 = 5;
+
+/// KoboldBridge's report for a script reload request
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptReloadResult {
+    pub reloaded: Vec<String>,
+    /// Scripts the game hasn't loaded yet (they pick up the change when it does)
+    pub skipped: Vec<String>,
+    pub failed: std::collections::HashMap<String, String>,
+}
+
+/// `res://` path of a project script given as `res://...`, a relative or an absolute path
+fn script_res_path(project: &Path, script_path: &str) -> Result<String, String> {
+    if !script_path.ends_with(".gd") {
+        return Err(format!("Only .gd scripts can be hot-reloaded; re-export to apply {}", script_path));
+    }
+    to_res_path(project, script_path)
+}
+
+/// True when only scripts changed, so running sessions can reload them in place.
+/// Scene and resource changes still need a full re-export.
+fn is_script_only_change(files: &[String]) -> bool {
+    !files.is_empty() && files.iter().all(|f| f.ends_with(".gd"))
+}
+
+/// Ask a session's KoboldBridge to reload scripts, returning the request id
+fn write_reload_request(project: &Path, scripts: &[String]) -> Result<String, String> {
+    let tav_dir = project.join(".tav");
+    fs::create_dir_all(&tav_dir).map_err(|e| format!("Failed to create .tav directory: {}", e))?;
+    let _ = fs::remove_file(tav_dir.join("reload_result.json"));
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let request = serde_json::json!({
+        "request_id": request_id,
+        "scripts": scripts,
+    });
+    write_file_atomic(&tav_dir.join("reload_request.json"), &request.to_string())
+        .map_err(|e| format!("Failed to write reload request: {}", e))?;
+    Ok(request_id)
+}
+
+/// Signal running sessions of a project after a script-only change
+fn request_session_script_reload(app: &tauri::AppHandle, project: &Path, files: &[String]) {
+    let state = app.state::<AppState>();
+    let has_session = state.game_sessions.lock().unwrap()
+        .values()
+        .any(|s| Path::new(&s.project_path) == project);
+    if !has_session {
+        return;
+    }
+    let scripts: Vec<String> = files.iter().filter_map(|f| script_res_path(project, f).ok()).collect();
+    match write_reload_request(project, &scripts) {
+        Ok(_) => println!("[HotReload] Requested reload of {:?}", scripts),
+        Err(e) => println!("[HotReload] {}", e),
+    }
+}
+
+/// Poll for a bridge result file answering `request_id`, consuming it when found
+async fn await_bridge_result(result_path: &Path, request_id: &str, timeout_secs: u64) -> Option<serde_json::Value> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+    while std::time::Instant::now() < deadline {
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+
+        let Ok(content) = fs::read_to_string(result_path) else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&content) else {
+            continue;
+        };
+        if value["request_id"].as_str() == Some(request_id) {
+            let _ = fs::remove_file(result_path);
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Reload a changed script inside a running session without re-exporting
+#[tauri::command]
+async fn hot_reload_script(
+    session_id: String,
+    script_path: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<ScriptReloadResult, String> {
+    let project_path = {
+        let sessions = state.game_sessions.lock().unwrap();
+        let session = sessions.get(&session_id).ok_or("Session not found")?;
+        session.project_path.clone()
+    };
+    let project = Path::new(&project_path);
+    let script = script_res_path(project, &script_path)?;
+    let request_id = write_reload_request(project, std::slice::from_ref(&script))?;
+    let result_path = project.join(".tav/reload_result.json");
+
+    let Some(value) = await_bridge_result(&result_path, &request_id, SCRIPT_RELOAD_TIMEOUT_SECS).await else {
+        let _ = fs::remove_file(project.join(".tav/reload_request.json"));
+        return Err(format!("Timed out waiting for reload of {} (is KoboldBridge running?)", script));
+    };
+    let result: ScriptReloadResult = serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse reload result: {}", e))?;
+    if let Some(error) = result.failed.get(&script) {
+        return Err(format!("Failed to reload {}: {}", script, error));
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// Scene Smoke Test - Headless load check
+// ============================================================================
+
+/// Frames per simulated second in smoke_test_scene (run with --fixed-fps)
+const SMOKE_TEST_FPS: u64 = 60;
+/// Extra wall-clock time allowed for Godot to start and load before it is stopped
+const SMOKE_TEST_GRACE_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmokeTestResult {
+    /// Godot quit by itself with a zero exit code and printed no script errors
+    pub exited_cleanly: bool,
+    pub errors: Vec<String>,
+}
+
+/// Script errors, parse errors and failed assertions in Godot's output, each
+/// with the `at:` location that follows it. Engine errors only count when they
+/// point at a project file (res://).
+fn parse_godot_errors(lines: &[String]) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+    let mut last_was_error = false;
+    for line in lines.iter().map(|l| l.trim()) {
+        if let Some(location) = line.strip_prefix("at:") {
+            if last_was_error {
+                if let Some(error) = errors.last_mut() {
+                    error.push_str(&format!(" (at {})", location.trim()));
+                }
+            }
+            last_was_error = false;
+            continue;
+        }
+        let script_error = ["SCRIPT ERROR:", "USER SCRIPT ERROR:", "Parse Error:"].iter().any(|p| line.starts_with(p))
+            || line.contains("Assertion failed");
+        let engine_error = ["ERROR:", "USER ERROR:"].iter().any(|p| line.starts_with(p)) && line.contains("res://");
+        last_was_error = script_error || engine_error;
+        if last_was_error {
+            errors.push(line.to_string());
+        }
+    }
+    errors
+}
+
+/// Load a scene headlessly for `seconds` of game time and report script errors,
+/// as a quick check before committing (no AI involved, unlike the playtests)
+#[tauri::command]
+async fn smoke_test_scene(
+    project_path: String,
+    scene_path: String,
+    seconds: u64,
+    state: tauri::State<'_, AppState>,
+) -> Result<SmokeTestResult, String> {
+    let project = PathBuf::from(&project_path);
+    if !project.join("project.godot").exists() {
+        return Err("Not a Godot project (project.godot not found)".to_string());
+    }
+    let scene = to_res_path(&project, &scene_path)?;
+    if !res_path(&project, &scene).is_some_and(|p| p.is_file()) {
+        return Err(format!("Scene not found: {}", scene));
+    }
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot_cmd = project_godot_cmd(&project, saved)?.ok_or("Godot not found")?;
+
+    tokio::task::spawn_blocking(move || {
+        let frames = (seconds.max(1) * SMOKE_TEST_FPS).to_string();
+        let fps = SMOKE_TEST_FPS.to_string();
+        let mut command = Command::new(&godot_cmd);
+        command
+            .args(["--headless", "--path", &project_path, "--fixed-fps", &fps, "--quit-after", &frames, &scene])
+            .current_dir(&project);
+        #[cfg(windows)]
+        command.creation_flags(CREATE_NO_WINDOW);
+        println!("[SmokeTest] Running {} for {}s", scene, seconds);
+
+        let output = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let collected = output.clone();
+        let timeout = std::time::Duration::from_secs(seconds + SMOKE_TEST_GRACE_SECS);
+        let run = run_streamed(command, "Godot", Some(timeout), move |line| {
+            collected.lock().unwrap().push(line);
+        });
+
+        let mut errors = parse_godot_errors(&output.lock().unwrap());
+        let exited_cleanly = run.is_ok() && errors.is_empty();
+        if let Err(e) = run {
+            if errors.is_empty() {
+                errors.push(e);
+            }
+        }
+        println!("[SmokeTest] {}: {} errors", scene, errors.len());
+        Ok(SmokeTestResult { exited_cleanly, errors })
+    })
+    .await
+    .map_err(|e| format!("Smoke test task failed: {}", e))?
+}
+
+// ============================================================================
+// Scene Snapshots - Node tree regression diffs
+// ============================================================================
+
+/// How long snapshot_scene waits for the bridge
+const SNAPSHOT_TIMEOUT_SECS: u64 = 5;
+
+/// A node saved in a scene, by path relative to the scene root ("." for the root)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneNode {
+    pub path: String,
+    /// Built-in class; unknown for instanced sub-scenes read from a .tscn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+}
+
+/// Scene tree baseline, as saved under `.tav/snapshots/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneSnapshot {
+    pub scene: String,
+    pub nodes: Vec<SceneNode>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSnapshot {
+    /// Path of the snapshot file, usable as `baseline_json` for diff_scene once read
+    pub file: String,
+    pub scene: String,
+    pub node_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SceneChange {
+    /// "added", "removed", "moved" or "retyped"
+    pub kind: String,
+    pub path: String,
+    /// Previous path of a moved node
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SceneDiff {
+    pub changes: Vec<SceneChange>,
+}
+
+/// `key="value"` attribute of a .tscn section header
+fn tscn_attr(line: &str, key: &str) -> Option<String> {
+    let needle = format!(" {}=\"", key);
+    let start = line.find(&needle)? + needle.len();
+    let end = line[start..].find('"')?;
+    Some(line[start..start + end].to_string())
+}
+
+/// Node tree of a .tscn file, in the same shape as a bridge snapshot
+fn parse_tscn_nodes(scene: &str) -> Vec<SceneNode> {
+    scene.lines()
+        .filter(|line| line.starts_with("[node "))
+        .filter_map(|line| {
+            let name = tscn_attr(line, "name")?;
+            let path = match tscn_attr(line, "parent").as_deref() {
+                None => ".".to_string(),
+                Some(".") => name,
+                Some(parent) => format!("{}/{}", parent, name),
+            };
+            Some(SceneNode { path, class: tscn_attr(line, "type") })
+        })
+        .collect()
+}
+
+/// Added, removed, moved (same name and class under a new parent) and retyped nodes
+fn diff_scene_nodes(baseline: &[SceneNode], current: &[SceneNode]) -> Vec<SceneChange> {
+    let leaf = |node: &SceneNode| node.path.rsplit('/').next().unwrap_or("").to_string();
+    let same_class = |a: &SceneNode, b: &SceneNode| match (&a.class, &b.class) {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    };
+    let change = |kind: &str, path: &str| SceneChange {
+        kind: kind.to_string(),
+        path: path.to_string(),
+        from: None,
+        detail: None,
+    };
+
+    let mut changes = Vec::new();
+    for node in current {
+        let Some(old) = baseline.iter().find(|b| b.path == node.path) else { continue };
+        if !same_class(old, node) {
+            changes.push(SceneChange {
+                detail: Some(format!("{} -> {}", old.class.as_deref().unwrap_or("?"), node.class.as_deref().unwrap_or("?"))),
+                ..change("retyped", &node.path)
+            });
+        }
+    }
+
+    let mut removed: Vec<&SceneNode> = baseline.iter().filter(|b| !current.iter().any(|c| c.path == b.path)).collect();
+    let mut added: Vec<&SceneNode> = current.iter().filter(|c| !baseline.iter().any(|b| b.path == c.path)).collect();
+    added.retain(|node| {
+        let Some(i) = removed.iter().position(|old| leaf(old) == leaf(node) && same_class(old, node)) else {
+            return true;
+        };
+        let old = removed.remove(i);
+        changes.push(SceneChange { from: Some(old.path.clone()), ..change("moved", &node.path) });
+        false
+    });
+    changes.extend(removed.iter().map(|node| change("removed", &node.path)));
+    changes.extend(added.iter().map(|node| change("added", &node.path)));
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// Save the running scene's node tree to `.tav/snapshots/` as a diff baseline
+#[tauri::command]
+async fn snapshot_scene(session_id: String, state: tauri::State<'_, AppState>) -> Result<SavedSnapshot, String> {
+    let project_path = {
+        let sessions = state.game_sessions.lock().unwrap();
+        sessions.get(&session_id).ok_or("Session not found")?.project_path.clone()
+    };
+    let tav_dir = Path::new(&project_path).join(".tav");
+    fs::create_dir_all(&tav_dir).map_err(|e| format!("Failed to create .tav directory: {}", e))?;
+    let result_path = tav_dir.join("snapshot_result.json");
+    let _ = fs::remove_file(&result_path);
+
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let request = serde_json::json!({"request_id": request_id});
+    write_file_atomic(&tav_dir.join("snapshot_request.json"), &request.to_string())
+        .map_err(|e| format!("Failed to write snapshot request: {}", e))?;
+
+    let Some(result) = await_bridge_result(&result_path, &request_id, SNAPSHOT_TIMEOUT_SECS).await else {
+        let _ = fs::remove_file(tav_dir.join("snapshot_request.json"));
+        return Err("Timed out waiting for scene snapshot (is KoboldBridge running?)".to_string());
+    };
+    if let Some(error) = result["error"].as_str() {
+        return Err(format!("Snapshot failed: {}", error));
+    }
+    let snapshot: SceneSnapshot = serde_json::from_value(result)
+        .map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+
+    let snapshots_dir = tav_dir.join("snapshots");
+    fs::create_dir_all(&snapshots_dir).map_err(|e| format!("Failed to create snapshots directory: {}", e))?;
+    let stem = Path::new(&snapshot.scene).file_stem().and_then(|s| s.to_str()).unwrap_or("scene");
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let file = snapshots_dir.join(format!("{}-{}.json", stem, timestamp));
+    let json = serde_json::to_string_pretty(&snapshot).map_err(|e| e.to_string())?;
+    fs::write(&file, json).map_err(|e| format!("Failed to save snapshot: {}", e))?;
+
+    println!("[Snapshot] Saved {} nodes of {} to {}", snapshot.nodes.len(), snapshot.scene, file.display());
+    Ok(SavedSnapshot {
+        file: file.to_string_lossy().to_string(),
+        scene: snapshot.scene,
+        node_count: snapshot.nodes.len(),
+    })
+}
+
+/// Compare a scene file's node tree against a baseline from snapshot_scene
+#[tauri::command]
+fn diff_scene(project_path: String, scene_path: String, baseline_json: String) -> Result<SceneDiff, String> {
+    let project = Path::new(&project_path);
+    let scene_file = res_path(project, &scene_path).unwrap_or_else(|| project.join(&scene_path));
+    let scene = fs::read_to_string(&scene_file)
+        .map_err(|e| format!("Failed to read scene {}: {}", scene_file.display(), e))?;
+    let baseline: SceneSnapshot = serde_json::from_str(&baseline_json)
+        .map_err(|e| format!("Invalid baseline snapshot: {}", e))?;
+
+    Ok(SceneDiff {
+        changes: diff_scene_nodes(&baseline.nodes, &parse_tscn_nodes(&scene)),
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentAdded {
+    /// Path of the new node relative to the scene root
+    pub node_path: String,
+    pub script_path: String,
+    /// "godot" when the scene was re-saved by Godot, "text" for a direct .tscn edit
+    pub method: String,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptExport {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_name: Option<String>,
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptSignal {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentInfo {
+    pub name: String,
+    /// "component" (added to scenes) or "autoload" (global singleton)
+    pub kind: String,
+    pub path: String,
+    pub base: String,
+    pub description: String,
+    pub exports: Vec<ScriptExport>,
+    pub signals: Vec<ScriptSignal>,
+}
+
+/// Base class, `##` description, exported variables and signals declared in a GDScript file
+fn parse_gdscript_api(name: &str, source: &str) -> (String, String, Vec<ScriptExport>, Vec<ScriptSignal>) {
+    let mut base = String::new();
+    let mut description = String::new();
+    let mut exports = Vec::new();
+    let mut signals = Vec::new();
+    for line in source.lines() {
+        if let Some(rest) = line.strip_prefix("extends ") {
+            base = rest.trim().to_string();
+        } else if let Some(doc) = line.strip_prefix("## ").filter(|_| description.is_empty()) {
+            let doc = doc.trim();
+            description = doc.strip_prefix(&format!("{} - ", name)).unwrap_or(doc).to_string();
+        } else if let Some((_, decl)) = line.strip_prefix("@export").and_then(|l| l.split_once("var ")) {
+            let (decl, default) = match decl.split_once('=') {
+                Some((decl, default)) => (decl, Some(default.trim().trim_end_matches(':').to_string())),
+                None => (decl, None),
+            };
+            let (var_name, type_name) = match decl.split_once(':') {
+                Some((var_name, type_name)) => (var_name, Some(type_name.trim().to_string())),
+                None => (decl, None),
+            };
+            exports.push(ScriptExport { name: var_name.trim().to_string(), type_name, default });
+        } else if let Some(decl) = line.strip_prefix("signal ") {
+            let (signal_name, args) = decl.split_once('(').unwrap_or((decl, ""));
+            signals.push(ScriptSignal {
+                name: signal_name.trim().to_string(),
+                args: args.trim_end().trim_end_matches(')').split(',')
+                    .map(str::trim)
+                    .filter(|a| !a.is_empty())
+                    .map(String::from)
+                    .collect(),
+            });
+        }
+    }
+    (base, description, exports, signals)
+}
+
+/// Built-in components and autoloads with their exports and signals, so the UI and
+/// agents can discover the building blocks every project ships with
+#[tauri::command]
+fn get_component_catalog() -> Vec<ComponentInfo> {
+    let components = BUILTIN_COMPONENTS.iter().map(|c| (c.class_name, "component", c.path(), c.source));
+    let autoloads = [
+        ("EventBus", "autoload", AUTOLOAD_FILES[0].0.to_string(), EVENT_BUS_GD),
+        ("GameState", "autoload", AUTOLOAD_FILES[1].0.to_string(), GAME_STATE_GD),
+    ];
+    components
+        .chain(autoloads)
+        .map(|(name, kind, path, source)| {
+            let (base, description, exports, signals) = parse_gdscript_api(name, source);
+            ComponentInfo {
+                name: name.to_string(),
+                kind: kind.to_string(),
+                path,
+                base,
+                description,
+                exports,
+                signals,
+            }
+        })
+        .collect()
+}
+
+/// `base` if no child of `parent` uses it yet, otherwise `base2`, `base3`, ...
+fn unique_child_name(nodes: &[SceneNode], parent: &str, base: &str) -> String {
+    let taken = |name: &str| {
+        let path = if parent == "." { name.to_string() } else { format!("{}/{}", parent, name) };
+        nodes.iter().any(|n| n.path == path)
+    };
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..).map(|n| format!("{}{}", base, n)).find(|name| !taken(name)).unwrap()
+}
+
+/// Add a scripted node under `parent` by editing .tscn text: reuses or appends the
+/// script's ext_resource and inserts the node before any [connection] sections.
+fn add_component_tscn(scene: &str, parent: &str, name: &str, base: &str, script_res: &str) -> String {
+    insert_tscn_node(scene, "Script", script_res, |id| vec![
+        format!("[node name=\"{}\" type=\"{}\" parent=\"{}\"]", name, base, parent),
+        format!("script = ExtResource(\"{}\")", id),
+    ])
+}
+
+/// Instance a packed scene under `parent`, the same way add_component_tscn adds a script
+fn add_instance_tscn(scene: &str, parent: &str, name: &str, scene_res: &str) -> String {
+    insert_tscn_node(scene, "PackedScene", scene_res, |id| vec![
+        format!("[node name=\"{}\" parent=\"{}\" instance=ExtResource(\"{}\")]", name, parent, id),
+    ])
+}
+
+/// Shared .tscn edit: find or add the ext_resource for `resource_path`, then insert
+/// the node lines `node(id)` returns
+fn insert_tscn_node(scene: &str, resource_type: &str, resource_path: &str, node: impl FnOnce(&str) -> Vec<String>) -> String {
+    let mut lines: Vec<String> = scene.lines().map(String::from).collect();
+    let ext_resources: Vec<(usize, Option<String>, Option<String>)> = lines.iter().enumerate()
+        .filter(|(_, l)| l.starts_with("[ext_resource "))
+        .map(|(i, l)| (i, tscn_attr(l, "path"), tscn_attr(l, "id")))
+        .collect();
+
+    let id = match ext_resources.iter().find(|(_, path, _)| path.as_deref() == Some(resource_path)) {
+        Some((_, _, Some(id))) => id.clone(),
+        _ => {
+            let taken: Vec<&str> = ext_resources.iter().filter_map(|(_, _, id)| id.as_deref()).collect();
+            let id = (taken.len() + 1..).map(|n| n.to_string()).find(|id| !taken.contains(&id.as_str())).unwrap();
+            let line = format!("[ext_resource type=\"{}\" path=\"{}\" id=\"{}\"]", resource_type, resource_path, id);
+            match ext_resources.last() {
+                Some((i, _, _)) => lines.insert(i + 1, line),
+                None => {
+                    let header = lines.iter().position(|l| l.starts_with("[gd_scene")).map_or(0, |i| i + 1);
+                    lines.splice(header..header, [String::new(), line]);
+                }
+            }
+            // load_steps counts resources + 1; Godot omits it for resource-free scenes
+            if let Some(header) = lines.iter_mut().find(|l| l.starts_with("[gd_scene")) {
+                if let Some(start) = header.find(" load_steps=").map(|i| i + " load_steps=".len()) {
+                    let end = header[start..].find(|c: char| !c.is_ascii_digit()).map_or(header.len(), |e| start + e);
+                    if let Ok(steps) = header[start..end].parse::<u32>() {
+                        header.replace_range(start..end, &(steps + 1).to_string());
+                    }
+                }
+            }
+            id
+        }
+    };
+
+    let mut node = node(&id);
+    node.push(String::new());
+    match lines.iter().position(|l| l.starts_with("[connection ") || l.starts_with("[editable ")) {
+        Some(i) => {
+            lines.splice(i..i, node);
+        }
+        None => {
+            if lines.last().is_some_and(|l| !l.is_empty()) {
+                lines.push(String::new());
+            }
+            lines.extend(node);
+            lines.pop();
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Instance a built-in component (HealthComponent, MovementComponent2D, ...) as a child
+/// of `node_path` in a saved scene. Godot re-saves the scene when available; otherwise
+/// the .tscn is edited directly.
+#[tauri::command]
+fn add_component_to_scene(
+    project_path: String,
+    scene_path: String,
+    node_path: String,
+    component: String,
+    state: tauri::State<AppState>,
+) -> Result<ComponentAdded, String> {
+    let comp = find_builtin_component(&component).ok_or_else(|| {
+        let names: Vec<_> = BUILTIN_COMPONENTS.iter().map(|c| c.class_name).collect();
+        format!("Unknown component: {} (available: {})", component, names.join(", "))
+    })?;
+    let project = Path::new(&project_path);
+    let scene_file = res_path(project, &scene_path).unwrap_or_else(|| project.join(&scene_path));
+    let scene = fs::read_to_string(&scene_file)
+        .map_err(|e| format!("Failed to read scene {}: {}", scene_file.display(), e))?;
+
+    let nodes = parse_tscn_nodes(&scene);
+    let parent = match node_path.trim().trim_start_matches("./").trim_end_matches('/') {
+        "" => ".".to_string(),
+        path => path.to_string(),
+    };
+    if !nodes.iter().any(|n| n.path == parent) {
+        return Err(format!("Node not found in scene: {}", node_path));
+    }
+    let name = unique_child_name(&nodes, &parent, comp.class_name);
+    let node_path = if parent == "." { name.clone() } else { format!("{}/{}", parent, name) };
 
-    if let Some(error) = response_json.get("error") {
-        return Err(format!("Gemini API error: {}", error));
+    // Older projects (or third-person ones) may not have every component script yet
+    let script_file = project.join(comp.path());
+    if !script_file.exists() {
+        if let Some(dir) = script_file.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create components directory: {}", e))?;
+        }
+        fs::write(&script_file, comp.source).map_err(|e| format!("Failed to write {}: {}", comp.path(), e))?;
     }
+    let script_res = format!("res://{}", comp.path());
 
-    let text = response_json["candidates"][0]["content"]["parts"][0]["text"]
-        .as_str()
-        .ok_or_else(|| format!("No text in response: {:?}", response_json))?;
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    // Editing the file directly needs no Godot, so a missing pinned version isn't fatal
+    let godot_cmd = project_godot_cmd(project, saved).unwrap_or_else(|e| {
+        println!("[Components] {}", e);
+        None
+    });
+    if let Some(godot_cmd) = godot_cmd {
+        let scene_res = format!("res://{}", scene_file.strip_prefix(project).unwrap_or(&scene_file).to_string_lossy().replace('\\', "/"));
+        let script_path = project.join(".tav/add_component.gd");
+        fs::create_dir_all(project.join(".tav")).ok();
+        fs::write(&script_path, ADD_COMPONENT_GD).map_err(|e| format!("Failed to write add_component script: {}", e))?;
+        let script = script_path.to_string_lossy().to_string();
+        let args = [
+            "--headless", "--path", &project_path, "--script", &script, "--",
+            &scene_res, &parent, &script_res, comp.base, &name,
+        ];
+        match silent_cmd(&godot_cmd, &args) {
+            Ok(output) if output.status.success() => {
+                let saved = fs::read_to_string(&scene_file).unwrap_or_default();
+                if parse_tscn_nodes(&saved).iter().any(|n| n.path == node_path) {
+                    return Ok(ComponentAdded { node_path, script_path: script_res, method: "godot".to_string() });
+                }
+                println!("[Components] Godot did not save {}, editing the scene file instead", node_path);
+            }
+            Ok(output) => println!(
+                "[Components] Godot failed ({}), editing the scene file instead",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => println!("[Components] Failed to run Godot ({}), editing the scene file instead", e),
+        }
+    }
 
-    Ok(text.to_string())
+    let updated = add_component_tscn(&scene, &parent, &name, comp.base, &script_res);
+    write_file_atomic(&scene_file, &updated).map_err(|e| format!("Failed to write scene: {}", e))?;
+    Ok(ComponentAdded { node_path, script_path: script_res, method: "text".to_string() })
 }
 
 #[tauri::command]
@@ -3784,22 +8019,27 @@ async fn analyze_node_captures(
     captures: std::collections::HashMap<String, String>,
     node_name: String,
     prompt: String,
+    temperature: Option<f64>,
+    thinking_budget: Option<u32>,
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     let settings = state.settings.lock().unwrap().clone();
     let api_key = settings.gemini_key.ok_or("Gemini API key not set")?;
+    let max_size = settings.frame_max_size.unwrap_or(DEFAULT_FRAME_MAX_SIZE);
 
     // Build image parts for each angle
     let mut image_parts: Vec<serde_json::Value> = vec![];
     let mut angle_desc = String::new();
     
     for (angle, data) in &captures {
-        image_parts.push(serde_json::json!({
-            "inlineData": {
-                "mimeType": "image/png",
-                "data": data
-            }
-        }));
+        // Captures saved with `to_disk` arrive as PNG paths instead of base64
+        let data = if data.ends_with(".png") && Path::new(data).exists() {
+            let bytes = fs::read(data).map_err(|e| format!("Failed to read capture {}: {}", data, e))?;
+            base64::engine::general_purpose::STANDARD.encode(&bytes)
+        } else {
+            data.clone()
+        };
+        image_parts.push(frame_part(data, FrameFormat::Png, max_size)?);
         angle_desc.push_str(&format!("- Image {}: {} view\n", image_parts.len(), angle));
     }
 
@@ -3823,40 +8063,8 @@ Be specific about which angle shows each issue."#,
 
     image_parts.push(serde_json::json!({"text": full_prompt}));
 
-    let request_body = serde_json::json!({
-        "contents": [{"parts": image_parts}],
-        "generationConfig": {
-            "temperature": 0.5,
-            "thinkingConfig": {"thinkingBudget": 2048}
-        }
-    });
-
-    let client = reqwest::Client::new();
-    let response = client
-        .post(format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.5-flash:generateContent?key={}",
-            api_key
-        ))
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| format!("API request failed: {}", e))?;
-
-    let response_json: serde_json::Value = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    if let Some(error) = response_json.get("error") {
-        return Err(format!("Gemini API error: {}", error));
-    }
-
-    let text = response_json["candidates"][0]["content"]["parts"][0]["text"]
-        .as_str()
-        .ok_or_else(|| format!("No text in response: {:?}", response_json))?;
-
-    Ok(text.to_string())
+    let contents = serde_json::json!([{"parts": image_parts}]);
+    gemini_generate_contents(&api_key, "gemini-2.5-flash", &contents, generation_config(temperature, thinking_budget, 2048)).await
 }
 
 // ============================================================================
@@ -3872,6 +8080,7 @@ fn get_animation_catalog() -> Vec<animations::AnimationPack> {
 async fn download_animation_pack(
     pack_id: String,
     project_path: String,
+    app: tauri::AppHandle,
 ) -> Result<String, String> {
     let catalog = animations::get_animation_catalog();
     let pack = catalog.iter()
@@ -3904,16 +8113,11 @@ async fn download_animation_pack(
                 .map_err(|e| format!("Failed to save zip: {}", e))?;
             
             // Extract
-            let file = fs::File::open(&zip_path)
-                .map_err(|e| format!("Failed to open zip: {}", e))?;
-            let mut archive = zip::ZipArchive::new(file)
-                .map_err(|e| format!("Failed to read zip: {}", e))?;
-            
             let pack_dir = animations_dir.join(&pack_id);
             fs::create_dir_all(&pack_dir)
                 .map_err(|e| format!("Failed to create pack directory: {}", e))?;
             
-            archive.extract(&pack_dir)
+            extract_zip_safe(&zip_path, &pack_dir, |_, _| {})
                 .map_err(|e| format!("Failed to extract: {}", e))?;
             
             // Clean up zip
@@ -3946,14 +8150,9 @@ async fn download_animation_pack(
                 .map_err(|e| format!("Failed to save: {}", e))?;
             
             // Extract
-            let file = fs::File::open(&zip_path)
-                .map_err(|e| format!("Failed to open zip: {}", e))?;
-            let mut archive = zip::ZipArchive::new(file)
-                .map_err(|e| format!("Failed to read zip: {}", e))?;
-            
             let pack_dir = animations_dir.join(&pack_id);
             fs::create_dir_all(&pack_dir).ok();
-            archive.extract(&pack_dir).ok();
+            extract_zip_safe(&zip_path, &pack_dir, |_, _| {}).ok();
             fs::remove_file(&zip_path).ok();
             
             Ok(pack_dir.to_string_lossy().to_string())
@@ -3975,8 +8174,23 @@ async fn download_animation_pack(
             ))
         }
         animations::AnimationSource::Bundled { asset_name } => {
-            // Use bundled assets (for offline/included assets)
-            Err(format!("Bundled asset '{}' not yet implemented", asset_name))
+            // Shipped in the app's resources, so this works without network access
+            let bundled = bundled_asset_path(&app, asset_name)
+                .ok_or_else(|| format!("Bundled asset '{}' is not included in this build", asset_name))?;
+            let pack_dir = animations_dir.join(&pack_id);
+            fs::create_dir_all(&pack_dir)
+                .map_err(|e| format!("Failed to create pack directory: {}", e))?;
+            
+            if bundled.extension().and_then(|e| e.to_str()) == Some("zip") {
+                extract_zip_safe(&bundled, &pack_dir, |_, _| {})
+                    .map_err(|e| format!("Failed to extract: {}", e))?;
+            } else {
+                let file_name = bundled.file_name().ok_or("Invalid bundled asset name")?;
+                fs::copy(&bundled, pack_dir.join(file_name))
+                    .map_err(|e| format!("Failed to copy bundled asset: {}", e))?;
+            }
+            
+            Ok(pack_dir.to_string_lossy().to_string())
         }
     }
 }
@@ -4027,6 +8241,377 @@ fn setup_animation_library(
     ))
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnimationSetupStatus {
+    /// Animations the tree references that the library will not contain
+    pub missing_animations: Vec<String>,
+    pub tree_valid: bool,
+    /// Names the AnimationLibrary setup script will load
+    pub available_animations: Vec<String>,
+}
+
+/// Animation names AnimationLibrarySetup builds: the basename of each .glb/.gltf
+/// directly inside assets/animations (subfolders are not scanned)
+fn library_animation_names(project: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(project.join("assets/animations"))
+        .map(|entries| {
+            entries.flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && matches!(p.extension().and_then(|e| e.to_str()), Some("glb" | "gltf")))
+                .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// `animation = &"Name"` references in a generated AnimationTree scene
+fn tree_animation_names(tscn: &str) -> Vec<String> {
+    tscn.lines()
+        .filter_map(|line| line.trim().strip_prefix("animation = &\""))
+        .map(|rest| rest.trim_end_matches('"').to_string())
+        .collect()
+}
+
+/// Cross-check scenes/locomotion_tree.tscn against the animations actually present
+#[tauri::command]
+fn verify_animation_setup(project_path: String) -> Result<AnimationSetupStatus, String> {
+    let project = Path::new(&project_path);
+    let tree_path = project.join("scenes/locomotion_tree.tscn");
+    let tree = fs::read_to_string(&tree_path)
+        .map_err(|e| format!("Failed to read {}: {}", tree_path.display(), e))?;
+    let available_animations = library_animation_names(project);
+    let referenced = tree_animation_names(&tree);
+    let missing_animations: Vec<String> = referenced.iter()
+        .filter(|name| !available_animations.contains(name))
+        .cloned()
+        .collect();
+
+    Ok(AnimationSetupStatus {
+        tree_valid: !referenced.is_empty() && missing_animations.is_empty(),
+        missing_animations,
+        available_animations,
+    })
+}
+
+/// Regenerate the AnimationTree from the animations actually in assets/animations
+#[tauri::command]
+fn repair_animation_setup(project_path: String) -> Result<AnimationSetupStatus, String> {
+    let project = Path::new(&project_path);
+    let available = library_animation_names(project);
+    if available.is_empty() {
+        return Err("No .glb/.gltf animations found directly in assets/animations".to_string());
+    }
+
+    let blend_script_path = project.join("scripts/locomotion_blend_tree.gd");
+    if !blend_script_path.exists() {
+        fs::create_dir_all(project.join("scripts"))
+            .map_err(|e| format!("Failed to create scripts dir: {}", e))?;
+        fs::write(&blend_script_path, animations::LOCOMOTION_BLEND_TREE_GD)
+            .map_err(|e| format!("Failed to write blend script: {}", e))?;
+    }
+    fs::create_dir_all(project.join("scenes")).ok();
+    fs::write(project.join("scenes/locomotion_tree.tscn"), animations::generate_animation_tree_tscn(&available))
+        .map_err(|e| format!("Failed to write AnimationTree: {}", e))?;
+
+    println!("[Animations] Rebuilt locomotion tree with {} animations", available.len());
+    verify_animation_setup(project_path)
+}
+
+/// Streamed as `convert-progress` while convert_fbx_to_glb runs
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConvertProgress {
+    input: String,
+    stage: String,
+    percent: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<String>,
+}
+
+/// FBX2glTF bundled in binaries/ or on the PATH
+fn find_fbx2gltf_path(app: &tauri::AppHandle) -> Option<String> {
+    let exe = if cfg!(windows) { "FBX2glTF.exe" } else { "FBX2glTF" };
+    if let Ok(resource_dir) = app.path().resource_dir() {
+        let bundled = resource_dir.join("binaries").join(exe);
+        if bundled.exists() {
+            return Some(bundled.to_string_lossy().to_string());
+        }
+    }
+    ["FBX2glTF", "fbx2gltf"].iter().find_map(|name| tool_path(name))
+}
+
+/// Bone renames from MixamoRetargeter's MIXAMO_TO_GODOT table
+fn mixamo_bone_map() -> Vec<(String, String)> {
+    MIXAMO_RETARGETER_GD.lines()
+        .filter_map(|line| {
+            let (from, to) = line.trim().trim_end_matches(',').split_once(": ")?;
+            let from = from.strip_prefix('"')?.strip_suffix('"')?;
+            Some((from.to_string(), to.trim_matches('"').to_string()))
+        })
+        .collect()
+}
+
+/// Rename glTF nodes (bones included) in a binary .glb. Importers may turn
+/// "mixamorig:Hips" into "mixamorig_Hips", so both spellings match.
+/// Returns the rewritten file and the number of renamed nodes.
+fn rename_glb_nodes(glb: &[u8], map: &[(String, String)]) -> Result<(Vec<u8>, usize), String> {
+    let u32_at = |at: usize| glb.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+    if glb.get(0..4) != Some(b"glTF".as_slice()) || glb.get(16..20) != Some(b"JSON".as_slice()) {
+        return Err("Not a binary glTF file".to_string());
+    }
+    let json_len = u32_at(12).ok_or("Truncated GLB header")?;
+    let json_bytes = glb.get(20..20 + json_len).ok_or("Truncated GLB JSON chunk")?;
+    let mut json: serde_json::Value = serde_json::from_slice(json_bytes)
+        .map_err(|e| format!("Failed to parse GLB JSON: {}", e))?;
+
+    let mut renamed = 0;
+    if let Some(nodes) = json.get_mut("nodes").and_then(|n| n.as_array_mut()) {
+        for node in nodes {
+            let Some(name) = node.get("name").and_then(|n| n.as_str()) else { continue };
+            let target = map.iter()
+                .find(|(from, _)| name == from || name == from.replace(':', "_"))
+                .map(|(_, to)| to.clone());
+            if let Some(to) = target {
+                node["name"] = serde_json::Value::String(to);
+                renamed += 1;
+            }
+        }
+    }
+
+    let mut json_out = serde_json::to_vec(&json).map_err(|e| format!("Failed to encode GLB JSON: {}", e))?;
+    while json_out.len() % 4 != 0 {
+        json_out.push(b' ');
+    }
+    let rest = &glb[20 + json_len..];
+    let total = 12 + 8 + json_out.len() + rest.len();
+    let mut out = Vec::with_capacity(total);
+    out.extend_from_slice(b"glTF");
+    out.extend_from_slice(&2u32.to_le_bytes());
+    out.extend_from_slice(&(total as u32).to_le_bytes());
+    out.extend_from_slice(&(json_out.len() as u32).to_le_bytes());
+    out.extend_from_slice(b"JSON");
+    out.extend_from_slice(&json_out);
+    out.extend_from_slice(rest);
+    Ok((out, renamed))
+}
+
+/// Convert one FBX to `output` (a .glb) with FBX2glTF or, failing that, Godot's FBX
+/// importer, then apply the Mixamo bone renames. Returns the number of renamed bones.
+/// `on_retarget` runs once conversion succeeds, before the renames.
+fn convert_fbx_file<F, R>(
+    app: &tauri::AppHandle,
+    godot_cmd: Option<&str>,
+    input: &Path,
+    output: &Path,
+    on_line: F,
+    on_retarget: R,
+) -> Result<usize, String>
+where
+    F: Fn(String) + Clone + Send + 'static,
+    R: FnOnce(),
+{
+    let tmp_dir = std::env::temp_dir().join(format!("tav-fbx-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&tmp_dir).map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let tmp_glb = tmp_dir.join("converted.glb");
+    let input_str = input.to_string_lossy().to_string();
+
+    let result = if let Some(fbx2gltf) = find_fbx2gltf_path(app) {
+        println!("[Convert] Using FBX2glTF: {}", fbx2gltf);
+        // FBX2glTF appends the .glb suffix itself
+        let out_stem = tmp_dir.join("converted").to_string_lossy().to_string();
+        let mut command = Command::new(&fbx2gltf);
+        command.args(["--binary", "--input", &input_str, "--output", &out_stem]);
+        #[cfg(windows)]
+        command.creation_flags(CREATE_NO_WINDOW);
+        run_streamed(command, "FBX2glTF", None, on_line)
+    } else if let Some(godot) = godot_cmd {
+        println!("[Convert] Using Godot FBX import: {}", godot);
+        let script = tmp_dir.join("fbx_to_glb.gd");
+        fs::write(tmp_dir.join("project.godot"), "config_version=5\n")
+            .and_then(|_| fs::write(&script, FBX_TO_GLB_GD))
+            .map_err(|e| format!("Failed to write conversion project: {}", e))?;
+        let mut command = Command::new(godot);
+        command.arg("--headless").arg("--path").arg(&tmp_dir).arg("--script").arg(&script)
+            .arg("--").arg(&input_str).arg(&tmp_glb);
+        #[cfg(windows)]
+        command.creation_flags(CREATE_NO_WINDOW);
+        run_streamed(command, "Godot", None, on_line)
+    } else {
+        Err("No FBX converter found. Install Godot 4.3+ or FBX2glTF.".to_string())
+    };
+
+    let converted = result.and_then(|_| {
+        fs::read(&tmp_glb).map_err(|e| format!("Converter produced no GLB: {}", e))
+    });
+    let _ = fs::remove_dir_all(&tmp_dir);
+    let converted = converted?;
+    on_retarget();
+    retarget_glb(&converted, output)
+}
+
+/// Write `glb` to `output` with Mixamo bones renamed; returns the number renamed
+fn retarget_glb(glb: &[u8], output: &Path) -> Result<usize, String> {
+    let (glb, renamed) = rename_glb_nodes(glb, &mixamo_bone_map())?;
+    fs::write(output, glb).map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+    Ok(renamed)
+}
+
+/// Convert a (Mixamo) FBX to GLB with FBX2glTF or Godot's FBX importer, renaming
+/// bones to the names MixamoRetargeter uses. Progress streams as `convert-progress`.
+#[tauri::command]
+async fn convert_fbx_to_glb(
+    input: String,
+    output: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let input_path = Path::new(&input).canonicalize()
+        .map_err(|e| format!("Failed to read {}: {}", input, e))?;
+    if !input_path.extension().is_some_and(|e| e.eq_ignore_ascii_case("fbx")) {
+        return Err(format!("Not an FBX file: {}", input));
+    }
+    let output_path = Path::new(&output).with_extension("glb");
+    if let Some(dir) = output_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
+    }
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let emit = {
+            let app = app.clone();
+            let input = input.clone();
+            move |stage: &str, percent: u8, line: Option<String>| {
+                let _ = app.emit("convert-progress", ConvertProgress {
+                    input: input.clone(),
+                    stage: stage.to_string(),
+                    percent,
+                    line,
+                });
+            }
+        };
+        let on_line = {
+            let emit = emit.clone();
+            move |line: String| emit("converting", 10, Some(line))
+        };
+        emit("converting", 0, None);
+
+        let godot = saved_godot_cmd(saved);
+        let on_retarget = || emit("retargeting", 80, None);
+        let renamed = convert_fbx_file(&app, godot.as_deref(), &input_path, &output_path, on_line, on_retarget)?;
+        println!("[Convert] Wrote {} ({} bones renamed)", output_path.display(), renamed);
+        emit("done", 100, Some(format!("Renamed {} Mixamo bones", renamed)));
+        Ok(output_path.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Conversion task failed: {}", e))?
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetargetFailure {
+    pub file: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRetargetSummary {
+    /// Written files, relative to the project
+    pub converted: Vec<String>,
+    pub failed: Vec<RetargetFailure>,
+}
+
+/// Convert every .fbx/.glb in `source_dir` (top level only) to a retargeted GLB in
+/// assets/animations/, where AnimationLibrarySetup picks it up. One bad file does
+/// not stop the batch; progress streams as `convert-progress` per file.
+#[tauri::command]
+async fn batch_retarget_animations(
+    project_path: String,
+    source_dir: String,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<BatchRetargetSummary, String> {
+    let mut files: Vec<PathBuf> = fs::read_dir(&source_dir)
+        .map_err(|e| format!("Failed to read {}: {}", source_dir, e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("fbx") || e.eq_ignore_ascii_case("glb")))
+        .collect();
+    if files.is_empty() {
+        return Err(format!("No .fbx or .glb animations found in {}", source_dir));
+    }
+    files.sort();
+
+    let out_dir = Path::new(&project_path).join("assets/animations");
+    fs::create_dir_all(&out_dir).map_err(|e| format!("Failed to create animations directory: {}", e))?;
+    let saved = state.settings.lock().unwrap().godot_path.clone();
+    let godot = project_godot_cmd(Path::new(&project_path), saved)?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut summary = BatchRetargetSummary::default();
+        let total = files.len();
+
+        for (i, file) in files.iter().enumerate() {
+            let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let output = out_dir.join(file.with_extension("glb").file_name().unwrap_or_default());
+            let percent = (i * 100 / total) as u8;
+            let emit = {
+                let app = app.clone();
+                let input = file.to_string_lossy().to_string();
+                move |stage: &str, line: Option<String>| {
+                    let _ = app.emit("convert-progress", ConvertProgress {
+                        input: input.clone(),
+                        stage: stage.to_string(),
+                        percent,
+                        line,
+                    });
+                }
+            };
+            emit("converting", None);
+
+            let result = if file.extension().is_some_and(|e| e.eq_ignore_ascii_case("glb")) {
+                fs::read(file)
+                    .map_err(|e| format!("Failed to read {}: {}", name, e))
+                    .and_then(|glb| {
+                        emit("retargeting", None);
+                        retarget_glb(&glb, &output)
+                    })
+            } else {
+                let on_line = {
+                    let emit = emit.clone();
+                    move |line: String| emit("converting", Some(line))
+                };
+                convert_fbx_file(&app, godot.as_deref(), file, &output, on_line, || emit("retargeting", None))
+            };
+            match result {
+                Ok(renamed) => {
+                    println!("[Convert] {} -> {} ({} bones renamed)", name, output.display(), renamed);
+                    summary.converted.push(format!("assets/animations/{}", output.file_name().unwrap_or_default().to_string_lossy()));
+                }
+                Err(error) => {
+                    println!("[Convert] {} failed: {}", name, error);
+                    summary.failed.push(RetargetFailure { file: name, error });
+                }
+            }
+        }
+
+        let _ = app.emit("convert-progress", ConvertProgress {
+            input: source_dir,
+            stage: "done".to_string(),
+            percent: 100,
+            line: Some(format!("{} converted, {} failed", summary.converted.len(), summary.failed.len())),
+        });
+        Ok(summary)
+    })
+    .await
+    .map_err(|e| format!("Batch retarget task failed: {}", e))?
+}
+
 #[tauri::command]
 fn list_project_animations(project_path: String) -> Result<Vec<String>, String> {
     let animations_dir = Path::new(&project_path).join("assets").join("animations");
@@ -4140,56 +8725,300 @@ fn get_input_mappings(project_path: String) -> Result<Vec<InputMapping>, String>
     Ok(mappings)
 }
 
+/// Read a `"field":value` entry from a serialized Godot input event
+fn event_field<'a>(event: &'a str, field: &str) -> Option<&'a str> {
+    let key = format!("\"{}\":", field);
+    let start = event.find(&key)? + key.len();
+    let rest = &event[start..];
+    let end = rest.find([',', ')']).unwrap_or(rest.len());
+    Some(rest[..end].trim())
+}
+
+const JOY_BUTTON_NAMES: [&str; 15] = [
+    "A", "B", "X", "Y", "Back", "Guide", "Start", "Left Stick", "Right Stick",
+    "LB", "RB", "DPad Up", "DPad Down", "DPad Left", "DPad Right",
+];
+
+/// Axis names with the direction for negative and positive values
+const JOY_AXIS_NAMES: [(&str, &str, &str); 6] = [
+    ("Left Stick", "Left", "Right"),
+    ("Left Stick", "Up", "Down"),
+    ("Right Stick", "Left", "Right"),
+    ("Right Stick", "Up", "Down"),
+    ("LT", "", ""),
+    ("RT", "", ""),
+];
+
+/// Display name for one serialized input event, e.g. "Ctrl+S" or "Joy A"
+fn input_event_name(event: &str) -> Option<String> {
+    let name = if event.starts_with("InputEventKey") {
+        let code = event_field(event, "physical_keycode")
+            .and_then(|c| c.parse::<u32>().ok())
+            .filter(|c| *c != 0)
+            .or_else(|| event_field(event, "keycode")?.parse().ok())?;
+        let key = keycode_to_name(code)?;
+        let modifiers: String = [("ctrl_pressed", "Ctrl"), ("shift_pressed", "Shift"), ("alt_pressed", "Alt"), ("meta_pressed", "Meta")]
+            .iter()
+            .filter(|(field, name)| event_field(event, field) == Some("true") && key != *name)
+            .map(|(_, name)| format!("{}+", name))
+            .collect();
+        format!("{}{}", modifiers, key)
+    } else if event.starts_with("InputEventMouseButton") {
+        match event_field(event, "button_index")? {
+            "1" => "LeftClick".to_string(),
+            "2" => "RightClick".to_string(),
+            "3" => "MiddleClick".to_string(),
+            "4" => "WheelUp".to_string(),
+            "5" => "WheelDown".to_string(),
+            other => format!("Mouse {}", other),
+        }
+    } else if event.starts_with("InputEventJoypadButton") {
+        let index: usize = event_field(event, "button_index")?.parse().ok()?;
+        match JOY_BUTTON_NAMES.get(index) {
+            Some(name) => format!("Joy {}", name),
+            None => format!("Joy Button {}", index),
+        }
+    } else if event.starts_with("InputEventJoypadMotion") {
+        let axis: usize = event_field(event, "axis")?.parse().ok()?;
+        let value: f32 = event_field(event, "axis_value").and_then(|v| v.parse().ok()).unwrap_or(1.0);
+        match JOY_AXIS_NAMES.get(axis) {
+            Some((name, "", _)) => format!("Joy {}", name),
+            Some((name, negative, positive)) => {
+                format!("Joy {} {}", name, if value < 0.0 { negative } else { positive })
+            }
+            None => format!("Joy Axis {}{}", axis, if value < 0.0 { "-" } else { "+" }),
+        }
+    } else {
+        return None;
+    };
+
+    // Gamepad bindings can target one controller; -1 means any device
+    match event_field(event, "device").and_then(|d| d.parse::<i32>().ok()) {
+        Some(device) if device >= 0 && event.starts_with("InputEventJoypad") => {
+            Some(format!("{} (Device {})", name, device))
+        }
+        _ => Some(name),
+    }
+}
+
 fn parse_keys_from_block(block: &str) -> Vec<String> {
     let mut keys = Vec::new();
-    
-    // Parse physical_keycode values using simple string search
-    let search = "physical_keycode\":";
-    let mut pos = 0;
-    while let Some(start) = block[pos..].find(search) {
-        let code_start = pos + start + search.len();
-        if let Some(end) = block[code_start..].find(|c: char| !c.is_ascii_digit()) {
-            if let Ok(code) = block[code_start..code_start + end].parse::<u32>() {
-                if let Some(key) = keycode_to_name(code) {
-                    if !keys.contains(&key) {
-                        keys.push(key);
-                    }
-                }
+
+    // Each binding is serialized as Object(InputEvent..., "field":value, ...)
+    for event in block.split("Object(").skip(1) {
+        if let Some(key) = input_event_name(event) {
+            if !keys.contains(&key) {
+                keys.push(key);
             }
         }
-        pos = code_start;
     }
-    
-    // Check for mouse buttons
-    if block.contains("InputEventMouseButton") {
-        if block.contains("button_index\":1") {
-            keys.push("LeftClick".to_string());
-        } else if block.contains("button_index\":2") {
-            keys.push("RightClick".to_string());
+
+    keys
+}
+
+/// Serialize a binding name (as produced by `input_event_name`) into a Godot input event object
+fn input_event_object(name: &str) -> Option<String> {
+    let name = name.trim();
+    // Gamepad bindings for one controller carry a " (Device N)" suffix
+    let (name, device) = match name.strip_suffix(')').and_then(|n| n.rsplit_once(" (Device ")) {
+        Some((base, device)) => (base, device.parse::<i32>().ok()?),
+        None => (name, -1),
+    };
+    let mouse_button = match name {
+        "LeftClick" => Some(1),
+        "RightClick" => Some(2),
+        "MiddleClick" => Some(3),
+        "WheelUp" => Some(4),
+        "WheelDown" => Some(5),
+        _ => name.strip_prefix("Mouse ").and_then(|i| i.parse().ok()),
+    };
+    if let Some(index) = mouse_button {
+        return Some(format!(
+            r#"Object(InputEventMouseButton,"resource_local_to_scene":false,"resource_name":"","device":-1,"window_id":0,"alt_pressed":false,"shift_pressed":false,"ctrl_pressed":false,"meta_pressed":false,"button_mask":0,"position":Vector2(0, 0),"global_position":Vector2(0, 0),"factor":1.0,"button_index":{},"canceled":false,"pressed":true,"double_click":false,"script":null)"#,
+            index
+        ));
+    }
+
+    if let Some(joy) = name.strip_prefix("Joy ") {
+        let button = JOY_BUTTON_NAMES.iter().position(|b| *b == joy)
+            .or_else(|| joy.strip_prefix("Button ")?.parse().ok());
+        if let Some(index) = button {
+            return Some(format!(
+                r#"Object(InputEventJoypadButton,"resource_local_to_scene":false,"resource_name":"","device":{},"button_index":{},"pressure":0.0,"pressed":true,"script":null)"#,
+                device, index
+            ));
         }
+
+        // Axes: "Left Stick Left", "LT", or "Axis 7+"
+        let motion = JOY_AXIS_NAMES.iter().enumerate().find_map(|(axis, (stick, negative, positive))| {
+            if joy == *stick && negative.is_empty() {
+                return Some((axis, 1.0));
+            }
+            let direction = joy.strip_prefix(stick)?.strip_prefix(' ')?;
+            if direction == *negative {
+                Some((axis, -1.0))
+            } else if direction == *positive {
+                Some((axis, 1.0))
+            } else {
+                None
+            }
+        }).or_else(|| {
+            let axis = joy.strip_prefix("Axis ")?;
+            let (index, sign) = axis.split_at(axis.len().checked_sub(1)?);
+            let value = match sign { "-" => -1.0, "+" => 1.0, _ => return None };
+            Some((index.parse().ok()?, value))
+        });
+        let (axis, value) = motion?;
+        return Some(format!(
+            r#"Object(InputEventJoypadMotion,"resource_local_to_scene":false,"resource_name":"","device":{},"axis":{},"axis_value":{:.1},"script":null)"#,
+            device, axis, value
+        ));
     }
-    
-    keys
+    if device >= 0 {
+        // Only gamepad bindings are device-specific
+        return None;
+    }
+
+    // Keys, optionally with modifier prefixes like "Ctrl+Shift+S"
+    let (modifiers, key) = match name.rfind('+') {
+        Some(pos) if pos + 1 < name.len() => (&name[..pos], &name[pos + 1..]),
+        _ => ("", name),
+    };
+    let code = name_to_keycode(key)?;
+    let has = |m: &str| modifiers.split('+').any(|part| part.eq_ignore_ascii_case(m));
+    let unicode = match code {
+        65..=90 => code + 32,
+        33..=126 => code,
+        32 => 32,
+        _ => 0,
+    };
+    Some(format!(
+        r#"Object(InputEventKey,"resource_local_to_scene":false,"resource_name":"","device":-1,"window_id":0,"alt_pressed":{},"shift_pressed":{},"ctrl_pressed":{},"meta_pressed":{},"pressed":false,"keycode":0,"physical_keycode":{},"key_label":0,"unicode":{},"location":0,"echo":false,"script":null)"#,
+        has("Alt"), has("Shift"), has("Ctrl"), has("Meta"), code, unicode
+    ))
+}
+
+/// Build an `action={...}` entry for the `[input]` section of project.godot
+fn input_action_block(action: &str, keys: &[String]) -> Result<String, String> {
+    let events = keys.iter()
+        .map(|key| input_event_object(key).ok_or_else(|| format!("Unknown key: {}", key)))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!(
+        "{}={{\n\"deadzone\": 0.5,\n\"events\": [{}]\n}}",
+        action,
+        events.join(", ")
+    ))
+}
+
+/// Insert or replace an action entry in the `[input]` section, leaving everything else untouched
+fn upsert_input_action(content: &str, action: &str, block: &str) -> String {
+    let value = block.strip_prefix(action).and_then(|b| b.strip_prefix('=')).unwrap_or(block);
+    let mut config = parse_godot_config(content);
+    config.section_or_insert("input", &["rendering"]).set(action, value);
+    write_godot_config(&config)
+}
+
+#[tauri::command]
+fn set_input_action(project_path: String, action: String, keys: Vec<String>) -> Result<(), String> {
+    let action = action.trim();
+    if action.is_empty() || action.contains(['=', '{', '}', '[', ']']) || action.contains(char::is_whitespace) {
+        return Err(format!("Invalid action name: {}", action));
+    }
+
+    let project_file = Path::new(&project_path).join("project.godot");
+    let content = fs::read_to_string(&project_file)
+        .map_err(|e| format!("Failed to read project.godot: {}", e))?;
+
+    let block = input_action_block(action, &keys)?;
+    fs::write(&project_file, upsert_input_action(&content, action, &block))
+        .map_err(|e| format!("Failed to update project.godot: {}", e))?;
+
+    println!("[Input] Set {} = {:?}", action, keys);
+    Ok(())
 }
 
+/// Flag Godot 4 sets on keycodes outside the printable range
+const KEY_SPECIAL: u32 = 1 << 22;
+/// Godot 3 flagged special keys with this bit instead, using the same low bits
+const KEY_SPECIAL_GODOT3: u32 = 1 << 24;
+
+/// Names for special keys, keyed by the low bits below KEY_SPECIAL
+const SPECIAL_KEY_NAMES: &[(u32, &str)] = &[
+    (0x01, "Escape"), (0x02, "Tab"), (0x03, "Backtab"), (0x04, "Backspace"),
+    (0x05, "Enter"), (0x06, "Kp Enter"), (0x07, "Insert"), (0x08, "Delete"),
+    (0x09, "Pause"), (0x0A, "Print"), (0x0B, "SysReq"), (0x0C, "Clear"),
+    (0x0D, "Home"), (0x0E, "End"), (0x0F, "Left"), (0x10, "Up"),
+    (0x11, "Right"), (0x12, "Down"), (0x13, "PageUp"), (0x14, "PageDown"),
+    (0x15, "Shift"), (0x16, "Ctrl"), (0x17, "Meta"), (0x18, "Alt"),
+    (0x19, "CapsLock"), (0x1A, "NumLock"), (0x1B, "ScrollLock"), (0x42, "Menu"),
+    (0x81, "Kp *"), (0x82, "Kp /"), (0x83, "Kp -"), (0x84, "Kp ."), (0x85, "Kp +"),
+];
+const KEY_F1: u32 = 0x1C;
+const KEY_F_COUNT: u32 = 35;
+const KEY_KP_0: u32 = 0x86;
+
 fn keycode_to_name(code: u32) -> Option<String> {
+    // Normalize Godot 3 special keycodes to their Godot 4 equivalents
+    let code = if code & KEY_SPECIAL_GODOT3 != 0 {
+        (code & !KEY_SPECIAL_GODOT3) | KEY_SPECIAL
+    } else {
+        code
+    };
+
     match code {
-        65..=90 => Some(((code as u8) as char).to_string()), // A-Z
         32 => Some("Space".to_string()),
-        16777217 => Some("Escape".to_string()),
-        16777218 => Some("Tab".to_string()),
-        16777220 => Some("Enter".to_string()),
-        16777221 => Some("Shift".to_string()),
-        16777238 => Some("Ctrl".to_string()),
-        16777240 => Some("Alt".to_string()),
-        4194319 => Some("Left".to_string()),
-        4194320 => Some("Up".to_string()),
-        4194321 => Some("Right".to_string()),
-        4194322 => Some("Down".to_string()),
+        // Digits, A-Z and punctuation use their uppercase ASCII value
+        33..=64 | 65..=90 | 91..=96 | 123..=126 => Some(((code as u8) as char).to_string()),
+        _ if code & KEY_SPECIAL != 0 => {
+            let low = code & !KEY_SPECIAL;
+            match low {
+                _ if (KEY_F1..KEY_F1 + KEY_F_COUNT).contains(&low) => Some(format!("F{}", low - KEY_F1 + 1)),
+                _ if (KEY_KP_0..=KEY_KP_0 + 9).contains(&low) => Some(format!("Kp {}", low - KEY_KP_0)),
+                _ => SPECIAL_KEY_NAMES.iter()
+                    .find(|(value, _)| *value == low)
+                    .map(|(_, name)| name.to_string()),
+            }
+        }
         _ => None,
     }
 }
 
+/// Inverse of `keycode_to_name`, returning the Godot 4 physical keycode
+fn name_to_keycode(name: &str) -> Option<u32> {
+    let name = name.trim();
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        let upper = c.to_ascii_uppercase();
+        return (upper.is_ascii_graphic() && !upper.is_ascii_lowercase()).then_some(upper as u32);
+    }
+    if name.eq_ignore_ascii_case("space") {
+        return Some(32);
+    }
+    if let Some(n) = name.strip_prefix(['F', 'f']).and_then(|n| n.parse::<u32>().ok()) {
+        return (1..=KEY_F_COUNT).contains(&n).then(|| KEY_SPECIAL | (KEY_F1 + n - 1));
+    }
+    if let Some(rest) = name.get(..3).filter(|p| p.eq_ignore_ascii_case("kp ")).map(|_| &name[3..]) {
+        if let Ok(n @ 0..=9) = rest.parse::<u32>() {
+            return Some(KEY_SPECIAL | (KEY_KP_0 + n));
+        }
+    }
+    SPECIAL_KEY_NAMES.iter()
+        .find(|(_, key_name)| key_name.eq_ignore_ascii_case(name))
+        .map(|(value, _)| KEY_SPECIAL | value)
+}
+
+/// Display name for a Godot physical keycode
+#[tauri::command]
+fn get_key_name(keycode: u32) -> Option<String> {
+    keycode_to_name(keycode)
+}
+
+/// Godot 4 physical keycode for a key name as shown by `get_key_name`
+#[tauri::command]
+fn get_keycode(name: String) -> Option<u32> {
+    name_to_keycode(&name)
+}
+
 fn action_to_description(action: &str) -> String {
     match action {
         "move_left" => "Move character left".to_string(),
@@ -4216,30 +9045,117 @@ fn get_settings_path() -> PathBuf {
         .join("settings.json")
 }
 
-fn save_settings_to_disk(settings: &AppSettings) -> Result<(), String> {
+/// Keyring service name for secret settings
+const SECRETS_SERVICE: &str = "kobold";
+
+/// Settings fields (as serialized) kept out of plaintext settings.json
+const SECRET_FIELDS: [&str; 2] = ["openrouterKey", "geminiKey"];
+
+/// Prefix for secrets written to settings.json when no OS keyring is available.
+/// This is obfuscation rather than real encryption: the XOR key is derived from
+/// the settings path, so values are unreadable at a glance and meaningless if the
+/// file is synced or shared, but anyone with access to this account can recover them.
+const OBFUSCATED_PREFIX: &str = "obf1:";
+
+fn obfuscation_key() -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(b"kobold-settings:");
+    hasher.update(get_settings_path().to_string_lossy().as_bytes());
+    hasher.finalize().to_vec()
+}
+
+fn xor_with_key(data: &[u8]) -> Vec<u8> {
+    let key = obfuscation_key();
+    data.iter().zip(key.iter().cycle()).map(|(b, k)| b ^ k).collect()
+}
+
+fn obfuscate_secret(value: &str) -> String {
+    format!(
+        "{}{}",
+        OBFUSCATED_PREFIX,
+        base64::engine::general_purpose::STANDARD.encode(xor_with_key(value.as_bytes()))
+    )
+}
+
+fn deobfuscate_secret(value: &str) -> Option<String> {
+    let encoded = value.strip_prefix(OBFUSCATED_PREFIX)?;
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded).ok()?;
+    String::from_utf8(xor_with_key(&bytes)).ok()
+}
+
+/// Store a secret in the OS keyring. Returns the value to keep in settings.json
+/// instead (obfuscated) when the keyring is unavailable.
+fn store_secret(field: &str, value: Option<&str>) -> Option<String> {
+    let entry = keyring::Entry::new(SECRETS_SERVICE, field).ok();
+    match value {
+        Some(v) if !v.is_empty() => {
+            if let Some(entry) = entry {
+                if entry.set_password(v).is_ok() {
+                    return None;
+                }
+            }
+            println!("[Settings] Keyring unavailable, storing {} obfuscated", field);
+            Some(obfuscate_secret(v))
+        }
+        _ => {
+            if let Some(entry) = entry {
+                let _ = entry.delete_credential();
+            }
+            None
+        }
+    }
+}
+
+/// Resolve a secret from settings.json (obfuscated or legacy plaintext) or the OS keyring
+fn load_secret(field: &str, stored: Option<&serde_json::Value>) -> Option<String> {
+    if let Some(value) = stored.and_then(|v| v.as_str()) {
+        // Legacy plaintext keys are returned as-is and moved to the keyring on next save
+        return deobfuscate_secret(value).or_else(|| Some(value.to_string()));
+    }
+    keyring::Entry::new(SECRETS_SERVICE, field).ok()?.get_password().ok()
+}
+
+/// Write settings.json with secret fields moved to the keyring
+fn write_settings_file(settings: &AppSettings) -> Result<(), String> {
     let path = get_settings_path();
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
-    
-    // Merge with existing settings
-    let mut existing = load_settings_from_disk();
-    if settings.openrouter_key.is_some() {
-        existing.openrouter_key = settings.openrouter_key.clone();
-    }
-    if settings.goose_model.is_some() {
-        existing.goose_model = settings.goose_model.clone();
-    }
-    if settings.godot_path.is_some() {
-        existing.godot_path = settings.godot_path.clone();
+
+    let mut json = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    if let Some(obj) = json.as_object_mut() {
+        for field in SECRET_FIELDS {
+            let value = obj.remove(field);
+            if let Some(kept) = store_secret(field, value.as_ref().and_then(|v| v.as_str())) {
+                obj.insert(field.to_string(), serde_json::Value::String(kept));
+            }
+        }
     }
-    if settings.gemini_key.is_some() {
-        existing.gemini_key = settings.gemini_key.clone();
+
+    let json = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save settings: {}", e))?;
+    Ok(())
+}
+
+/// Overlay every field set in `incoming` onto `existing`, keeping existing values for `None`
+fn merge_settings(existing: &AppSettings, incoming: &AppSettings) -> AppSettings {
+    let mut merged = serde_json::to_value(existing).unwrap_or_else(|_| serde_json::json!({}));
+    if let (Some(target), Ok(serde_json::Value::Object(updates))) =
+        (merged.as_object_mut(), serde_json::to_value(incoming))
+    {
+        for (key, value) in updates {
+            if !value.is_null() {
+                target.insert(key, value);
+            }
+        }
     }
-    
-    let json = serde_json::to_string_pretty(&existing).map_err(|e| e.to_string())?;
-    fs::write(&path, json).map_err(|e| format!("Failed to save settings: {}", e))?;
-    Ok(())
+    serde_json::from_value(merged).unwrap_or_else(|_| existing.clone())
+}
+
+fn save_settings_to_disk(settings: &AppSettings) -> Result<(), String> {
+    // Merge with existing settings
+    let existing = load_settings_from_disk();
+    write_settings_file(&merge_settings(&existing, settings))
 }
 
 #[tauri::command]
@@ -4250,39 +9166,180 @@ fn get_settings(state: tauri::State<AppState>) -> AppSettings {
 #[tauri::command]
 fn save_settings(settings: AppSettings, state: tauri::State<AppState>) -> Result<(), String> {
     *state.settings.lock().unwrap() = settings.clone();
+    write_settings_file(&settings)
+}
 
+fn load_settings_from_disk() -> AppSettings {
     let path = get_settings_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    let mut json = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .unwrap_or_else(|| serde_json::json!({}));
+
+    // Reassemble secret fields from the keyring or their obfuscated form
+    if let Some(obj) = json.as_object_mut() {
+        for field in SECRET_FIELDS {
+            match load_secret(field, obj.get(field)) {
+                Some(secret) => {
+                    obj.insert(field.to_string(), serde_json::Value::String(secret));
+                }
+                None => {
+                    obj.remove(field);
+                }
+            }
+        }
     }
 
-    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    fs::write(&path, json).map_err(|e| format!("Failed to save settings: {}", e))?;
+    serde_json::from_value(json).unwrap_or_default()
+}
 
-    Ok(())
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyValidation {
+    pub valid: bool,
+    pub message: String,
 }
 
-fn load_settings_from_disk() -> AppSettings {
-    let path = get_settings_path();
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(settings) = serde_json::from_str(&content) {
-                return settings;
-            }
+/// Turn the response of a cheap authenticated request into a KeyValidation
+fn key_validation_from_response(
+    provider: &str,
+    result: Result<reqwest::Response, reqwest::Error>,
+) -> KeyValidation {
+    match result {
+        Ok(response) if response.status().is_success() => KeyValidation {
+            valid: true,
+            message: format!("{} key is valid", provider),
+        },
+        Ok(response) => {
+            let status = response.status();
+            let message = match status.as_u16() {
+                400 | 401 | 403 => format!("{} rejected the key ({})", provider, status),
+                429 => format!("{} rate limited the request, try again shortly", provider),
+                _ => format!("{} returned {}", provider, status),
+            };
+            KeyValidation { valid: false, message }
         }
+        Err(e) => KeyValidation {
+            valid: false,
+            message: format!("Could not reach {}: {}", provider, e),
+        },
+    }
+}
+
+#[tauri::command]
+async fn validate_gemini_key(key: String) -> KeyValidation {
+    let key = key.trim();
+    if key.is_empty() {
+        return KeyValidation { valid: false, message: "Gemini key is empty".to_string() };
+    }
+
+    let client = reqwest::Client::new();
+    let result = client
+        .get("https://generativelanguage.googleapis.com/v1beta/models")
+        .query(&[("key", key), ("pageSize", "1")])
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await;
+    key_validation_from_response("Gemini", result)
+}
+
+#[tauri::command]
+async fn validate_openrouter_key(key: String) -> KeyValidation {
+    let key = key.trim();
+    if key.is_empty() {
+        return KeyValidation { valid: false, message: "OpenRouter key is empty".to_string() };
+    }
+
+    let client = reqwest::Client::new();
+    let result = client
+        .get("https://openrouter.ai/api/v1/key")
+        .bearer_auth(key)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await;
+    key_validation_from_response("OpenRouter", result)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPricing {
+    pub prompt: String,
+    pub completion: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRouterModel {
+    pub id: String,
+    pub name: String,
+    pub context_length: Option<u64>,
+    pub pricing: ModelPricing,
+}
+
+/// Keep models that take text and produce text, sorted by name for the picker
+fn parse_openrouter_models(data: &serde_json::Value) -> Vec<OpenRouterModel> {
+    let mut models: Vec<OpenRouterModel> = data["data"].as_array().into_iter().flatten()
+        .filter(|model| {
+            let arch = &model["architecture"];
+            match arch["output_modalities"].as_array() {
+                Some(outputs) => outputs.iter().any(|m| m == "text")
+                    && arch["input_modalities"].as_array().is_none_or(|inputs| inputs.iter().any(|m| m == "text")),
+                // Older responses only carry a "text->text" style modality string
+                None => arch["modality"].as_str().is_none_or(|m| m.ends_with("->text")),
+            }
+        })
+        .filter_map(|model| {
+            let id = model["id"].as_str()?.to_string();
+            let price = |key: &str| model["pricing"][key].as_str().unwrap_or("0").to_string();
+            Some(OpenRouterModel {
+                name: model["name"].as_str().unwrap_or(&id).to_string(),
+                context_length: model["context_length"].as_u64(),
+                pricing: ModelPricing {
+                    prompt: price("prompt"),
+                    completion: price("completion"),
+                },
+                id,
+            })
+        })
+        .collect();
+    models.sort_by_key(|m| m.name.to_lowercase());
+    models
+}
+
+#[tauri::command]
+async fn list_openrouter_models(state: tauri::State<'_, AppState>) -> Result<Vec<OpenRouterModel>, String> {
+    let key = state.settings.lock().unwrap().openrouter_key.clone().unwrap_or_default();
+
+    let client = reqwest::Client::new();
+    let mut request = client
+        .get("https://openrouter.ai/api/v1/models")
+        .timeout(std::time::Duration::from_secs(15));
+    if !key.is_empty() {
+        request = request.bearer_auth(&key);
+    }
+
+    let response = request.send().await
+        .map_err(|e| format!("Failed to fetch models: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch models: {}", response.status()));
     }
-    AppSettings::default()
+
+    let data: serde_json::Value = response.json().await
+        .map_err(|e| format!("Failed to parse models: {}", e))?;
+    Ok(parse_openrouter_models(&data))
 }
 
 // ============================================================================
 // OpenRouter OAuth PKCE Flow
 // ============================================================================
 
-use std::sync::atomic::{AtomicU16, Ordering as AtomicOrdering};
+use std::sync::atomic::{AtomicU16, AtomicU64, Ordering as AtomicOrdering};
 use sha2::{Sha256, Digest};
 
 static OAUTH_PORT: AtomicU16 = AtomicU16::new(0);
 static OAUTH_VERIFIER: Mutex<Option<String>> = Mutex::new(None);
+static OAUTH_STATE: Mutex<Option<String>> = Mutex::new(None);
+/// Incremented per sign-in attempt; a callback server exits once its session is superseded
+static OAUTH_SESSION: AtomicU64 = AtomicU64::new(0);
 
 fn generate_code_verifier() -> String {
     use rand::Rng;
@@ -4306,6 +9363,10 @@ async fn start_openrouter_auth(app: tauri::AppHandle) -> Result<(), String> {
     
     // Store verifier for later exchange
     *OAUTH_VERIFIER.lock().unwrap() = Some(verifier);
+
+    // Random state ties the callback to this attempt so other local processes can't inject a code
+    let oauth_state = generate_code_verifier();
+    *OAUTH_STATE.lock().unwrap() = Some(oauth_state.clone());
     
     // Find available port
     let listener = std::net::TcpListener::bind("127.0.0.1:0")
@@ -4314,20 +9375,23 @@ async fn start_openrouter_auth(app: tauri::AppHandle) -> Result<(), String> {
     OAUTH_PORT.store(port, AtomicOrdering::SeqCst);
     drop(listener);
     
-    // Start callback server in background
+    // Start callback server in background, superseding any earlier attempt
+    let session = OAUTH_SESSION.fetch_add(1, AtomicOrdering::SeqCst) + 1;
     let app_clone = app.clone();
     std::thread::spawn(move || {
-        if let Err(e) = run_oauth_callback_server(port, app_clone) {
+        if let Err(e) = run_oauth_callback_server(port, session, app_clone) {
             eprintln!("OAuth callback server error: {}", e);
         }
     });
     
     // Open browser
-    let callback_url = format!("http://127.0.0.1:{}", port);
+    // State is also embedded in the callback URL so it round-trips even if the provider drops it
+    let callback_url = format!("http://127.0.0.1:{}/?state={}", port, oauth_state);
     let auth_url = format!(
-        "https://openrouter.ai/auth?callback_url={}&code_challenge={}&code_challenge_method=S256",
+        "https://openrouter.ai/auth?callback_url={}&code_challenge={}&code_challenge_method=S256&state={}",
         urlencoding::encode(&callback_url),
-        urlencoding::encode(&challenge)
+        urlencoding::encode(&challenge),
+        urlencoding::encode(&oauth_state)
     );
     
     open::that(&auth_url).map_err(|e| format!("Failed to open browser: {}", e))?;
@@ -4335,50 +9399,106 @@ async fn start_openrouter_auth(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
-fn run_oauth_callback_server(port: u16, app: tauri::AppHandle) -> Result<(), String> {
+/// Extract and decode a query parameter from a request URL
+fn query_param(url: &str, name: &str) -> Option<String> {
+    let query = url.split_once('?').map(|(_, q)| q).unwrap_or(url);
+    query.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        if key != name || value.is_empty() {
+            return None;
+        }
+        urlencoding::decode(value).ok().map(|v| v.into_owned())
+    })
+}
+
+fn run_oauth_callback_server(port: u16, session: u64, app: tauri::AppHandle) -> Result<(), String> {
     let server = tiny_http::Server::http(format!("127.0.0.1:{}", port))
         .map_err(|e| format!("Failed to start server: {}", e))?;
     
-    // Wait for callback (with timeout)
-    let timeout = std::time::Duration::from_secs(300); // 5 minute timeout
+    // Wait for callback (with timeout), polling so cancel_openrouter_auth can stop the server
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(300); // 5 minute timeout
+    let request = loop {
+        if OAUTH_SESSION.load(AtomicOrdering::SeqCst) != session {
+            println!("[OAuth] Sign-in cancelled, stopping callback server");
+            return Ok(());
+        }
+
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        if remaining.is_zero() {
+            println!("[OAuth] No callback received before timeout");
+            OAUTH_VERIFIER.lock().unwrap().take();
+            OAUTH_STATE.lock().unwrap().take();
+            let _ = app.emit("oauth-timeout", ());
+            return Ok(());
+        }
+
+        match server.recv_timeout(remaining.min(std::time::Duration::from_millis(500))) {
+            // Browsers may request a favicon alongside the callback
+            Ok(Some(request)) if request.url().starts_with("/favicon") => {
+                let _ = request.respond(tiny_http::Response::empty(404));
+            }
+            Ok(Some(request)) => {
+                let expected = OAUTH_STATE.lock().unwrap().clone();
+                if expected.is_some() && query_param(request.url(), "state") == expected {
+                    break request;
+                }
+                // Keep waiting for the real callback rather than letting a stray request end the flow
+                println!("[OAuth] Rejected callback with missing or mismatched state");
+                let _ = request.respond(oauth_error_response("The sign-in request could not be verified."));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                let _ = app.emit("oauth-error", format!("Callback server failed: {}", e));
+                return Err(format!("Failed to receive callback: {}", e));
+            }
+        }
+    };
+    // The state is single-use: a replayed callback must not pass validation
+    OAUTH_STATE.lock().unwrap().take();
+
+    let url = request.url().to_string();
     
-    if let Ok(Some(request)) = server.recv_timeout(timeout) {
-        let url = request.url().to_string();
+    // Parse the code from URL
+    if let Some(code) = query_param(&url, "code") {
+        // Exchange code for API key
+        let verifier = OAUTH_VERIFIER.lock().unwrap().take();
         
-        // Parse the code from URL
-        if let Some(code) = url.split("code=").nth(1).map(|s| s.split('&').next().unwrap_or(s)) {
-            // Exchange code for API key
-            let verifier = OAUTH_VERIFIER.lock().unwrap().take();
+        if let Some(verifier) = verifier {
+            // Spawn async task to exchange code
+            let app_clone = app.clone();
             
-            if let Some(verifier) = verifier {
-                // Spawn async task to exchange code
-                let code = code.to_string();
-                let app_clone = app.clone();
-                
-                std::thread::spawn(move || {
-                    let rt = tokio::runtime::Runtime::new().unwrap();
-                    rt.block_on(async {
-                        match exchange_code_for_key(&code, &verifier).await {
-                            Ok(api_key) => {
-                                // Save the key
-                                let settings = AppSettings {
-                                    openrouter_key: Some(api_key),
-                                    ..Default::default()
-                                };
-                                let _ = save_settings_to_disk(&settings);
-                                let _ = app_clone.emit("oauth-success", ());
-                            }
-                            Err(e) => {
-                                let _ = app_clone.emit("oauth-error", e);
-                            }
+            std::thread::spawn(move || {
+                let rt = tokio::runtime::Runtime::new().unwrap();
+                rt.block_on(async {
+                    match exchange_code_for_key(&code, &verifier).await {
+                        Ok(issued) => {
+                            // Save the key, replacing any expiry left from a previous key
+                            let mut settings = load_settings_from_disk();
+                            settings.openrouter_key = Some(issued.key);
+                            settings.openrouter_key_expires_at = issued.expires_at;
+                            let _ = write_settings_file(&settings);
+
+                            let state = app_clone.state::<AppState>();
+                            let mut current = state.settings.lock().unwrap();
+                            current.openrouter_key = settings.openrouter_key.clone();
+                            current.openrouter_key_expires_at = settings.openrouter_key_expires_at;
+                            drop(current);
+
+                            let _ = app_clone.emit("oauth-success", ());
                         }
-                    });
+                        Err(e) => {
+                            let _ = app_clone.emit("oauth-error", e);
+                        }
+                    }
                 });
-            }
-            
-            // Respond with success page
-            let response = tiny_http::Response::from_string(
-                r#"<!DOCTYPE html>
+            });
+        } else {
+            let _ = app.emit("oauth-error", "Sign-in was cancelled or already completed".to_string());
+        }
+        
+        // Respond with success page
+        let response = tiny_http::Response::from_string(
+            r#"<!DOCTYPE html>
 <html>
 <head>
     <title>Sign In Complete</title>
@@ -4396,22 +9516,171 @@ fn run_oauth_callback_server(port: u16, app: tauri::AppHandle) -> Result<(), Str
     </div>
 </body>
 </html>"#
-            ).with_header(
-                tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap()
-            );
-            let _ = request.respond(response);
-        } else {
-            // Error response
-            let response = tiny_http::Response::from_string("Authorization failed")
-                .with_status_code(400);
-            let _ = request.respond(response);
-        }
+        ).with_header(
+            tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap()
+        );
+        let _ = request.respond(response);
+    } else {
+        let reason = query_param(&url, "error").unwrap_or_else(|| "no code in callback".to_string());
+        let _ = app.emit("oauth-error", format!("Authorization failed: {}", reason));
+
+        // Error response
+        let _ = request.respond(oauth_error_response("Authorization failed."));
     }
     
     Ok(())
 }
 
-async fn exchange_code_for_key(code: &str, verifier: &str) -> Result<String, String> {
+fn oauth_error_response(message: &str) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    tiny_http::Response::from_string(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Sign In Failed</title>
+    <style>
+        body {{ font-family: -apple-system, BlinkMacSystemFont, sans-serif; background: #0a0a0a; color: #fff; display: flex; align-items: center; justify-content: center; height: 100vh; margin: 0; }}
+        .container {{ text-align: center; }}
+        h1 {{ font-size: 24px; margin-bottom: 8px; }}
+        p {{ color: #888; font-size: 14px; }}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>Sign In Failed</h1>
+        <p>{}</p>
+    </div>
+</body>
+</html>"#,
+        message
+    ))
+    .with_status_code(400)
+    .with_header(
+        tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap()
+    )
+}
+
+#[tauri::command]
+fn cancel_openrouter_auth() {
+    // Bumping the session makes any running callback server exit on its next poll
+    OAUTH_SESSION.fetch_add(1, AtomicOrdering::SeqCst);
+    OAUTH_VERIFIER.lock().unwrap().take();
+    OAUTH_STATE.lock().unwrap().take();
+}
+
+/// Key returned by the OpenRouter PKCE exchange
+struct OpenRouterKey {
+    key: String,
+    expires_at: Option<i64>,
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Unix seconds of an RFC 3339 timestamp like "2025-06-01T12:00:00.123Z" or "2025-06-01 12:00:00+02:00"
+fn parse_rfc3339(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let num = |range: std::ops::Range<usize>| value.get(range)?.parse::<i64>().ok();
+    if value.len() < 19 || value.as_bytes()[4] != b'-' || value.as_bytes()[7] != b'-' || !matches!(value.as_bytes()[10], b'T' | b't' | b' ') {
+        return None;
+    }
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    // Skip fractional seconds, then read the UTC offset
+    let rest = value[19..].trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let (h, m) = rest[1..].split_once(':')?;
+            sign * (h.parse::<i64>().ok()? * 3600 + m.parse::<i64>().ok()? * 60)
+        }
+    };
+
+    // Days since 1970-01-01 (Howard Hinnant's days_from_civil)
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+/// Read an absolute `expires_at` (seconds, milliseconds or RFC 3339) or relative
+/// `expires_in` from a key response
+fn parse_key_expiry(data: &serde_json::Value, now: i64) -> Option<i64> {
+    let as_i64 = |v: &serde_json::Value| v.as_i64().or_else(|| v.as_str()?.trim().parse().ok());
+
+    if let Some(expires_at) = data.get("expires_at").and_then(as_i64) {
+        // Millisecond timestamps are far beyond any plausible seconds value
+        return Some(if expires_at > 100_000_000_000 { expires_at / 1000 } else { expires_at });
+    }
+    if let Some(expires_at) = data.get("expires_at").and_then(|v| v.as_str()).and_then(parse_rfc3339) {
+        return Some(expires_at);
+    }
+    data.get("expires_in").and_then(as_i64).map(|secs| now + secs)
+}
+
+fn openrouter_key_expired(settings: &AppSettings) -> bool {
+    settings.openrouter_key_expires_at.is_some_and(|at| at <= unix_now())
+}
+
+/// Whether a line of agent output reports a rejected OpenRouter key
+fn is_auth_failure(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    lower.contains("401") && (lower.contains("unauthorized") || lower.contains("auth") || lower.contains("api key"))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRouterAuthStatus {
+    pub authenticated: bool,
+    pub expires_at: Option<i64>,
+}
+
+#[tauri::command]
+async fn check_openrouter_auth(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<OpenRouterAuthStatus, String> {
+    let settings = state.settings.lock().unwrap().clone();
+    let expires_at = settings.openrouter_key_expires_at;
+    let expired = openrouter_key_expired(&settings);
+    let key = settings.openrouter_key.unwrap_or_default();
+    if key.is_empty() {
+        return Ok(OpenRouterAuthStatus { authenticated: false, expires_at });
+    }
+
+    // A revoked key only shows up server-side; network failures keep the local answer
+    let revoked = !expired && matches!(
+        reqwest::Client::new()
+            .get("https://openrouter.ai/api/v1/key")
+            .bearer_auth(&key)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await,
+        Ok(response) if response.status() == reqwest::StatusCode::UNAUTHORIZED
+    );
+
+    if expired || revoked {
+        let _ = app.emit("oauth-expired", ());
+    }
+    Ok(OpenRouterAuthStatus { authenticated: !expired && !revoked, expires_at })
+}
+
+async fn exchange_code_for_key(code: &str, verifier: &str) -> Result<OpenRouterKey, String> {
     let client = reqwest::Client::new();
     
     let response = client
@@ -4436,10 +9705,15 @@ async fn exchange_code_for_key(code: &str, verifier: &str) -> Result<String, Str
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
     
-    data.get("key")
+    let key = data.get("key")
         .and_then(|k| k.as_str())
         .map(|s| s.to_string())
-        .ok_or_else(|| "No key in response".to_string())
+        .ok_or_else(|| "No key in response".to_string())?;
+
+    Ok(OpenRouterKey {
+        key,
+        expires_at: parse_key_expiry(&data, unix_now()),
+    })
 }
 
 // ============================================================================
@@ -4461,9 +9735,61 @@ fn save_threads(threads: serde_json::Value) -> Result<(), String> {
     }
     let json = serde_json::to_string_pretty(&threads).map_err(|e| e.to_string())?;
     fs::write(&path, json).map_err(|e| format!("Failed to save threads: {}", e))?;
+
+    // Forget Goose sessions for threads that were deleted
+    let thread_ids: std::collections::HashSet<&str> = threads.as_array()
+        .map(|list| list.iter().filter_map(|t| t.get("id").and_then(|id| id.as_str())).collect())
+        .unwrap_or_default();
+    let mut sessions = load_goose_sessions();
+    let before = sessions.len();
+    sessions.retain(|thread_id, _| thread_ids.contains(thread_id.as_str()));
+    if sessions.len() != before {
+        save_goose_sessions(&sessions)?;
+    }
     Ok(())
 }
 
+fn get_goose_sessions_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("kobold")
+        .join("goose_sessions.json")
+}
+
+/// Map of thread id to the Goose session name holding its conversation
+fn load_goose_sessions() -> std::collections::HashMap<String, String> {
+    fs::read_to_string(get_goose_sessions_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_goose_sessions(sessions: &std::collections::HashMap<String, String>) -> Result<(), String> {
+    let path = get_goose_sessions_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(sessions).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save Goose sessions: {}", e))
+}
+
+/// Named Goose session backing a chat thread
+struct GooseSession {
+    name: String,
+    resume: bool,
+}
+
+/// Resume the thread's existing session when continuing, otherwise start a new named one
+fn goose_session_for_thread(thread_id: &str, continue_session: bool) -> GooseSession {
+    match load_goose_sessions().remove(thread_id) {
+        Some(name) if continue_session => GooseSession { name, resume: true },
+        _ => GooseSession {
+            name: format!("kobold-{}", uuid::Uuid::new_v4()),
+            resume: false,
+        },
+    }
+}
+
 #[tauri::command]
 fn load_threads() -> serde_json::Value {
     let path = get_threads_path();
@@ -4486,10 +9812,13 @@ async fn send_agent_message(
     app: tauri::AppHandle,
     message: String,
     project_path: Option<String>,
-    _continue_session: Option<bool>,
+    continue_session: Option<bool>,
+    thread_id: Option<String>,
+    turn_id: Option<String>,
     state: tauri::State<'_, AppState>,
 ) -> Result<String, String> {
     let settings = state.settings.lock().unwrap().clone();
+    let turn_id = turn_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
     
     let working_dir = match &project_path {
         Some(path) if !path.is_empty() && Path::new(path).exists() => path.clone(),
@@ -4498,70 +9827,370 @@ async fn send_agent_message(
         }
     };
 
-    let _ = ensure_project_config(&working_dir);
+    let _ = ensure_project_config(&working_dir);
+
+    // Check for OpenRouter API key (local providers don't need one)
+    let api_key = settings.openrouter_key.clone().unwrap_or_default();
+    let uses_openrouter = agent_provider(&settings) == DEFAULT_AGENT_PROVIDER;
+    if uses_openrouter && api_key.is_empty() {
+        return Ok("**Sign In Required**\n\nPlease sign in with OpenRouter in Settings to continue.".to_string());
+    }
+    if uses_openrouter && openrouter_key_expired(&settings) {
+        let _ = app.emit("oauth-expired", ());
+        return Ok("**Sign In Expired**\n\nYour OpenRouter session has expired. Please sign in again in Settings.".to_string());
+    }
+
+    // Check if Goose is installed (internal check, no branding shown)
+    if !detect_goose() {
+        return Ok("**Agent Setup Required**\n\nThe AI agent is not installed. Please install it and restart the app.\n\nVisit: https://github.com/block/goose".to_string());
+    }
+
+    // Registered before the slow preparation below so Stop works from here on
+    state.agent_turns.lock().unwrap().insert(turn_id.clone(), AgentTurn::Pending);
+
+    // Rollback point for the agent's edits
+    if settings.auto_commit.unwrap_or(false) && Path::new(&working_dir).join(".git").exists() {
+        let summary: String = message.lines().next().unwrap_or("").chars().take(60).collect();
+        if let Err(e) = git_snapshot(working_dir.clone(), format!("Snapshot before agent: {}", summary)) {
+            println!("[Git] Auto-commit skipped: {}", e);
+        }
+    }
+
+    // Auto-initialize Beads for task tracking if available
+    let beads_available = detect_beads();
+    if beads_available {
+        let beads_dir = Path::new(&working_dir).join(".beads");
+        if !beads_dir.exists() {
+            let _ = init_beads(working_dir.clone());
+        }
+    }
+
+    let _ = app.emit("agent-event", AgentEvent {
+        turn_id: Some(turn_id.clone()),
+        ..agent_event("start", format!("Working in: {}", working_dir), None, None)
+    });
+
+    // Get Beads context to inject into the message
+    let beads_context = if beads_available {
+        get_beads_context(working_dir.clone()).ok()
+    } else {
+        None
+    };
+
+    // Prepare message with Beads context
+    let enhanced_message = if let Some(ctx) = beads_context {
+        if !ctx.trim().is_empty() {
+            format!("{}\n\n---\nTask Context:\n{}", message, ctx)
+        } else {
+            message.clone()
+        }
+    } else {
+        message.clone()
+    };
+
+    let session = thread_id.as_deref()
+        .map(|id| goose_session_for_thread(id, continue_session.unwrap_or(false)));
+
+    // Hash the project before and after the turn so its edits can be undone
+    let before = if settings.agent_history.unwrap_or(false) {
+        let history_dir = working_dir.clone();
+        Some(tokio::task::spawn_blocking(move || hash_project_files(Path::new(&history_dir), true))
+            .await
+            .unwrap_or_default())
+    } else {
+        None
+    };
+
+    let result = run_goose(&app, &turn_id, &enhanced_message, &working_dir, &settings, session.as_ref()).await;
+    // run_goose removes the turn itself unless it failed before finishing
+    state.agent_turns.lock().unwrap().remove(&turn_id);
+
+    if let Some(before) = before {
+        let (history_dir, history_turn) = (working_dir.clone(), turn_id.clone());
+        match tokio::task::spawn_blocking(move || record_agent_turn(Path::new(&history_dir), &history_turn, &before)).await {
+            Ok(Ok(journal)) => {
+                if !journal.changes.is_empty() {
+                    println!("[History] Turn {} changed {} files", turn_id, journal.changes.len());
+                }
+                if !journal.too_large.is_empty() {
+                    println!("[History] Turn {} changed {} files too large to journal", turn_id, journal.too_large.len());
+                }
+            }
+            Ok(Err(e)) => println!("[History] Failed to record turn {}: {}", turn_id, e),
+            Err(_) => {}
+        }
+    }
+
+    // Remember a newly started session so the thread's next turn resumes it
+    if let (Some(thread_id), Some(session), Ok(Some(_))) = (&thread_id, &session, &result) {
+        if !session.resume {
+            let mut sessions = load_goose_sessions();
+            sessions.insert(thread_id.clone(), session.name.clone());
+            let _ = save_goose_sessions(&sessions);
+        }
+    }
+    let cancelled = matches!(result, Ok(None));
+
+    let _ = app.emit("agent-event", AgentEvent {
+        turn_id: Some(turn_id),
+        cancelled: Some(cancelled),
+        ..agent_event("done", String::new(), None, None)
+    });
+
+    result.map(|output| output.unwrap_or_else(|| "**Cancelled**\n\nThe agent was stopped before finishing.".to_string()))
+}
+
+// ============================================================================
+// Agent Change History
+// ============================================================================
+
+/// Files above this size are not journaled (large binary assets)
+const HISTORY_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Marks a hash_project_files value that is a size/mtime fingerprint of a file
+/// over HISTORY_MAX_FILE_BYTES rather than a content hash
+const HISTORY_LARGE_PREFIX: &str = "large:";
+
+/// One file an agent turn created, modified or deleted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    pub path: String,
+    /// "created", "modified" or "deleted"
+    pub change: String,
+    pub before_hash: Option<String>,
+    pub after_hash: Option<String>,
+}
+
+/// .tav/history/<turn_id>/journal.json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnJournal {
+    pub changes: Vec<JournalEntry>,
+    /// Files the turn changed that were over HISTORY_MAX_FILE_BYTES, so undo can't restore them
+    pub too_large: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResult {
+    pub restored: Vec<String>,
+    /// Files edited again since the turn; left alone and kept in the journal
+    pub skipped: Vec<String>,
+    /// Files the turn changed that were too large to journal; not restored
+    pub too_large: Vec<String>,
+}
 
-    // Check for OpenRouter API key
-    let api_key = settings.openrouter_key.clone().unwrap_or_default();
-    if api_key.is_empty() {
-        return Ok("**Sign In Required**\n\nPlease sign in with OpenRouter in Settings to continue.".to_string());
-    }
+fn history_dir(project: &Path) -> PathBuf {
+    project.join(".tav/history")
+}
 
-    // Check if Goose is installed (internal check, no branding shown)
-    if !detect_goose() {
-        return Ok("**Agent Setup Required**\n\nThe AI agent is not installed. Please install it and restart the app.\n\nVisit: https://github.com/block/goose".to_string());
+/// content_hash of every project file outside the ignore list, keyed by relative
+/// path; files over HISTORY_MAX_FILE_BYTES get a size/mtime fingerprint instead.
+/// With `store`, contents are also saved to .tav/history/objects/<hash> (once per
+/// distinct content) so the turn can restore them; record_agent_turn prunes the
+/// ones it turns out not to need.
+fn hash_project_files(project: &Path, store: bool) -> std::collections::HashMap<String, String> {
+    let objects_dir = history_dir(project).join("objects");
+    if store {
+        let _ = fs::create_dir_all(&objects_dir);
+    }
+    let mut hashes = std::collections::HashMap::new();
+    for (path, size) in walk_files(project, is_ignored_entry) {
+        let rel = path.strip_prefix(project).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        if size > HISTORY_MAX_FILE_BYTES {
+            let modified = fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            hashes.insert(rel, format!("{}{}:{}", HISTORY_LARGE_PREFIX, size, modified));
+            continue;
+        }
+        let Ok(content) = fs::read(&path) else { continue };
+        let hash = content_hash(&content);
+        if store && !objects_dir.join(&hash).exists() {
+            let _ = fs::write(objects_dir.join(&hash), &content);
+        }
+        hashes.insert(rel, hash);
     }
+    hashes
+}
 
-    // Auto-initialize Beads for task tracking if available
-    if detect_beads() {
-        let beads_dir = Path::new(&working_dir).join(".beads");
-        if !beads_dir.exists() {
-            let _ = init_beads(working_dir.clone());
+/// Journals under .tav/history, keyed by turn id
+fn read_turn_journals(project: &Path) -> Vec<(String, TurnJournal)> {
+    let Ok(entries) = fs::read_dir(history_dir(project)) else { return vec![] };
+    entries.flatten()
+        .filter_map(|entry| {
+            let journal = fs::read_to_string(entry.path().join("journal.json")).ok()?;
+            let journal = serde_json::from_str(&journal).ok()?;
+            Some((entry.file_name().to_string_lossy().to_string(), journal))
+        })
+        .collect()
+}
+
+/// Delete stored objects that no journal needs to restore a file
+fn prune_history_objects(project: &Path) {
+    let referenced: std::collections::HashSet<String> = read_turn_journals(project)
+        .into_iter()
+        .flat_map(|(_, journal)| journal.changes)
+        .filter_map(|entry| entry.before_hash)
+        .collect();
+    let Ok(objects) = fs::read_dir(history_dir(project).join("objects")) else { return };
+    for object in objects.flatten() {
+        if !referenced.contains(&*object.file_name().to_string_lossy()) {
+            let _ = fs::remove_file(object.path());
         }
     }
+}
 
-    let _ = app.emit("agent-event", AgentEvent {
-        event_type: "start".to_string(),
-        content: format!("Working in: {}", working_dir),
-        tool_name: None,
-        tool_args: None,
-    });
+fn write_turn_journal(turn_dir: &Path, journal: &TurnJournal) -> Result<(), String> {
+    fs::create_dir_all(turn_dir).map_err(|e| format!("Failed to create history directory: {}", e))?;
+    let json = serde_json::to_string_pretty(journal).map_err(|e| format!("Failed to encode journal: {}", e))?;
+    write_file_atomic(&turn_dir.join("journal.json"), &json)
+        .map_err(|e| format!("Failed to write journal: {}", e))
+}
 
-    // Get Beads context to inject into the message
-    let beads_context = if detect_beads() {
-        get_beads_context(working_dir.clone()).ok()
+/// Diff the project against `before` and journal the turn's changes to
+/// .tav/history/<turn_id>/journal.json, keeping only the stored objects a
+/// journal still refers to
+fn record_agent_turn(
+    project: &Path,
+    turn_id: &str,
+    before: &std::collections::HashMap<String, String>,
+) -> Result<TurnJournal, String> {
+    let after = hash_project_files(project, false);
+    let mut paths: Vec<&String> = before.keys().chain(after.keys().filter(|p| !before.contains_key(*p))).collect();
+    paths.sort();
+
+    let mut journal = TurnJournal::default();
+    for path in paths {
+        let (old, new) = (before.get(path), after.get(path));
+        let change = match (old, new) {
+            (None, Some(_)) => "created",
+            (Some(_), None) => "deleted",
+            (Some(a), Some(b)) if a != b => "modified",
+            _ => continue,
+        };
+        if old.into_iter().chain(new).any(|hash| hash.starts_with(HISTORY_LARGE_PREFIX)) {
+            journal.too_large.push(path.clone());
+            continue;
+        }
+        journal.changes.push(JournalEntry { path: path.clone(), change: change.to_string(), before_hash: old.cloned(), after_hash: new.cloned() });
+    }
+
+    let written = if journal.changes.is_empty() && journal.too_large.is_empty() {
+        Ok(())
     } else {
-        None
+        write_turn_journal(&history_dir(project).join(turn_id), &journal)
     };
+    prune_history_objects(project);
+    written.map(|_| journal)
+}
 
-    // Prepare message with Beads context
-    let enhanced_message = if let Some(ctx) = beads_context {
-        if !ctx.trim().is_empty() {
-            format!("{}\n\n---\nTask Context:\n{}", message, ctx)
+/// Revert the files an agent turn changed. Files edited again since the turn are
+/// skipped so later work is never overwritten.
+#[tauri::command]
+fn undo_agent_turn(project_path: String, turn_id: String) -> Result<UndoResult, String> {
+    let project = Path::new(&project_path);
+    if turn_id.is_empty() || turn_id == "objects" || turn_id.contains(['/', '\\', '.']) {
+        return Err(format!("Invalid turn id: {}", turn_id));
+    }
+    let turn_dir = history_dir(project).join(&turn_id);
+    let journal = fs::read_to_string(turn_dir.join("journal.json"))
+        .map_err(|_| format!("No recorded changes for turn {}", turn_id))?;
+    let journal: TurnJournal = serde_json::from_str(&journal)
+        .map_err(|e| format!("Failed to parse journal: {}", e))?;
+
+    let mut restored = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in journal.changes {
+        let file = project.join(&entry.path);
+        let current = fs::read(&file).ok().map(|c| content_hash(&c));
+        if current != entry.after_hash {
+            skipped.push(entry);
+            continue;
+        }
+        if let Some(hash) = &entry.before_hash {
+            if let Some(parent) = file.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            fs::copy(history_dir(project).join("objects").join(hash), &file)
+                .map_err(|e| format!("Failed to restore {}: {}", entry.path, e))?;
         } else {
-            message.clone()
+            fs::remove_file(&file).map_err(|e| format!("Failed to remove {}: {}", entry.path, e))?;
         }
+        restored.push(entry.path);
+    }
+
+    if skipped.is_empty() {
+        let _ = fs::remove_dir_all(&turn_dir);
     } else {
-        message.clone()
+        write_turn_journal(&turn_dir, &TurnJournal { changes: skipped.clone(), too_large: vec![] })?;
+    }
+    prune_history_objects(project);
+    println!("[History] Undid turn {}: {} restored, {} skipped", turn_id, restored.len(), skipped.len());
+    Ok(UndoResult {
+        restored,
+        skipped: skipped.into_iter().map(|e| e.path).collect(),
+        too_large: journal.too_large,
+    })
+}
+
+#[tauri::command]
+fn cancel_agent_message(turn_id: String, state: tauri::State<AppState>) -> Result<(), String> {
+    // Removing the child marks the turn cancelled; killing it closes the pipes so the readers finish
+    let mut turns = state.agent_turns.lock().unwrap();
+    let mut child = match turns.remove(&turn_id) {
+        Some(AgentTurn::Running(child)) => child,
+        Some(AgentTurn::Pending | AgentTurn::Cancelled) => {
+            turns.insert(turn_id.clone(), AgentTurn::Cancelled);
+            println!("[Agent] Cancelled turn {} before Goose started", turn_id);
+            return Ok(());
+        }
+        None => return Err(format!("No running agent turn: {}", turn_id)),
     };
+    drop(turns);
+    child.kill().map_err(|e| format!("Failed to stop agent: {}", e))?;
+    let _ = child.wait();
+    println!("[Agent] Cancelled turn {}", turn_id);
+    Ok(())
+}
 
-    let result = run_goose(&app, &enhanced_message, &working_dir, &settings).await;
+const DEFAULT_AGENT_PROVIDER: &str = "openrouter";
 
-    let _ = app.emit("agent-event", AgentEvent {
-        event_type: "done".to_string(),
-        content: "".to_string(),
-        tool_name: None,
-        tool_args: None,
-    });
+fn agent_provider(settings: &AppSettings) -> &str {
+    settings.agent_provider.as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .unwrap_or(DEFAULT_AGENT_PROVIDER)
+}
 
-    result
+/// Environment variable Goose reads for a provider's endpoint
+fn provider_base_url_env(provider: &str) -> Option<&'static str> {
+    match provider {
+        "openrouter" => Some("OPENROUTER_HOST"),
+        "ollama" => Some("OLLAMA_HOST"),
+        "openai" => Some("OPENAI_HOST"),
+        "anthropic" => Some("ANTHROPIC_HOST"),
+        "azure_openai" => Some("AZURE_OPENAI_ENDPOINT"),
+        _ => None,
+    }
 }
 
+/// Number of trailing stderr lines included when Goose fails
+const GOOSE_STDERR_TAIL_LINES: usize = 20;
+
+/// Run one Goose turn, returning `None` if it was cancelled via `cancel_agent_message`
 async fn run_goose(
     app: &tauri::AppHandle,
+    turn_id: &str,
     message: &str,
     working_dir: &str,
     settings: &AppSettings,
-) -> Result<String, String> {
+    session: Option<&GooseSession>,
+) -> Result<Option<String>, String> {
     let working_path = Path::new(working_dir);
     if !working_path.exists() {
         return Err(format!("Working directory does not exist: {}", working_dir));
@@ -4572,16 +10201,39 @@ async fn run_goose(
         .to_string_lossy()
         .to_string();
 
-    // Build goose command with OpenRouter configuration
+    // Build goose command with the configured provider
+    let provider = agent_provider(settings);
     let mut cmd = Command::new("goose");
-    cmd.args(["run", "--text", message])
-        .current_dir(&abs_working_dir)
-        .env("GOOSE_PROVIDER", "openrouter");
+    cmd.args(["run", "--text", message]);
+    if let Some(session) = session {
+        cmd.args(["--name", &session.name]);
+        if session.resume {
+            cmd.arg("--resume");
+        }
+    }
+    if goose_supports_stream_json() {
+        cmd.args(["--output-format", "stream-json"]);
+    }
+    cmd.current_dir(&abs_working_dir)
+        .env("GOOSE_PROVIDER", provider);
+
+    // Set model (default to claude-sonnet-4-20250514 on OpenRouter; other providers use Goose's own default)
+    let model = settings.goose_model.clone().filter(|m| !m.is_empty()).or_else(|| {
+        (provider == DEFAULT_AGENT_PROVIDER).then(|| "anthropic/claude-sonnet-4-20250514".to_string())
+    });
+    if let Some(model) = &model {
+        cmd.env("GOOSE_MODEL", model);
+    }
 
-    // Set model (default to claude-sonnet-4-20250514 if not specified)
-    let model = settings.goose_model.clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".to_string());
-    cmd.env("GOOSE_MODEL", &model);
+    // Point the provider at a custom endpoint, e.g. a local Ollama server
+    if let Some(base_url) = settings.agent_base_url.as_deref().filter(|u| !u.is_empty()) {
+        match provider_base_url_env(provider) {
+            Some(var) => {
+                cmd.env(var, base_url);
+            }
+            None => println!("[Agent] Provider {} has no base URL setting, ignoring {}", provider, base_url),
+        }
+    }
 
     // Set OpenRouter API key
     if let Some(key) = &settings.openrouter_key {
@@ -4592,86 +10244,341 @@ async fn run_goose(
 
     cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
+    // Stopped while the turn was being prepared
+    let state = app.state::<AppState>();
+    if matches!(state.agent_turns.lock().unwrap().get(turn_id), Some(AgentTurn::Cancelled)) {
+        state.agent_turns.lock().unwrap().remove(turn_id);
+        return Ok(None);
+    }
+
     let mut child = cmd.spawn()
         .map_err(|e| format!("Failed to start Goose in {}: {}", abs_working_dir, e))?;
 
     let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
     let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
 
-    let mut full_output = String::new();
-
-    let reader = BufReader::new(stdout);
-    for line in reader.lines() {
-        if let Ok(line) = line {
-            // Check for tool-related output patterns
-            if line.contains("tool_use") || line.contains("Reading") || line.contains("Writing") || line.contains("executing") {
-                let _ = app.emit("agent-event", AgentEvent {
-                    event_type: "tool_start".to_string(),
-                    content: line.clone(),
-                    tool_name: Some(extract_tool_name(&line)),
-                    tool_args: None,
-                });
-            } else if line.contains("tool_result") || line.contains("Created") || line.contains("Updated") || line.contains("completed") {
-                let _ = app.emit("agent-event", AgentEvent {
-                    event_type: "tool_end".to_string(),
-                    content: line.clone(),
-                    tool_name: None,
-                    tool_args: None,
-                });
-            } else if !line.trim().is_empty() {
-                let _ = app.emit("agent-event", AgentEvent {
-                    event_type: "output".to_string(),
-                    content: format!("{}\n", line),
-                    tool_name: None,
-                    tool_args: None,
-                });
-            }
-            full_output.push_str(&line);
-            full_output.push('\n');
+    {
+        let mut turns = state.agent_turns.lock().unwrap();
+        // Cancelled between the check above and the spawn
+        if matches!(turns.remove(turn_id), Some(AgentTurn::Cancelled)) {
+            drop(turns);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
         }
+        turns.insert(turn_id.to_string(), AgentTurn::Running(child));
     }
 
-    // Read stderr
-    let stderr_reader = BufReader::new(stderr);
-    for line in stderr_reader.lines() {
-        if let Ok(line) = line {
+    // Read stderr on its own thread so a full pipe can't stall stdout
+    let stderr_app = app.clone();
+    let stderr_thread = std::thread::spawn(move || {
+        let mut auth_failed = false;
+        let mut tail = std::collections::VecDeque::with_capacity(GOOSE_STDERR_TAIL_LINES);
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            auth_failed |= is_auth_failure(&line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            if tail.len() == GOOSE_STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line.clone());
             // Filter out noise, only emit actual errors
-            if !line.trim().is_empty() && !line.contains("Loading") {
-                let _ = app.emit("agent-event", AgentEvent {
-                    event_type: "error".to_string(),
-                    content: line.clone(),
-                    tool_name: None,
-                    tool_args: None,
-                });
+            if !line.contains("Loading") {
+                let _ = stderr_app.emit("agent-event", agent_event("error", line, None, None));
             }
         }
+        (auth_failed, tail)
+    });
+
+    let (event_tx, batcher_thread) = spawn_agent_event_batcher(app.clone());
+    let mut auth_failed = false;
+    let mut parser = GooseOutputParser::default();
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        auth_failed |= is_auth_failure(&line);
+        for event in parser.feed(&line) {
+            let _ = event_tx.send(event);
+        }
     }
+    for event in parser.finish() {
+        let _ = event_tx.send(event);
+    }
+    drop(event_tx);
+    let _ = batcher_thread.join();
+    let full_output = parser.text;
 
-    let _ = child.wait();
+    let (stderr_auth_failed, stderr_tail) = stderr_thread.join().unwrap_or_default();
+    auth_failed |= stderr_auth_failed;
+
+    // A missing entry means cancel_agent_message already killed and reaped the process
+    let child = state.agent_turns.lock().unwrap().remove(turn_id);
+    let status = match child {
+        Some(AgentTurn::Running(mut child)) => child.wait().map_err(|e| format!("Failed to wait for Goose: {}", e))?,
+        _ => return Ok(None),
+    };
+
+    if auth_failed && provider == DEFAULT_AGENT_PROVIDER {
+        println!("[Agent] OpenRouter rejected the key, prompting re-auth");
+        let _ = app.emit("oauth-expired", ());
+    }
+
+    if !status.success() {
+        let code = status.code().map(|c| c.to_string()).unwrap_or_else(|| "signal".to_string());
+        let details = Vec::from(stderr_tail).join("\n");
+        let message = if details.is_empty() {
+            format!("Goose exited with code {}", code)
+        } else {
+            format!("Goose exited with code {}:\n{}", code, details)
+        };
+        println!("[Agent] {}", message);
+        let _ = app.emit("agent-event", agent_event("error", message.clone(), None, None));
+        return Err(message);
+    }
 
     if full_output.trim().is_empty() {
-        Ok("Goose completed the task.".to_string())
+        Ok(Some("Goose completed the task.".to_string()))
     } else {
-        Ok(full_output)
+        Ok(Some(full_output))
     }
 }
 
-fn extract_tool_name(line: &str) -> String {
-    if let Some(start) = line.find("Tool:") {
-        let rest = &line[start + 5..];
-        rest.split_whitespace().next().unwrap_or("unknown").to_string()
-    } else if let Some(start) = line.find("Running:") {
-        let rest = &line[start + 8..];
-        rest.split_whitespace().next().unwrap_or("command").to_string()
-    } else if line.contains("Reading") {
-        "read_file".to_string()
-    } else if line.contains("Writing") {
-        "write_file".to_string()
-    } else {
-        "tool".to_string()
+/// How long streamed agent output is buffered before being emitted to the frontend
+const AGENT_EVENT_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Merges consecutive `output` events; any other event flushes the pending text first
+#[derive(Default)]
+struct AgentEventBatch {
+    pending: Option<AgentEvent>,
+}
+
+impl AgentEventBatch {
+    /// Add an event, returning whatever should be emitted right away
+    fn push(&mut self, event: AgentEvent) -> Vec<AgentEvent> {
+        if event.event_type == "output" {
+            match self.pending.as_mut() {
+                Some(pending) => pending.content.push_str(&event.content),
+                None => self.pending = Some(event),
+            }
+            return Vec::new();
+        }
+        let mut ready: Vec<AgentEvent> = self.pending.take().into_iter().collect();
+        ready.push(event);
+        ready
+    }
+
+    fn flush(&mut self) -> Option<AgentEvent> {
+        self.pending.take()
+    }
+}
+
+/// Emit agent events from a channel, batching output text on a short interval
+fn spawn_agent_event_batcher(
+    app: tauri::AppHandle,
+) -> (std::sync::mpsc::Sender<AgentEvent>, std::thread::JoinHandle<()>) {
+    let (tx, rx) = std::sync::mpsc::channel::<AgentEvent>();
+    let handle = std::thread::spawn(move || {
+        let mut batch = AgentEventBatch::default();
+        let mut last_flush = std::time::Instant::now();
+        loop {
+            let wait = AGENT_EVENT_FLUSH_INTERVAL.saturating_sub(last_flush.elapsed());
+            match rx.recv_timeout(wait) {
+                Ok(event) => {
+                    for ready in batch.push(event) {
+                        let _ = app.emit("agent-event", ready);
+                        last_flush = std::time::Instant::now();
+                    }
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+            if last_flush.elapsed() >= AGENT_EVENT_FLUSH_INTERVAL {
+                if let Some(output) = batch.flush() {
+                    let _ = app.emit("agent-event", output);
+                }
+                last_flush = std::time::Instant::now();
+            }
+        }
+        if let Some(output) = batch.flush() {
+            let _ = app.emit("agent-event", output);
+        }
+    });
+    (tx, handle)
+}
+
+/// Whether the installed Goose accepts `--output-format stream-json` (checked once)
+fn goose_supports_stream_json() -> bool {
+    static SUPPORTED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        run_tool("goose", &["run", "--help"])
+            .map(|out| String::from_utf8_lossy(&out.stdout).contains("stream-json"))
+            .unwrap_or(false)
+    })
+}
+
+fn agent_event(event_type: &str, content: String, tool_name: Option<String>, tool_args: Option<String>) -> AgentEvent {
+    AgentEvent {
+        event_type: event_type.to_string(),
+        content,
+        tool_name,
+        tool_args,
+        turn_id: None,
+        cancelled: None,
+    }
+}
+
+/// Turns Goose stdout into typed agent events.
+///
+/// With `--output-format stream-json` each line is a JSON event whose message content
+/// carries `text`, `toolRequest` and `toolResponse` items. Older Goose builds print plain
+/// text instead, where a tool call is a `─── name | extension ───` header followed by
+/// `key: value` argument lines.
+#[derive(Default)]
+struct GooseOutputParser {
+    /// Assistant text, returned as the final response
+    text: String,
+    /// Tool header seen in text mode whose argument lines are still being collected
+    pending_tool: Option<(String, serde_json::Map<String, serde_json::Value>)>,
+    /// A text-mode tool has started and not yet been closed by later output
+    tool_running: bool,
+}
+
+impl GooseOutputParser {
+    fn feed(&mut self, line: &str) -> Vec<AgentEvent> {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(line) {
+            if json.get("type").is_some() {
+                return self.feed_json(&json);
+            }
+        }
+        self.feed_text(line)
+    }
+
+    fn finish(&mut self) -> Vec<AgentEvent> {
+        let mut events = self.flush_pending_tool();
+        if std::mem::take(&mut self.tool_running) {
+            events.push(agent_event("tool_end", String::new(), None, None));
+        }
+        events
+    }
+
+    fn feed_json(&mut self, json: &serde_json::Value) -> Vec<AgentEvent> {
+        let mut events = Vec::new();
+        match json.get("type").and_then(|t| t.as_str()) {
+            Some("message") => {
+                let message = &json["message"];
+                if message.get("role").and_then(|r| r.as_str()) == Some("user") {
+                    // Tool responses arrive as user-role messages; plain user text is our own prompt
+                    let has_tool_response = message["content"].as_array()
+                        .is_some_and(|items| items.iter().any(|i| i["type"] == "toolResponse"));
+                    if !has_tool_response {
+                        return events;
+                    }
+                }
+                for item in message["content"].as_array().into_iter().flatten() {
+                    match item.get("type").and_then(|t| t.as_str()) {
+                        Some("text") => {
+                            let text = item["text"].as_str().unwrap_or_default();
+                            if !text.trim().is_empty() {
+                                // Separate text blocks so consecutive messages don't run together
+                                if !self.text.is_empty() && !self.text.ends_with('\n') {
+                                    self.text.push('\n');
+                                }
+                                self.text.push_str(text);
+                                events.push(agent_event("output", text.to_string(), None, None));
+                            }
+                        }
+                        Some("toolRequest") => {
+                            let call = &item["toolCall"]["value"];
+                            let name = call["name"].as_str().unwrap_or("tool").to_string();
+                            let args = call.get("arguments").filter(|a| !a.is_null()).map(|a| a.to_string());
+                            events.push(agent_event("tool_start", name.clone(), Some(name), args));
+                        }
+                        Some("toolResponse") => {
+                            let result = &item["toolResult"];
+                            if result["status"] == "error" {
+                                let error = result["error"].as_str().map(str::to_string)
+                                    .unwrap_or_else(|| result["error"].to_string());
+                                events.push(agent_event("error", error, None, None));
+                            } else {
+                                let content = result["value"].as_array().into_iter().flatten()
+                                    .filter_map(|v| v["text"].as_str())
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+                                events.push(agent_event("tool_end", content, None, None));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Some("error") => {
+                let error = json["error"].as_str().unwrap_or("Agent error").to_string();
+                events.push(agent_event("error", error, None, None));
+            }
+            _ => {}
+        }
+        events
+    }
+
+    fn feed_text(&mut self, line: &str) -> Vec<AgentEvent> {
+        let trimmed = line.trim();
+
+        if let Some(name) = parse_goose_tool_header(trimmed) {
+            let mut events = self.flush_pending_tool();
+            if std::mem::take(&mut self.tool_running) {
+                events.push(agent_event("tool_end", String::new(), None, None));
+            }
+            self.pending_tool = Some((name, serde_json::Map::new()));
+            return events;
+        }
+
+        if let Some((_, args)) = self.pending_tool.as_mut() {
+            if let Some((key, value)) = trimmed.split_once(": ").filter(|(k, _)| is_goose_arg_key(k)) {
+                args.insert(key.to_string(), serde_json::Value::String(value.to_string()));
+                return Vec::new();
+            }
+        }
+
+        let mut events = self.flush_pending_tool();
+        if trimmed.is_empty() {
+            return events;
+        }
+        if std::mem::take(&mut self.tool_running) {
+            events.push(agent_event("tool_end", String::new(), None, None));
+        }
+        self.text.push_str(line);
+        self.text.push('\n');
+        events.push(agent_event("output", format!("{}\n", line), None, None));
+        events
+    }
+
+    fn flush_pending_tool(&mut self) -> Vec<AgentEvent> {
+        match self.pending_tool.take() {
+            Some((name, args)) => {
+                self.tool_running = true;
+                let content = args.values()
+                    .filter_map(|v| v.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let args = (!args.is_empty()).then(|| serde_json::Value::Object(args).to_string());
+                vec![agent_event("tool_start", content, Some(name), args)]
+            }
+            None => Vec::new(),
+        }
     }
 }
 
+/// Parse a Goose text-mode tool header such as `─── shell | developer ──────`
+fn parse_goose_tool_header(line: &str) -> Option<String> {
+    let inner = line.strip_prefix("───")?.trim_matches(|c: char| c == '─' || c.is_whitespace());
+    let (name, _extension) = inner.split_once(" | ")?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn is_goose_arg_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 // ============================================================================
 // File Watcher for Live Preview
 // ============================================================================
@@ -4748,6 +10655,11 @@ fn start_file_watcher(project_path: String, app: tauri::AppHandle) -> Result<(),
                         
                         println!("[FileWatcher] Changes detected: {:?}", changed_files);
                         
+                        // Running native sessions reload scripts in place
+                        if is_script_only_change(&changed_files) {
+                            request_session_script_reload(&app, &path, &changed_files);
+                        }
+                        
                         // Emit event to frontend
                         let _ = app.emit("project-files-changed", changed_files);
                     }
@@ -4783,6 +10695,9 @@ fn main() {
         .manage(AppState {
             settings: Mutex::new(initial_settings),
             game_sessions: Mutex::new(std::collections::HashMap::new()),
+            agent_turns: Mutex::new(std::collections::HashMap::new()),
+            tool_status: Mutex::new(None),
+            exporting: Mutex::new(std::collections::HashSet::new()),
         })
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
@@ -4791,54 +10706,118 @@ fn main() {
             list_files,
             read_file,
             write_file,
+            read_file_bytes,
+            write_file_bytes,
             delete_file,
+            get_file_info,
+            generate_thumbnail,
             run_godot,
             export_project_web,
+            list_export_presets,
+            update_autoloads,
+            add_component_to_scene,
+            get_component_catalog,
+            verify_animation_setup,
+            repair_animation_setup,
+            package_web_build,
+            detect_butler,
+            publish_to_itch,
+            validate_project,
+            analyze_unused_assets,
+            set_rendering_method,
+            strip_kobold_bridge,
             ensure_export_templates,
             check_setup_status,
             open_url,
+            reveal_in_file_manager,
             download_asset,
             download_and_extract_asset,
             check_asset_exists,
             setup_3d_character,
             start_preview_server,
+            start_asset_server,
+            stop_preview_server,
             start_file_watcher,
             stop_file_watcher,
             get_settings,
             save_settings,
+            validate_gemini_key,
+            validate_openrouter_key,
+            list_openrouter_models,
             save_threads,
             load_threads,
             start_openrouter_auth,
+            check_openrouter_auth,
+            cancel_openrouter_auth,
             detect_beads,
             install_beads,
             init_beads,
+            git_status,
+            git_snapshot,
+            undo_agent_turn,
             get_beads_context,
+            get_beads_tasks,
+            close_beads_task,
             detect_godot,
+            set_godot_path,
+            list_godot_installations,
+            pin_godot_version,
+            get_godot_pin,
             install_godot,
             detect_godot_mcp,
             install_godot_mcp,
             setup_godot_mcp_config,
+            verify_mcp_setup,
             create_project_from_template,
+            create_project_from_template_dir,
             initialize_godot_project,
+            list_templates,
+            refresh_project_rules,
             open_url,
             send_agent_message,
+            cancel_agent_message,
             start_game_session,
             get_game_frame,
+            get_game_logs,
             send_game_action,
             execute_actions,
+            test_controls_in_session,
             stop_game_session,
+            get_session_status,
+            list_game_sessions,
             plan_trajectory,
             analyze_game_frame,
+            analyze_game_frames,
+            analyze_game_frame_structured,
             test_game_controls,
+            compare_frames,
+            smoke_test_scene,
+            request_node_capture,
+            capture_node,
+            capture_now,
+            move_game_window,
+            hot_reload_script,
+            snapshot_scene,
+            diff_scene,
             analyze_node_captures,
             get_input_mappings,
+            get_key_name,
+            set_input_action,
+            get_keycode,
             clear_export_cache,
+            get_project_disk_usage,
+            clear_godot_cache,
+            backup_project,
+            restore_project_backup,
             get_animation_catalog,
             download_animation_pack,
             setup_animation_library,
             list_project_animations,
+            convert_fbx_to_glb,
+            batch_retarget_animations,
             run_playtest,
             check_nitrogen_installed,
+            detect_all_tools,
             start_nitrogen_server,
             stop_nitrogen_server,
             get_control_mappings,
@@ -4848,3 +10827,1356 @@ fn main() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    /// Scratch directory under the system temp dir, removed when dropped (also
+    /// when the test panics)
+    struct TempProject(PathBuf);
+
+    fn temp_project(name: &str) -> TempProject {
+        let dir = std::env::temp_dir().join(format!("tav-{}-{}", name, uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        TempProject(dir)
+    }
+
+    impl std::ops::Deref for TempProject {
+        type Target = Path;
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempProject {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempProject {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_project_hash_tracks_content() {
+        let dir = temp_project("hash");
+        let script = dir.join("player.gd");
+        fs::write(&script, "extends Node\n").unwrap();
+        let initial = get_project_hash(&dir);
+
+        // Touching a script without changing it keeps the cache valid
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(3600);
+        fs::File::options().write(true).open(&script).unwrap().set_modified(later).unwrap();
+        assert_eq!(get_project_hash(&dir), initial);
+
+        fs::write(&script, "extends Node2D\n").unwrap();
+        assert_ne!(get_project_hash(&dir), initial);
+    }
+
+    #[test]
+    fn test_resolve_preview_path_blocks_traversal() {
+        let dir = temp_project("preview");
+        let export = dir.join("web");
+        fs::create_dir_all(export.join("assets/sprites")).unwrap();
+        fs::write(export.join("index.html"), "<html></html>").unwrap();
+        fs::write(export.join("assets/sprites/hero.png"), [0u8]).unwrap();
+        fs::write(dir.join("secret.txt"), "secret").unwrap();
+        let root = export.canonicalize().unwrap();
+
+        assert_eq!(resolve_preview_path(&root, "/").unwrap(), root.join("index.html"));
+        assert_eq!(resolve_preview_path(&root, "/assets/sprites/hero.png?v=2").unwrap(), root.join("assets/sprites/hero.png"));
+        assert_eq!(resolve_preview_path(&root, "/assets/sprites/hero%2Epng").unwrap(), root.join("assets/sprites/hero.png"));
+        assert_eq!(resolve_preview_path(&root, "/missing.png"), Err(404));
+        assert_eq!(resolve_preview_path(&root, "/assets"), Err(404));
+
+        assert_eq!(resolve_preview_path(&root, "/../secret.txt"), Err(403));
+        assert_eq!(resolve_preview_path(&root, "/assets/../../secret.txt"), Err(403));
+        assert_eq!(resolve_preview_path(&root, "/%2e%2e/secret.txt"), Err(403));
+        assert_eq!(resolve_preview_path(&root, "/..%2f..%2f..%2fetc%2fpasswd"), Err(403));
+    }
+
+    #[test]
+    fn test_asset_server_serves_and_stops() {
+        use std::io::{Read, Write};
+
+        let dir = temp_project("assets");
+        fs::create_dir_all(dir.join("assets/models")).unwrap();
+        fs::write(dir.join("assets/models/hero.glb"), b"glTF").unwrap();
+
+        let port = start_asset_server(dir.to_string_lossy().to_string()).unwrap();
+        let get = |path: &str| {
+            let mut stream = std::net::TcpStream::connect(("127.0.0.1", port)).unwrap();
+            write!(stream, "GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let ok = get("/models/hero.glb");
+        assert!(ok.starts_with("HTTP/1.0 200") || ok.starts_with("HTTP/1.1 200"), "{}", ok);
+        assert!(ok.contains("model/gltf-binary"));
+        assert!(get("/../assets/models/hero.glb").contains(" 403 "));
+
+        stop_preview_server(port).unwrap();
+        assert!(stop_preview_server(port).is_err());
+    }
+
+    #[test]
+    fn test_static_server_reuse_and_preferred_port() {
+        let dir = temp_project("serve");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+
+        let free_port = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap().local_addr().unwrap().port();
+        let port = start_static_server(dir.join("a"), "PreviewServer", Some(free_port), None).unwrap();
+        assert_eq!(port, free_port);
+        // Same root: the running server is returned rather than a new one
+        assert_eq!(start_static_server(dir.join("a"), "PreviewServer", None, None).unwrap(), port);
+        let other = start_static_server(dir.join("b"), "PreviewServer", Some(port), None).unwrap();
+        assert_ne!(other, port);
+
+        // HTTPS is a separate server for the same root
+        let identity = generate_localhost_identity().unwrap();
+        assert!(identity.certificate.starts_with(b"-----BEGIN CERTIFICATE-----"));
+        let secure = start_static_server(dir.join("a"), "PreviewServer", None, Some(identity)).unwrap();
+        assert_ne!(secure, port);
+
+        stop_preview_server(port).unwrap();
+        stop_preview_server(other).unwrap();
+        stop_preview_server(secure).unwrap();
+    }
+
+    #[test]
+    fn test_preview_server_headers() {
+        assert_eq!(preview_mime_type(Path::new("index.wasm")), "application/wasm");
+        assert_eq!(preview_mime_type(Path::new("assets/logo.SVG")), "image/svg+xml");
+        assert_eq!(preview_mime_type(Path::new("music/theme.ogg")), "audio/ogg");
+        assert_eq!(preview_mime_type(Path::new("index.pck")), "application/octet-stream");
+        assert_eq!(preview_cache_control(Path::new("index.pck")), "no-cache");
+        assert_eq!(preview_cache_control(Path::new("fonts/ui.woff2")), "public, max-age=3600");
+        assert!(preview_not_found_page("/<script>").contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_diff_scene_nodes() {
+        let scene = r#"[gd_scene load_steps=2 format=3]
+
+[ext_resource type="PackedScene" path="res://player.tscn" id="1"]
+
+[node name="Main" type="Node3D"]
+
+[node name="Player" parent="." instance=ExtResource("1")]
+
+[node name="Camera" type="Camera3D" parent="Player"]
+
+[node name="Light" type="OmniLight3D" parent="."]
+"#;
+        let current = parse_tscn_nodes(scene);
+        assert_eq!(current[0], SceneNode { path: ".".to_string(), class: Some("Node3D".to_string()) });
+        assert_eq!(current[1], SceneNode { path: "Player".to_string(), class: None });
+        assert_eq!(current[2].path, "Player/Camera");
+
+        let node = |path: &str, class: &str| SceneNode { path: path.to_string(), class: Some(class.to_string()) };
+        let baseline = vec![
+            node(".", "Node3D"),
+            node("Player", "CharacterBody3D"),
+            node("Camera", "Camera3D"),
+            node("Light", "DirectionalLight3D"),
+            node("Enemy", "CharacterBody3D"),
+        ];
+        let changes = diff_scene_nodes(&baseline, &current);
+        let summary: Vec<(&str, &str)> = changes.iter().map(|c| (c.kind.as_str(), c.path.as_str())).collect();
+        assert_eq!(summary, vec![("removed", "Enemy"), ("retyped", "Light"), ("moved", "Player/Camera")]);
+        assert_eq!(changes[2].from.as_deref(), Some("Camera"));
+        assert!(diff_scene_nodes(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_script_res_path() {
+        let project = Path::new("/games/demo");
+        assert_eq!(script_res_path(project, "/games/demo/scripts/player.gd").unwrap(), "res://scripts/player.gd");
+        assert_eq!(script_res_path(project, "res://player.gd").unwrap(), "res://player.gd");
+        assert_eq!(script_res_path(project, "scripts/enemy.gd").unwrap(), "res://scripts/enemy.gd");
+        assert!(script_res_path(project, "/games/demo/main.tscn").is_err());
+        assert!(script_res_path(project, "/elsewhere/player.gd").is_err());
+
+        assert!(is_script_only_change(&["/games/demo/player.gd".to_string()]));
+        assert!(!is_script_only_change(&["/games/demo/player.gd".to_string(), "/games/demo/main.tscn".to_string()]));
+        assert!(!is_script_only_change(&[]));
+    }
+
+    #[test]
+    fn test_file_bytes_round_trip() {
+        let dir = temp_project("bytes");
+        let path = dir.join("bytes.bin");
+        let path = path.to_string_lossy().to_string();
+        // glTF binary magic followed by bytes that aren't valid UTF-8
+        let data = vec![b'g', b'l', b'T', b'F', 0x00, 0xff, 0xfe, 0x80];
+        write_file_bytes(path.clone(), data.clone()).unwrap();
+        assert_eq!(read_file_bytes(path.clone()).unwrap(), data);
+        assert!(read_file(path.clone()).is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_helpers() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        assert_eq!(png_dimensions(&png), Some((640, 480)));
+        assert_eq!(png_dimensions(b"GIF89a not a png at all"), None);
+
+        let dir = temp_project("thumb");
+        fs::create_dir_all(dir.join("assets/models")).unwrap();
+        fs::write(dir.join("project.godot"), "").unwrap();
+        let model = dir.join("assets/models/hero.glb");
+        assert_eq!(thumbnail_project_root(&model), dir.to_path_buf());
+        assert_ne!(thumbnail_cache_prefix(&model), thumbnail_cache_prefix(&dir.join("assets/models/enemy.glb")));
+    }
+
+    #[test]
+    fn test_get_file_info() {
+        let dir = temp_project("file-info");
+        let file = dir.join("a.txt");
+        fs::write(&file, "hello").unwrap();
+
+        let info = get_file_info(file.to_string_lossy().to_string()).unwrap();
+        assert_eq!(info.size, 5);
+        assert!(!info.is_dir && !info.is_symlink && !info.readonly);
+        assert!(info.modified > 0);
+        assert!(get_file_info(dir.to_string_lossy().to_string()).unwrap().is_dir);
+
+        let missing = dir.join("missing.txt").to_string_lossy().to_string();
+        assert_eq!(get_file_info(missing.clone()).unwrap_err(), format!("Path does not exist: {}", missing));
+    }
+
+    #[test]
+    fn test_parse_env_line() {
+        assert_eq!(parse_env_line("GEMINI_API_KEY=abc", "GEMINI_API_KEY"), Some("abc".to_string()));
+        assert_eq!(parse_env_line("export GEMINI_API_KEY=abc", "GEMINI_API_KEY"), Some("abc".to_string()));
+        assert_eq!(parse_env_line("GEMINI_API_KEY=\"a b#c\" # note", "GEMINI_API_KEY"), Some("a b#c".to_string()));
+        assert_eq!(parse_env_line("GEMINI_API_KEY='abc'", "GEMINI_API_KEY"), Some("abc".to_string()));
+        assert_eq!(parse_env_line("GEMINI_API_KEY = abc # note", "GEMINI_API_KEY"), Some("abc".to_string()));
+        assert_eq!(parse_env_line("GEMINI_API_KEY_2=abc", "GEMINI_API_KEY"), None);
+        assert_eq!(parse_env_line("# GEMINI_API_KEY=abc", "GEMINI_API_KEY"), None);
+    }
+
+    #[test]
+    fn test_platformer_tilemap_template() {
+        let (scene, script) = generate_template_files("2d", "platformer-tilemap");
+        let nodes = parse_tscn_nodes(&scene);
+        assert!(nodes.iter().any(|n| n.path == "Level" && n.class.as_deref() == Some("TileMapLayer")));
+        assert!(nodes.iter().any(|n| n.path == "Player/HealthComponent"));
+        assert!(scene.contains("physics_layer_0/collision_layer = 1"));
+        assert!(scene.contains("0:0/0/physics_layer_0/polygon_0/points"));
+        assert_eq!(script.as_str(), PLATFORMER_2D_PLAYER);
+
+        // tile_map_data is a u16 format header followed by 12-byte cells
+        let data = scene.lines().find_map(|l| l.strip_prefix("tile_map_data = PackedByteArray(")).unwrap();
+        let bytes = data.trim_end_matches(')').split(", ").count();
+        assert!(bytes > 2 && (bytes - 2) % 12 == 0);
+    }
+
+    #[test]
+    fn test_multiplayer_template_files() {
+        let dir = temp_project("mp");
+        write_template_extra_files(&dir, "3d", "multiplayer").unwrap();
+        let player_scene = fs::read_to_string(dir.join("scenes/player.tscn")).unwrap();
+        assert!(player_scene.contains("type=\"MultiplayerSynchronizer\""));
+        let lobby = fs::read_to_string(dir.join("src/systems/network_lobby.gd")).unwrap();
+        for signal in ["peer_connected", "peer_disconnected", "connected_to_server", "connection_failed", "server_disconnected"] {
+            assert!(EVENT_BUS_GD.contains(&format!("signal {}", signal)));
+            assert!(lobby.contains(&format!("EventBus.{}.emit(", signal)));
+        }
+
+        let (scene, _) = generate_template_files("3d", "multiplayer");
+        assert!(scene.contains("res://src/systems/network_lobby.gd"));
+        assert!(scene.contains("_spawnable_scenes = PackedStringArray(\"res://scenes/player.tscn\")"));
+
+        // Templates without extra files write nothing
+        write_template_extra_files(&dir, "2d", "platformer").unwrap();
+    }
+
+    #[test]
+    fn test_ui_menu_template() {
+        let (scene, script) = generate_template_files("2d", "ui-menu");
+        let paths: Vec<_> = parse_tscn_nodes(&scene).into_iter().map(|n| n.path).collect();
+        for button in ["Menu/StartButton", "Menu/OptionsButton", "Menu/QuitButton", "OptionsPanel/Options/BackButton"] {
+            assert!(paths.iter().any(|p| p == button), "missing {}", button);
+            assert!(script.contains(&format!("${}", button)));
+        }
+        for signal in ["game_started", "options_requested", "quit_requested"] {
+            assert!(EVENT_BUS_GD.contains(&format!("signal {}\n", signal)));
+            assert!(script.contains(&format!("EventBus.{}.emit()", signal)));
+        }
+        assert!(list_template_meta().iter().any(|t| t.id == "ui-menu" && t.dimensions == ["2d"]));
+    }
+
+    #[test]
+    fn test_update_autoloads_respects_user_edits() {
+        let dir = temp_project("autoloads");
+        fs::create_dir_all(dir.join("autoload")).unwrap();
+        fs::create_dir_all(dir.join(".tav")).unwrap();
+        fs::write(dir.join("project.godot"), "config_version=5\n").unwrap();
+        // event_bus.gd is an older generated version; game_state.gd was edited by the user
+        let old_bus = "extends Node\nsignal game_started\n";
+        fs::write(dir.join("autoload/event_bus.gd"), old_bus).unwrap();
+        fs::write(dir.join("autoload/game_state.gd"), "extends Node\n# mine\n").unwrap();
+        let info = serde_json::json!({"template": "empty", "file_hashes": {
+            "autoload/event_bus.gd": content_hash(old_bus.as_bytes()),
+            "autoload/game_state.gd": content_hash(GAME_STATE_GD.as_bytes()),
+        }});
+        fs::write(dir.join(".tav/template_info.json"), info.to_string()).unwrap();
+        let project = dir.to_string_lossy().to_string();
+
+        let result = update_autoloads(project.clone(), false).unwrap();
+        assert_eq!(result.updated, ["autoload/event_bus.gd", "autoload/ai_controller.gd"]);
+        assert_eq!(result.skipped, ["autoload/game_state.gd"]);
+        assert_eq!(fs::read_to_string(dir.join("autoload/event_bus.gd")).unwrap(), EVENT_BUS_GD);
+        assert!(dir.join(".tav/template_updates/autoload/game_state.gd").exists());
+
+        let result = update_autoloads(project.clone(), true).unwrap();
+        assert_eq!(result.updated, ["autoload/game_state.gd"]);
+        assert_eq!(result.unchanged.len(), 2);
+        assert_eq!(fs::read_to_string(&result.backups[0]).unwrap(), "extends Node\n# mine\n");
+
+        let result = update_autoloads(project, false).unwrap();
+        assert_eq!(result.unchanged.len(), 3);
+    }
+
+    #[test]
+    fn test_add_component_tscn() {
+        let with_movement = add_component_tscn(
+            PLATFORMER_2D_SCENE, "Player", "MovementComponent2D", "Node", "res://src/components/movement_component_2d.gd",
+        );
+        assert!(with_movement.starts_with("[gd_scene load_steps=4 format=3]\n"));
+        assert!(with_movement.contains("[ext_resource type=\"Script\" path=\"res://src/components/movement_component_2d.gd\" id=\"3\"]"));
+        assert!(with_movement.ends_with("[node name=\"MovementComponent2D\" type=\"Node\" parent=\"Player\"]\nscript = ExtResource(\"3\")\n"));
+
+        // An existing ext_resource is reused and the new node gets a free name
+        let nodes = parse_tscn_nodes(&with_movement);
+        assert_eq!(unique_child_name(&nodes, "Player", "HealthComponent"), "HealthComponent2");
+        assert_eq!(unique_child_name(&nodes, ".", "HealthComponent"), "HealthComponent");
+        let with_health = add_component_tscn(&with_movement, "Player", "HealthComponent2", "Node", "res://src/components/health_component.gd");
+        assert!(with_health.starts_with("[gd_scene load_steps=4 format=3]\n"));
+        assert!(with_health.contains("parent=\"Player\"]\nscript = ExtResource(\"2\")"));
+        assert!(parse_tscn_nodes(&with_health).iter().any(|n| n.path == "Player/HealthComponent2"));
+
+        // Nodes go before connections; scenes without resources get their first ext_resource
+        let scene = "[gd_scene format=3]\n\n[node name=\"Main\" type=\"Node2D\"]\n\n[connection signal=\"ready\" from=\".\" to=\".\" method=\"_on_ready\"]\n";
+        let updated = add_component_tscn(scene, ".", "StateMachine", "Node", "res://src/components/state_machine.gd");
+        assert_eq!(updated, "[gd_scene format=3]\n\n[ext_resource type=\"Script\" path=\"res://src/components/state_machine.gd\" id=\"1\"]\n\n[node name=\"Main\" type=\"Node2D\"]\n\n[node name=\"StateMachine\" type=\"Node\" parent=\".\"]\nscript = ExtResource(\"1\")\n\n[connection signal=\"ready\" from=\".\" to=\".\" method=\"_on_ready\"]\n");
+        assert_eq!(find_builtin_component("healthcomponent").unwrap().id, "health_component");
+    }
+
+    #[test]
+    fn test_component_catalog() {
+        let catalog = get_component_catalog();
+        let health = catalog.iter().find(|c| c.name == "HealthComponent").unwrap();
+        assert_eq!((health.kind.as_str(), health.path.as_str(), health.base.as_str()), ("component", "src/components/health_component.gd", "Node"));
+        assert_eq!(health.description, "Reusable health management - attach to any entity");
+        assert_eq!(health.exports[0], ScriptExport {
+            name: "max_health".to_string(), type_name: Some("int".to_string()), default: Some("100".to_string()),
+        });
+        assert_eq!(health.signals, vec![
+            ScriptSignal { name: "health_changed".to_string(), args: vec!["current: int".to_string(), "maximum: int".to_string()] },
+            ScriptSignal { name: "died".to_string(), args: vec![] },
+        ]);
+
+        let bus = catalog.iter().find(|c| c.name == "EventBus").unwrap();
+        assert_eq!(bus.kind, "autoload");
+        assert!(bus.description.starts_with("Central signal hub"));
+        assert!(bus.signals.iter().any(|s| s.name == "player_died"));
+        let rig = catalog.iter().find(|c| c.name == "CameraRig3D").unwrap();
+        assert!(rig.exports.iter().any(|e| e.name == "target_path" && e.default.is_none()));
+    }
+
+    #[test]
+    fn test_asset_base_url_override() {
+        let mut settings = AppSettings::default();
+        assert_eq!(resolve_asset_base_url(None, &settings), R2_BASE_URL);
+        settings.asset_base_url = Some("http://localhost:9000/assets/".to_string());
+        assert_eq!(resolve_asset_base_url(None, &settings), "http://localhost:9000/assets");
+        assert_eq!(resolve_asset_base_url(Some(" ".to_string()), &settings), "http://localhost:9000/assets");
+        settings.asset_base_url = Some("  ".to_string());
+        assert_eq!(resolve_asset_base_url(None, &settings), R2_BASE_URL);
+
+        let env = Some("https://mirror.example.com".to_string());
+        assert_eq!(resolve_asset_base_url(env, &settings), "https://mirror.example.com");
+    }
+
+    #[test]
+    fn test_find_bundled_asset() {
+        let root = temp_project("bundled");
+        fs::create_dir_all(root.join("packs")).unwrap();
+        fs::write(root.join("packs/anims.zip"), "zip").unwrap();
+        let roots = [root.join("missing"), root.to_path_buf()];
+        assert_eq!(find_bundled_asset(&roots, "packs/anims.zip"), Some(root.join("packs/anims.zip")));
+        assert_eq!(find_bundled_asset(&roots, "other.zip"), None);
+        assert_eq!(find_bundled_asset(&roots, "packs"), None);
+        assert_eq!(find_bundled_asset(&[root.join("packs")], "../packs/anims.zip"), None);
+    }
+
+    #[test]
+    fn test_verify_and_repair_animation_setup() {
+        let dir = temp_project("anim");
+        fs::create_dir_all(dir.join("assets/animations/quaternius-ual")).unwrap();
+        fs::create_dir_all(dir.join("scenes")).unwrap();
+        for file in ["Idle.glb", "Walk_F.gltf", "notes.txt", "quaternius-ual/Jog_F.glb"] {
+            fs::write(dir.join("assets/animations").join(file), "").unwrap();
+        }
+        let names = ["Idle", "Walk_F", "Jog_F"].map(String::from);
+        fs::write(dir.join("scenes/locomotion_tree.tscn"), animations::generate_animation_tree_tscn(&names)).unwrap();
+        let project = dir.to_string_lossy().to_string();
+
+        let status = verify_animation_setup(project.clone()).unwrap();
+        assert_eq!(status.available_animations, ["Idle", "Walk_F"]);
+        assert_eq!(status.missing_animations, ["Jog_F"]);
+        assert!(!status.tree_valid);
+
+        let status = repair_animation_setup(project).unwrap();
+        assert!(status.tree_valid && status.missing_animations.is_empty());
+        assert!(dir.join("scripts/locomotion_blend_tree.gd").exists());
+    }
+
+    #[test]
+    fn test_rename_glb_nodes_applies_mixamo_map() {
+        let map = mixamo_bone_map();
+        assert_eq!(map.len(), 22);
+        assert!(map.contains(&("mixamorig:LeftArm".to_string(), "LeftUpperArm".to_string())));
+
+        let json = br#"{"nodes":[{"name":"mixamorig:Hips"},{"name":"mixamorig_LeftArm"},{"name":"Mesh"},{}]}"#;
+        let mut glb = b"glTF".to_vec();
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        glb.extend_from_slice(&((20 + json.len() + 12) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(json);
+        glb.extend_from_slice(&[4, 0, 0, 0, b'B', b'I', b'N', 0, 1, 2, 3, 4]);
+
+        let (out, renamed) = rename_glb_nodes(&glb, &map).unwrap();
+        assert_eq!(renamed, 2);
+        assert_eq!(u32::from_le_bytes(out[8..12].try_into().unwrap()) as usize, out.len());
+        let json_len = u32::from_le_bytes(out[12..16].try_into().unwrap()) as usize;
+        assert_eq!(json_len % 4, 0);
+        let parsed: serde_json::Value = serde_json::from_slice(&out[20..20 + json_len]).unwrap();
+        assert_eq!(parsed["nodes"][0]["name"], "Hips");
+        assert_eq!(parsed["nodes"][1]["name"], "LeftUpperArm");
+        assert_eq!(parsed["nodes"][2]["name"], "Mesh");
+        assert!(out.ends_with(&[1, 2, 3, 4]));
+        assert!(rename_glb_nodes(b"not a glb", &map).is_err());
+    }
+
+    #[test]
+    fn test_get_project_disk_usage() {
+        let dir = temp_project("usage");
+        for (file, size) in [
+            ("player.gd", 10), ("scenes/main.tscn", 20), ("assets/models/hero.glb", 400),
+            ("icon.png", 30), (".godot/imported/hero.scn", 300), (".tav/web/index.wasm", 500),
+            (".git/objects/pack", 50), ("notes.bin", 5),
+        ] {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![0u8; size]).unwrap();
+        }
+
+        let usage = get_project_disk_usage(dir.to_string_lossy().to_string()).unwrap();
+        assert_eq!(usage.total, 1315);
+        assert_eq!(usage.by_category.source, 30);
+        assert_eq!(usage.by_category.assets, 430);
+        assert_eq!(usage.by_category.godot_cache, 300);
+        assert_eq!(usage.by_category.exports, 500);
+        assert_eq!(usage.by_category.other, 55);
+        assert_eq!(usage.ignored, 850);
+        assert_eq!(usage.largest_files[0].path, ".tav/web/index.wasm");
+        assert_eq!(usage.largest_files[1].category, "assets");
+        assert_eq!(usage.largest_files.len(), 8);
+    }
+
+    #[test]
+    fn test_zip_project_round_trip() {
+        let dir = temp_project("backup");
+        let project = dir.join("game");
+        for file in ["project.godot", "scenes/main.tscn", ".godot/imported/x.ctex", ".tav/web/index.html", "node_modules/a.js"] {
+            let path = project.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+
+        // A linked folder must not be followed (this one would loop forever)
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&project, project.join("scenes/loop")).unwrap();
+        // Written inside the project through a non-canonical path; must not zip itself
+        fs::write(project.join("backup.zip"), "stale").unwrap();
+        let inner_zip = project.join("scenes/../backup.zip");
+        assert_eq!(zip_project(&project, &inner_zip, |_, _| {}).unwrap(), 2);
+
+        let zip_path = dir.join("game-backup.zip");
+        let mut progress = vec![];
+        assert_eq!(zip_project(&project, &zip_path, |done, total| progress.push((done, total))).unwrap(), 3);
+        assert_eq!(progress, [(1, 3), (2, 3), (3, 3)]);
+
+        let restored = dir.join("restored");
+        assert_eq!(extract_zip_safe(&zip_path, &restored, |_, _| {}).unwrap(), 3);
+        assert_eq!(fs::read_to_string(restored.join("scenes/main.tscn")).unwrap(), "scenes/main.tscn");
+        assert!(!restored.join(".godot").exists() && !restored.join(".tav").exists());
+    }
+
+    #[test]
+    fn test_parse_git_status() {
+        let files = parse_git_status(" M scenes/main.tscn\0R  new name.gd\0a.g\0?? notes.md\0");
+        assert_eq!(files, [
+            GitFileStatus { path: "scenes/main.tscn".to_string(), status: " M".to_string() },
+            GitFileStatus { path: "new name.gd".to_string(), status: "R ".to_string() },
+            GitFileStatus { path: "notes.md".to_string(), status: "??".to_string() },
+        ]);
+        assert!(parse_git_status("").is_empty());
+    }
+
+    #[test]
+    fn test_git_status_modified_file() {
+        if !detect_git() {
+            return;
+        }
+        let dir = temp_project("git-status");
+        let project = dir.to_string_lossy().to_string();
+        fs::create_dir_all(dir.join("scenes")).unwrap();
+        fs::write(dir.join("scenes/main.tscn"), "[gd_scene]").unwrap();
+        run_git(&project, &["init", "-q"]).unwrap();
+        run_git(&project, &["add", "-A"]).unwrap();
+        run_git(&project, &["-c", "user.name=Test", "-c", "user.email=test@example.com", "commit", "-q", "-m", "init"]).unwrap();
+
+        fs::write(dir.join("scenes/main.tscn"), "[gd_scene format=3]").unwrap();
+        assert_eq!(git_status(project).unwrap(), [
+            GitFileStatus { path: "scenes/main.tscn".to_string(), status: " M".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_record_and_undo_agent_turn() {
+        let dir = temp_project("history");
+        fs::create_dir_all(dir.join("scripts")).unwrap();
+        fs::write(dir.join("scripts/player.gd"), "extends Node").unwrap();
+        fs::write(dir.join("scripts/enemy.gd"), "extends Node2D").unwrap();
+        fs::write(dir.join("keep.gd"), "pass").unwrap();
+
+        let before = hash_project_files(&dir, true);
+        fs::write(dir.join("scripts/player.gd"), "extends CharacterBody2D").unwrap();
+        fs::remove_file(dir.join("scripts/enemy.gd")).unwrap();
+        fs::write(dir.join("new.gd"), "new").unwrap();
+        fs::write(dir.join("keep.gd"), "edited").unwrap();
+
+        let journal = record_agent_turn(&dir, "turn1", &before).unwrap();
+        let summary: Vec<_> = journal.changes.iter().map(|c| (c.path.as_str(), c.change.as_str())).collect();
+        assert_eq!(summary, [("keep.gd", "modified"), ("new.gd", "created"), ("scripts/enemy.gd", "deleted"), ("scripts/player.gd", "modified")]);
+        // Only the pre-turn copies of changed files are kept
+        let objects = fs::read_dir(history_dir(&dir).join("objects")).unwrap().count();
+        assert_eq!(objects, 3);
+
+        // Edited again after the turn, so undo must leave it alone
+        fs::write(dir.join("keep.gd"), "user edit").unwrap();
+        let project = dir.to_string_lossy().to_string();
+        let result = undo_agent_turn(project.clone(), "turn1".to_string()).unwrap();
+        assert_eq!(result.skipped, ["keep.gd"]);
+        assert_eq!(fs::read_to_string(dir.join("scripts/player.gd")).unwrap(), "extends Node");
+        assert_eq!(fs::read_to_string(dir.join("scripts/enemy.gd")).unwrap(), "extends Node2D");
+        assert!(!dir.join("new.gd").exists());
+        assert_eq!(fs::read_to_string(dir.join("keep.gd")).unwrap(), "user edit");
+        assert!(undo_agent_turn(project, "../turn1".to_string()).is_err());
+        let objects = fs::read_dir(history_dir(&dir).join("objects")).unwrap().count();
+        assert_eq!(objects, 1);
+
+        // Files over the size limit are reported instead of journaled
+        let big = dir.join("big.bin");
+        fs::write(&big, vec![0u8; HISTORY_MAX_FILE_BYTES as usize + 1]).unwrap();
+        let before = hash_project_files(&dir, true);
+        fs::write(&big, vec![1u8; HISTORY_MAX_FILE_BYTES as usize + 2]).unwrap();
+        let journal = record_agent_turn(&dir, "turn2", &before).unwrap();
+        assert!(journal.changes.is_empty());
+        assert_eq!(journal.too_large, ["big.bin"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_streamed_timeout() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo started; sleep 5"]);
+        let start = std::time::Instant::now();
+        let err = run_streamed(command, "sh", Some(std::time::Duration::from_millis(300)), |_| {}).unwrap_err();
+        assert!(start.elapsed() < std::time::Duration::from_secs(4));
+        assert!(err.contains("timed out") && err.contains("started"), "{}", err);
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "echo ok"]);
+        assert!(run_streamed(command, "sh", Some(std::time::Duration::from_secs(5)), |_| {}).is_ok());
+    }
+
+    #[test]
+    fn test_create_project_mobile_option() {
+        let dir = temp_project("mobile");
+        let parent = dir.to_string_lossy().to_string();
+        let project = PathBuf::from(create_project_from_template(
+            "Phone".to_string(), parent.clone(), "2d".to_string(), "platformer".to_string(), Some(true),
+        ).unwrap());
+
+        let config = parse_godot_config(&fs::read_to_string(project.join("project.godot")).unwrap());
+        let rendering = config.section("rendering").unwrap();
+        assert_eq!(rendering.get("renderer/rendering_method").as_deref(), Some("\"mobile\""));
+        assert_eq!(web_rendering_method(&config), "gl_compatibility");
+        let scene = fs::read_to_string(project.join("scenes/main.tscn")).unwrap();
+        assert!(parse_tscn_nodes(&scene).iter().any(|n| n.path == "TouchControls"));
+        assert!(scene.contains("[ext_resource type=\"PackedScene\" path=\"res://scenes/ui/touch_controls.tscn\""));
+        assert!(project.join("src/ui/virtual_joystick.gd").exists());
+
+        // Templates without touch controls ignore the option (3D ones need mouse look)
+        let menu = PathBuf::from(create_project_from_template(
+            "Menu".to_string(), parent.clone(), "2d".to_string(), "ui-menu".to_string(), Some(true),
+        ).unwrap());
+        assert!(!menu.join("scenes/ui/touch_controls.tscn").exists());
+        let fps = PathBuf::from(create_project_from_template(
+            "Shooter".to_string(), parent, "3d".to_string(), "first-person".to_string(), Some(true),
+        ).unwrap());
+        assert!(!fps.join("scenes/ui/touch_controls.tscn").exists());
+    }
+
+    #[test]
+    fn test_measure_export() {
+        let dir = temp_project("export-size");
+        fs::create_dir_all(dir.join("assets")).unwrap();
+        fs::write(dir.join("index.html"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("index.pck"), vec![0u8; 4000]).unwrap();
+        fs::write(dir.join("index.wasm"), vec![0u8; 3000]).unwrap();
+        fs::write(dir.join("assets/extra.png"), vec![0u8; 500]).unwrap();
+        fs::write(dir.join(".export_hash"), vec![0u8; 9000]).unwrap();
+
+        let size = measure_export(&dir, 5000);
+        assert_eq!(size.total, 7600);
+        assert!(size.over_threshold);
+        let paths: Vec<_> = size.largest_files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["index.pck", "index.wasm", "assets/extra.png", "index.html"]);
+
+        assert!(!measure_export(&dir, 7600).over_threshold);
+
+    }
+
+    #[test]
+    fn test_parse_godot_version() {
+        assert_eq!(parse_godot_version("4.3.stable.official.77dcf97d8\n").unwrap(), "4.3.stable");
+        assert!(parse_godot_version("Usage: foo").is_err());
+        assert!(require_godot_4("4.3.stable").is_ok());
+        assert!(require_godot_4("3.5.stable").unwrap_err().contains("Godot 4.x is required"));
+        assert!(require_godot_4("v4.x.y").is_err());
+    }
+
+    #[test]
+    fn test_project_godot_pin() {
+        let dir = temp_project("godot-pin");
+        fs::create_dir_all(dir.join(".tav")).unwrap();
+        let binary = dir.join("godot_v4.2");
+        fs::write(&binary, "").unwrap();
+        let saved = Some(binary.to_string_lossy().to_string());
+        assert_eq!(project_godot_cmd(&dir, saved.clone()).unwrap(), saved);
+
+        let pinned = dir.join("godot_v4.3");
+        fs::write(&pinned, "").unwrap();
+        let pin = GodotPin { path: pinned.to_string_lossy().to_string(), version: "4.3.stable".to_string() };
+        fs::write(godot_pin_path(&dir), serde_json::to_string(&pin).unwrap()).unwrap();
+        assert_eq!(read_godot_pin(&dir), Some(pin.clone()));
+        assert_eq!(project_godot_cmd(&dir, saved.clone()).unwrap(), Some(pin.path.clone()));
+        // Templates are checked for the pinned version without running the binary
+        let project = dir.to_string_lossy().to_string();
+        assert_eq!(export_templates_version(Some(&project), &pin.path).unwrap(), "4.3.stable");
+
+        // A pinned version that is no longer installed is an error, not a fallback
+        fs::remove_file(&pinned).unwrap();
+        let missing = GodotPin { version: "0.0.test".to_string(), ..pin };
+        fs::write(godot_pin_path(&dir), serde_json::to_string(&missing).unwrap()).unwrap();
+        assert!(project_godot_cmd(&dir, saved.clone()).unwrap_err().contains("not installed"));
+
+        assert_eq!(pin_godot_version(project.clone(), None).unwrap(), None);
+        assert_eq!(get_godot_pin(project), None);
+        assert_eq!(project_godot_cmd(&dir, saved.clone()).unwrap(), saved);
+    }
+
+    #[test]
+    fn test_input_event_round_trip() {
+        for name in [
+            "Ctrl+S", "Space", "RightClick", "Mouse 8", "Joy A", "Joy Left Stick", "Joy Button 20",
+            "Joy Left Stick Left", "Joy Right Stick Down", "Joy LT", "Joy Axis 9-",
+            "Joy B (Device 2)", "Joy Left Stick Left (Device 1)",
+        ] {
+            let object = input_event_object(name).unwrap_or_else(|| panic!("no event for {}", name));
+            let event = object.strip_prefix("Object(").unwrap();
+            assert_eq!(input_event_name(event).as_deref(), Some(name));
+        }
+        assert!(input_event_object("Joy Left Stick Sideways").is_none());
+        assert!(input_event_object("S (Device 1)").is_none());
+    }
+
+    #[test]
+    fn test_compare_template_versions() {
+        assert_eq!(compare_versions("0.9.0", "1.0.0"), Some(Ordering::Less));
+        assert_eq!(compare_versions("1.0.0", "1.0.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("1.0", "1.0.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("2.0.0-beta", "1.0.0"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("latest", "1.0.0"), None);
+    }
+
+    #[test]
+    fn test_obfuscated_secret_roundtrip() {
+        let stored = obfuscate_secret("sk-or-v1-abc123");
+        assert!(stored.starts_with(OBFUSCATED_PREFIX));
+        assert!(!stored.contains("abc123"));
+        assert_eq!(deobfuscate_secret(&stored), Some("sk-or-v1-abc123".to_string()));
+        assert_eq!(deobfuscate_secret("sk-plaintext"), None);
+    }
+
+    #[test]
+    fn test_merge_settings_preserves_unset_fields() {
+        let existing = AppSettings {
+            openrouter_key: Some("sk-old".to_string()),
+            godot_path: Some("/old/godot".to_string()),
+            godot_mcp_installed: Some(true),
+            auto_connect: Some(false),
+            last_project_path: Some("/projects/game".to_string()),
+            env_path: Some("/projects/.env".to_string()),
+            ..Default::default()
+        };
+        let partial = AppSettings {
+            godot_path: Some("/new/godot".to_string()),
+            ..Default::default()
+        };
+
+        let merged = merge_settings(&existing, &partial);
+        assert_eq!(merged.godot_path.as_deref(), Some("/new/godot"));
+        assert_eq!(merged.openrouter_key.as_deref(), Some("sk-old"));
+        assert_eq!(merged.godot_mcp_installed, Some(true));
+        assert_eq!(merged.auto_connect, Some(false));
+        assert_eq!(merged.last_project_path.as_deref(), Some("/projects/game"));
+        assert_eq!(merged.env_path.as_deref(), Some("/projects/.env"));
+    }
+
+    #[test]
+    fn test_parse_key_expiry() {
+        let now = 1_700_000_000;
+        assert_eq!(parse_key_expiry(&serde_json::json!({"key": "k"}), now), None);
+        assert_eq!(parse_key_expiry(&serde_json::json!({"expires_at": 1_800_000_000}), now), Some(1_800_000_000));
+        assert_eq!(parse_key_expiry(&serde_json::json!({"expires_at": 1_800_000_000_000i64}), now), Some(1_800_000_000));
+        assert_eq!(parse_key_expiry(&serde_json::json!({"expires_in": "3600"}), now), Some(now + 3600));
+        assert_eq!(parse_key_expiry(&serde_json::json!({"expires_at": "2023-11-14T22:13:20Z"}), now), Some(1_700_000_000));
+        assert_eq!(parse_key_expiry(&serde_json::json!({"expires_at": "2023-11-15T00:13:20.500+02:00"}), now), Some(1_700_000_000));
+        assert_eq!(parse_rfc3339("2000-03-01 00:00:00-01:30"), Some(951_868_800 + 5400));
+        assert_eq!(parse_rfc3339("next tuesday"), None);
+    }
+
+    #[test]
+    fn test_goose_parser_stream_json() {
+        let sample = [
+            r#"{"type":"message","message":{"role":"user","content":[{"type":"text","text":"add a jump"}]}}"#,
+            r#"{"type":"message","message":{"role":"assistant","content":[{"type":"text","text":"I'll read the player script."},{"type":"toolRequest","id":"t1","toolCall":{"status":"success","value":{"name":"developer__text_editor","arguments":{"command":"view","path":"player.gd"}}}}]}}"#,
+            r#"{"type":"message","message":{"role":"user","content":[{"type":"toolResponse","id":"t1","toolResult":{"status":"success","value":[{"type":"text","text":"extends CharacterBody3D"}]}}]}}"#,
+            r#"{"type":"message","message":{"role":"user","content":[{"type":"toolResponse","id":"t2","toolResult":{"status":"error","error":"file not found"}}]}}"#,
+            r#"{"type":"message","message":{"role":"assistant","content":[{"type":"text","text":"Done."}]}}"#,
+            r#"{"type":"complete","total_tokens":1200}"#,
+        ];
+        let mut parser = GooseOutputParser::default();
+        let events: Vec<AgentEvent> = sample.iter().flat_map(|line| parser.feed(line)).collect();
+
+        let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, ["output", "tool_start", "tool_end", "error", "output"]);
+        assert_eq!(events[1].tool_name.as_deref(), Some("developer__text_editor"));
+        assert_eq!(events[1].tool_args.as_deref(), Some(r#"{"command":"view","path":"player.gd"}"#));
+        assert_eq!(events[2].content, "extends CharacterBody3D");
+        assert_eq!(events[3].content, "file not found");
+        assert_eq!(parser.text, "I'll read the player script.\nDone.");
+    }
+
+    #[test]
+    fn test_goose_parser_text_output() {
+        let sample = "\
+Reading the project before making changes.
+─── shell | developer ──────────────────────────
+command: ls scenes
+
+main.tscn
+─── text_editor | developer ──────────────────────────
+path: player.gd
+command: view
+
+Updated the jump height.";
+        let mut parser = GooseOutputParser::default();
+        let mut events: Vec<AgentEvent> = sample.lines().flat_map(|line| parser.feed(line)).collect();
+        events.extend(parser.finish());
+
+        let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, ["output", "tool_start", "tool_end", "output", "tool_start", "tool_end", "output"]);
+        assert_eq!(events[1].tool_name.as_deref(), Some("shell"));
+        assert_eq!(events[1].tool_args.as_deref(), Some(r#"{"command":"ls scenes"}"#));
+        assert_eq!(events[4].tool_name.as_deref(), Some("text_editor"));
+        assert!(parser.text.contains("Reading the project"));
+        assert!(!parser.text.contains("command:"));
+    }
+
+    #[test]
+    fn test_parse_beads_tasks() {
+        let list = r#"[
+            {"id": "bd-1", "title": "Add double jump", "status": "open", "priority": 1},
+            {"id": "bd-2", "title": "Fix camera clip", "status": "in_progress", "priority": "P0"},
+            {"id": "bd-3", "title": "Old task", "status": "closed"}
+        ]"#;
+        let ready = r#"[{"id": "bd-1", "title": "Add double jump"}]"#;
+
+        let tasks = parse_beads_tasks(list, ready).unwrap();
+        assert_eq!(tasks.len(), 3);
+        assert!(tasks[0].ready);
+        assert_eq!(tasks[0].priority, Some(1));
+        assert!(!tasks[1].ready);
+        assert_eq!(tasks[1].priority, Some(0));
+        assert_eq!(tasks[2].status, "closed");
+        assert_eq!(tasks[2].priority, None);
+        assert!(parse_beads_tasks("not json", "").is_err());
+    }
+
+    #[test]
+    fn test_agent_event_batch_merges_output() {
+        let mut batch = AgentEventBatch::default();
+        assert!(batch.push(agent_event("output", "a\n".to_string(), None, None)).is_empty());
+        assert!(batch.push(agent_event("output", "b\n".to_string(), None, None)).is_empty());
+
+        let ready = batch.push(agent_event("tool_start", "ls".to_string(), Some("shell".to_string()), None));
+        assert_eq!(ready.len(), 2);
+        assert_eq!(ready[0].content, "a\nb\n");
+        assert_eq!(ready[1].event_type, "tool_start");
+
+        assert!(batch.push(agent_event("output", "c\n".to_string(), None, None)).is_empty());
+        assert_eq!(batch.flush().map(|e| e.content), Some("c\n".to_string()));
+        assert!(batch.flush().is_none());
+    }
+
+    #[test]
+    fn test_parse_openrouter_models_filters_chat_models() {
+        let data = serde_json::json!({"data": [
+            {"id": "z/chat", "name": "Zeta Chat", "context_length": 128000,
+             "architecture": {"input_modalities": ["text", "image"], "output_modalities": ["text"]},
+             "pricing": {"prompt": "0.000003", "completion": "0.000015"}},
+            {"id": "a/image-gen", "name": "Alpha Image",
+             "architecture": {"input_modalities": ["text"], "output_modalities": ["image"]}},
+            {"id": "b/legacy", "name": "beta legacy", "architecture": {"modality": "text->text"}}
+        ]});
+
+        let models = parse_openrouter_models(&data);
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["b/legacy", "z/chat"]);
+        assert_eq!(models[1].context_length, Some(128000));
+        assert_eq!(models[1].pricing.completion, "0.000015");
+        assert_eq!(models[0].pricing.prompt, "0");
+    }
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(extract_json("```json\n{\"a\": 1}\n```"), "{\"a\": 1}");
+        assert_eq!(extract_json("Here is the plan: {\"actions\": []} Good luck!"), "{\"actions\": []}");
+        assert_eq!(extract_json("  [1, 2]  "), "[1, 2]");
+        assert_eq!(extract_json("no json here"), "no json here");
+    }
+
+    #[test]
+    fn test_log_tail_reads_incrementally() {
+        let dir = temp_project("log");
+        let path = dir.join("log.log");
+        fs::write(&path, "[INFO] [F1] start\n[ERROR] [F2] no player\n[INFO] [F3] par").unwrap();
+
+        let mut tail = LogTail::default();
+        tail.read_new(&path).unwrap();
+        assert_eq!(tail.next_line(), 2);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, b"tial\n[WARN] [F4] slow\n").unwrap();
+        tail.read_new(&path).unwrap();
+        assert_eq!(tail.next_line(), 4);
+
+        let errors = tail.query(0, Some("error"), 10);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 1);
+
+        let newer = tail.query(2, None, 10);
+        assert_eq!(newer.iter().map(|l| l.text.as_str()).collect::<Vec<_>>(), ["[INFO] [F3] partial", "[WARN] [F4] slow"]);
+        assert_eq!(tail.query(0, None, 1)[0].line, 3);
+
+    }
+
+    #[test]
+    fn test_keycode_name_round_trip() {
+        for name in ["A", "Z", "0", "9", "Space", "-", "/", "[", "`", "Escape", "Enter", "Shift",
+                     "Left", "Down", "PageUp", "F1", "F12", "Kp 0", "Kp 9", "Kp +", "Menu"] {
+            let code = name_to_keycode(name).unwrap_or_else(|| panic!("no keycode for {}", name));
+            assert_eq!(keycode_to_name(code).as_deref(), Some(name), "round trip for {}", name);
+        }
+        assert_eq!(name_to_keycode("Left"), Some(4194319));
+        assert_eq!(name_to_keycode("f5"), Some(4194336));
+        assert_eq!(keycode_to_name(16777238).as_deref(), Some("Ctrl")); // Godot 3
+        assert_eq!(keycode_to_name(97), None);
+        assert_eq!(name_to_keycode("F40"), None);
+    }
+
+    #[test]
+    fn test_parse_keys_from_block_modifiers_and_joypad() {
+        let block = r#"={"deadzone": 0.5, "events": [Object(InputEventKey,"resource_local_to_scene":false,"device":-1,"alt_pressed":false,"shift_pressed":false,"ctrl_pressed":true,"meta_pressed":false,"pressed":false,"keycode":0,"physical_keycode":83,"key_label":0,"unicode":115,"echo":false,"script":null), Object(InputEventJoypadButton,"resource_local_to_scene":false,"device":-1,"button_index":0,"pressure":0.0,"pressed":true,"script":null), Object(InputEventJoypadMotion,"resource_local_to_scene":false,"device":1,"axis":0,"axis_value":-1.0,"script":null), Object(InputEventMouseButton,"device":-1,"button_index":2,"pressed":true,"script":null)]}"#;
+        assert_eq!(parse_keys_from_block(block), ["Ctrl+S", "Joy A", "Joy Left Stick Left (Device 1)", "RightClick"]);
+
+        let shift_only = r#"Object(InputEventKey,"shift_pressed":true,"ctrl_pressed":false,"physical_keycode":4194325,"script":null)"#;
+        assert_eq!(parse_keys_from_block(shift_only), ["Shift"]);
+    }
+
+    #[test]
+    fn test_upsert_input_action() {
+        let content = "[application]\n\nconfig/name=\"Game\"\n\n[input]\n\njump={\n\"deadzone\": 0.5,\n\"events\": []\n}\n\n[rendering]\n\nrenderer=\"x\"\n";
+
+        let block = input_action_block("jump", &["Space".to_string(), "Joy A".to_string()]).unwrap();
+        let replaced = upsert_input_action(content, "jump", &block);
+        assert_eq!(replaced.matches("jump={").count(), 1);
+        assert!(replaced.contains("[rendering]\n\nrenderer=\"x\""));
+        assert_eq!(parse_keys_from_block(&block), ["Space", "Joy A"]);
+
+        let block = input_action_block("save", &["Ctrl+S".to_string()]).unwrap();
+        let added = upsert_input_action(&replaced, "save", &block);
+        let input_section = &added[added.find("[input]").unwrap()..added.find("[rendering]").unwrap()];
+        assert!(input_section.contains("jump={") && input_section.contains("save={"));
+        assert_eq!(parse_keys_from_block(&block), ["Ctrl+S"]);
+
+        let no_input = upsert_input_action("[application]\n\n[rendering]\n", "save", &block);
+        assert!(no_input.find("[input]").unwrap() < no_input.find("[rendering]").unwrap());
+        assert!(input_action_block("x", &["NotAKey".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_add_autoload_with_existing_section() {
+        let content = "config_version=5\n\n[application]\n\nconfig/name=\"Game\"\n; [autoload] mentioned in a comment\n\n[autoload]\n\nEventBus=\"*res://autoload/event_bus.gd\"\n\n[input]\n\njump={\n\"deadzone\": 0.5,\n\"events\": []\n}\n";
+
+        let updated = add_autoload(content, "KoboldBridge", "res://.tav/kobold_bridge.gd");
+        let config = parse_godot_config(&updated);
+        let autoload = config.section("autoload").unwrap();
+        assert_eq!(autoload.get("EventBus").as_deref(), Some("\"*res://autoload/event_bus.gd\""));
+        assert_eq!(autoload.get("KoboldBridge").as_deref(), Some("\"*res://.tav/kobold_bridge.gd\""));
+        assert_eq!(config.section("input").unwrap().get("jump").as_deref(), Some("{\n\"deadzone\": 0.5,\n\"events\": []\n}"));
+        assert_eq!(updated.matches("[autoload]").count(), 2); // header plus the untouched comment
+
+        // Injecting again is a no-op
+        assert_eq!(add_autoload(&updated, "KoboldBridge", "res://.tav/kobold_bridge.gd"), updated);
+    }
+
+    #[test]
+    fn test_add_autoload_without_section() {
+        let content = "config_version=5\n\n[application]\n\nconfig/name=\"Game\"\n\n[input]\n\njump={\n\"events\": [Object(InputEventKey,\"physical_keycode\":32,\"script\":null)]\n}\n";
+
+        let updated = add_autoload(content, "KoboldBridge", "res://.tav/kobold_bridge.gd");
+        let autoload_at = updated.find("[autoload]").unwrap();
+        assert!(autoload_at > updated.find("[application]").unwrap());
+        assert!(autoload_at < updated.find("[input]").unwrap());
+        assert!(updated.contains("[autoload]\n\nKoboldBridge=\"*res://.tav/kobold_bridge.gd\"\n\n[input]"));
+        assert!(updated.ends_with("jump={\n\"events\": [Object(InputEventKey,\"physical_keycode\":32,\"script\":null)]\n}\n"));
+    }
+
+    #[test]
+    fn test_remove_autoload_reverses_add() {
+        let content = "config_version=5\n\n[autoload]\n\nEventBus=\"*res://autoload/event_bus.gd\"\n\n[input]\n\njump={\n\"events\": []\n}\n";
+
+        let injected = add_autoload(content, "KoboldBridge", "res://.tav/kobold_bridge.gd");
+        assert_eq!(remove_autoload(&injected, "KoboldBridge"), content);
+        assert_eq!(remove_autoload(content, "KoboldBridge"), content);
+    }
+
+    #[test]
+    fn test_inject_helper_html_replaces_old_versions() {
+        let html = "<html><head>\n<title>Game</title>\n</head><body></body></html>";
+        let script = "<script>\n// Kobold Bridge Helper\n</script>";
+
+        let injected = inject_helper_html(html, script).unwrap();
+        assert!(injected.contains(&js_helper_start_marker()));
+        assert!(inject_helper_html(&injected, script).is_none());
+
+        // A helper from an older bridge version gets swapped out, not duplicated
+        let stale = injected.replace(&js_helper_start_marker(), "<!-- kobold-helper v1 -->");
+        let updated = inject_helper_html(&stale, script).unwrap();
+        assert_eq!(updated, injected);
+
+        // Unmarked helpers from before versioning are replaced too
+        let legacy = html.replace("</head>", &format!("\n{}\n</head>", script));
+        let updated = inject_helper_html(&legacy, script).unwrap();
+        assert_eq!(updated.matches("Kobold Bridge Helper").count(), 1);
+        assert!(updated.contains(&js_helper_start_marker()));
+    }
+
+    #[test]
+    fn test_export_guard_blocks_concurrent_exports() {
+        let exporting = Mutex::new(std::collections::HashSet::new());
+        let project = temp_project("export-guard");
+        let guard = ExportGuard::acquire(&exporting, &project).unwrap();
+        assert!(ExportGuard::acquire(&exporting, &project).is_err());
+        assert!(ExportGuard::acquire(&exporting, &project.join("other-project")).is_ok());
+        drop(guard);
+        assert!(ExportGuard::acquire(&exporting, &project).is_ok());
+        assert!(exporting.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_export_presets() {
+        let cfg = format!("{}\n[preset.1]\n\nname=\"Windows Desktop\"\nplatform=\"Windows Desktop\"\nrunnable=false\n\n[preset.1.options]\n\nname=\"ignored\"\n", WEB_EXPORT_PRESET);
+        let presets = parse_export_presets(&cfg);
+        assert_eq!(presets, vec![
+            ExportPreset { name: "Web".to_string(), platform: "Web".to_string(), runnable: true },
+            ExportPreset { name: "Windows Desktop".to_string(), platform: "Windows Desktop".to_string(), runnable: false },
+        ]);
+        assert!(parse_export_presets("").is_empty());
+    }
+
+    #[test]
+    fn test_zip_web_build_strips_helper_and_hash() {
+        let dir = temp_project("package");
+        let build = dir.join("release");
+        fs::create_dir_all(&build).unwrap();
+        let html = "<html><head>\n</head><body></body></html>";
+        let injected = inject_helper_html(html, "<script>\n// Kobold Bridge Helper\n</script>").unwrap();
+        assert_eq!(strip_helper_html(&injected), html);
+
+        fs::write(build.join("index.html"), html).unwrap();
+        fs::write(build.join("index.pck"), [0u8, 1, 2]).unwrap();
+        fs::write(build.join(".export_hash"), "42").unwrap();
+        fs::write(build.join("index.wasm.tmp"), "partial").unwrap();
+
+        let zip_path = dir.join("game.zip");
+        let size = zip_web_build(&build, &zip_path).unwrap();
+        assert_eq!(size, fs::metadata(&zip_path).unwrap().len());
+        let archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["index.html", "index.pck"]);
+
+        let staging = dir.join("publish");
+        fs::create_dir_all(build.join("assets")).unwrap();
+        fs::write(build.join("assets/icon.png"), "png").unwrap();
+        stage_web_build(&build, &staging).unwrap();
+        assert!(staging.join("index.pck").exists());
+        assert!(staging.join("assets/icon.png").exists());
+        assert!(!staging.join(".export_hash").exists());
+        assert!(!staging.join("index.wasm.tmp").exists());
+    }
+
+    #[test]
+    fn test_game_session_is_running() {
+        let child = if cfg!(windows) {
+            Command::new("cmd").args(["/C", "exit 0"]).spawn()
+        } else {
+            Command::new("true").spawn()
+        };
+        let mut session = GameSession {
+            id: "test".to_string(),
+            process: Some(child.unwrap()),
+            project_path: String::new(),
+            scene_path: String::new(),
+            frame_count: 0,
+            log_tail: LogTail::default(),
+            started_at: std::time::Instant::now(),
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while session.is_running() && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(!session.is_running());
+
+        session.process = None;
+        assert!(!session.is_running());
+    }
+
+    #[test]
+    fn test_window_args() {
+        assert_eq!(window_resolution_arg(None).unwrap(), "768x768");
+        assert_eq!(window_resolution_arg(Some("1280x720")).unwrap(), "1280x720");
+        assert!(window_resolution_arg(Some("1280")).is_err());
+        assert!(window_resolution_arg(Some("0x720")).is_err());
+
+        assert_eq!(window_position_arg(Some("")).unwrap(), "0,0");
+        assert_eq!(window_position_arg(Some("-1920, 40")).unwrap(), "-1920,40");
+        assert!(window_position_arg(Some("left")).is_err());
+    }
+
+    #[test]
+    fn test_capture_interval_env() {
+        assert_eq!(capture_interval_env(None), "10");
+        assert_eq!(capture_interval_env(Some(3)), "3");
+        // Zero would make the controller's modulo divide by zero
+        assert_eq!(capture_interval_env(Some(0)), "1");
+    }
+
+    #[test]
+    fn test_frame_format_defaults_to_png() {
+        assert_eq!(FrameFormat::parse(None, Some(50)).unwrap(), FrameFormat::Png);
+        assert_eq!(FrameFormat::parse(Some("JPG"), None).unwrap(), FrameFormat::Jpeg { quality: DEFAULT_JPEG_QUALITY });
+        assert_eq!(FrameFormat::parse(Some("jpeg"), Some(0)).unwrap(), FrameFormat::Jpeg { quality: 1 });
+        assert_eq!(FrameFormat::parse(Some("jpeg"), Some(255)).unwrap().mime_type(), "image/jpeg");
+        assert!(FrameFormat::parse(Some("webp"), None).is_err());
+
+        // Small PNG frames are passed through untouched
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&640u32.to_be_bytes());
+        png.extend_from_slice(&480u32.to_be_bytes());
+        let png_b64 = base64::engine::general_purpose::STANDARD.encode(&png);
+        let (mime, data) = prepare_frame(png_b64.clone(), FrameFormat::Png, DEFAULT_FRAME_MAX_SIZE).unwrap();
+        assert_eq!((mime, data), ("image/png", png_b64));
+        assert!(prepare_frame("not base64!".to_string(), FrameFormat::Png, 768).is_err());
+    }
+
+    #[test]
+    fn test_diff_rgba() {
+        let before = [10, 10, 10, 255, 200, 0, 0, 255, 0, 0, 0, 255, 50, 50, 50, 255];
+        let after = [12, 8, 10, 255, 0, 200, 0, 255, 0, 0, 0, 255, 50, 50, 90, 255];
+        let (changed, diff) = diff_rgba(&before, &after, 16);
+        assert_eq!(changed, 2);
+        assert_eq!(diff.len(), before.len());
+        assert_eq!(&diff[4..8], &[255, 0, 0, 255]);
+        assert_eq!(&diff[12..16], &[255, 0, 0, 255]);
+        assert_eq!(&diff[8..12], &[0, 0, 0, 255]);
+        assert_eq!(diff_rgba(&before, &before, 0).0, 0);
+    }
+
+    #[test]
+    fn test_parse_godot_errors() {
+        let output: Vec<String> = [
+            "Godot Engine v4.3.stable.official - https://godotengine.org",
+            "ERROR: Condition \"!is_inside_tree()\" is true.",
+            "   at: get_global_transform (scene/3d/node_3d.cpp:345)",
+            "SCRIPT ERROR: Invalid call. Nonexistent function 'jump' in base 'Nil'.",
+            "          at: _physics_process (res://scenes/player.gd:12)",
+            "SCRIPT ERROR: Assertion failed: health must be positive",
+            "   at: _ready (res://scenes/main.gd:4)",
+            "ERROR: Failed to load resource 'res://assets/missing.png'.",
+            "WARNING: something harmless",
+        ].iter().map(|s| s.to_string()).collect();
+        assert_eq!(parse_godot_errors(&output), vec![
+            "SCRIPT ERROR: Invalid call. Nonexistent function 'jump' in base 'Nil'. (at _physics_process (res://scenes/player.gd:12))",
+            "SCRIPT ERROR: Assertion failed: health must be positive (at _ready (res://scenes/main.gd:4))",
+            "ERROR: Failed to load resource 'res://assets/missing.png'.",
+        ]);
+    }
+
+    #[test]
+    fn test_fit_frame_size() {
+        assert_eq!(fit_frame_size(1920, 1080, 768), Some((768, 432)));
+        assert_eq!(fit_frame_size(1080, 1920, 768), Some((432, 768)));
+        assert_eq!(fit_frame_size(768, 768, 768), None);
+        assert_eq!(fit_frame_size(640, 480, 768), None);
+        assert_eq!(fit_frame_size(4000, 1, 768), Some((768, 1)));
+        assert_eq!(fit_frame_size(4000, 3000, 0), None);
+    }
+
+    #[test]
+    fn test_parse_butler_line() {
+        let progress = parse_butler_line(r#"{"type":"progress","progress":0.25,"eta":12,"bps":1024}"#).unwrap();
+        assert_eq!(progress.percent, Some(25.0));
+        assert!(!progress.error);
+
+        let log = parse_butler_line(r#"{"type":"log","level":"info","message":"Patch applied"}"#).unwrap();
+        assert_eq!(log.message.as_deref(), Some("Patch applied"));
+
+        let error = parse_butler_line(r#"{"type":"error","message":"invalid game"}"#).unwrap();
+        assert!(error.error);
+        assert_eq!(error.message.as_deref(), Some("invalid game"));
+
+        assert!(parse_butler_line("∙ Pushing 4 MiB").is_none());
+        assert!(parse_butler_line(r#"{"type":"result","value":{}}"#).is_none());
+    }
+
+    #[test]
+    fn test_merge_frame_analyses() {
+        assert_eq!(merge_frame_analyses(&[(1, 3, "All good".to_string())]), "All good");
+
+        let merged = merge_frame_analyses(&[
+            (1, 6, "Intro pans left\n".to_string()),
+            (7, 8, "Door clips".to_string()),
+        ]);
+        assert_eq!(merged, "## Frames 1-6\n\nIntro pans left\n\n## Frames 7-8\n\nDoor clips");
+    }
+
+    #[test]
+    fn test_parse_frame_detections() {
+        let reply = "```json\n{\"objects\": [{\"label\": \"player\", \"bbox\": [120, 400, 80, 200], \"confidence\": 0.92}], \"summary\": \"Player on a platform\"}\n```";
+        let detections = parse_frame_detections(reply).unwrap();
+        assert_eq!(detections.objects.len(), 1);
+        assert_eq!(detections.objects[0].label, "player");
+        assert_eq!(detections.objects[0].bbox, [120.0, 400.0, 80.0, 200.0]);
+        assert_eq!(detections.summary, "Player on a platform");
+
+        let bare = parse_frame_detections("[{\"label\": \"coin\", \"bbox\": [1, 2, 3, 4]}]").unwrap();
+        assert_eq!(bare.objects[0].confidence, 0.0);
+        assert!(parse_frame_detections("no objects here").is_err());
+    }
+
+    #[test]
+    fn test_gemini_response_text() {
+        let multi = serde_json::json!({"candidates": [{"content": {"parts": [
+            {"text": "thinking...", "thought": true},
+            {"text": "Hello, "},
+            {"text": "world"}
+        ]}, "finishReason": "STOP"}]});
+        assert_eq!(gemini_response_text(&multi).unwrap(), "Hello, world");
+
+        let safety = serde_json::json!({"candidates": [{"finishReason": "SAFETY"}]});
+        assert!(gemini_response_text(&safety).unwrap_err().contains("SAFETY"));
+
+        let blocked = serde_json::json!({"promptFeedback": {"blockReason": "OTHER"}});
+        assert!(gemini_response_text(&blocked).unwrap_err().contains("OTHER"));
+    }
+
+    #[test]
+    fn test_tool_command_construction() {
+        assert_eq!(
+            tool_command(true, "npm", &["list", "-g", "godot-mcp"]),
+            ("cmd".to_string(), vec!["/C".into(), "npm".into(), "list".into(), "-g".into(), "godot-mcp".into()])
+        );
+        assert_eq!(
+            tool_command(false, "npm", &["list", "-g"]),
+            ("npm".to_string(), vec!["list".into(), "-g".into()])
+        );
+        assert_eq!(tool_lookup_command(true, "bd"), ("cmd".to_string(), vec!["/C".into(), "where".into(), "bd".into()]));
+        assert_eq!(tool_lookup_command(false, "bd"), ("which".to_string(), vec!["bd".into()]));
+    }
+
+    #[test]
+    fn test_merge_goose_extensions_is_additive() {
+        let mut profiles: serde_yaml::Value = serde_yaml::from_str(
+            "default:\n  provider: openrouter\n  extensions:\n    godot:\n      type: stdio\n      cmd: node\n      args: [/opt/godot-mcp/index.js]\n    developer:\n      type: builtin\n",
+        ).unwrap();
+
+        let summary = merge_goose_extensions(&mut profiles).unwrap();
+        assert_eq!(summary.added, vec!["beads"]);
+        assert_eq!(summary.untouched, vec!["godot"]);
+
+        let extensions = &profiles["default"]["extensions"];
+        assert_eq!(extensions["godot"]["cmd"].as_str(), Some("node"));
+        assert_eq!(extensions["developer"]["type"].as_str(), Some("builtin"));
+        assert_eq!(extensions["beads"]["cmd"].as_str(), Some("beads-mcp"));
+        assert_eq!(profiles["default"]["provider"].as_str(), Some("openrouter"));
+
+        let mut empty = serde_yaml::Value::Null;
+        assert_eq!(merge_goose_extensions(&mut empty).unwrap().added, vec!["godot", "beads"]);
+        assert_eq!(empty["default"]["extensions"]["godot"]["args"][1].as_str(), Some("godot-mcp"));
+    }
+
+    #[test]
+    fn test_mcp_initialize_handshake() {
+        let request: serde_json::Value = serde_json::from_str(&mcp_initialize_request()).unwrap();
+        assert_eq!(request["method"], "initialize");
+
+        assert!(is_mcp_initialize_response(r#"{"jsonrpc":"2.0","id":1,"result":{"serverInfo":{"name":"godot-mcp"}}}"#));
+        assert!(!is_mcp_initialize_response(r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32600}}"#));
+        assert!(!is_mcp_initialize_response("Godot MCP server starting..."));
+    }
+
+    #[test]
+    fn test_find_character_model() {
+        let dir = temp_project("character");
+        fs::create_dir_all(dir.join("Quaternius/glTF")).unwrap();
+        assert_eq!(find_character_model(&dir), None);
+
+        fs::write(dir.join("Quaternius/glTF/Character.gltf"), "{}").unwrap();
+        assert_eq!(find_character_model(&dir), Some(dir.join("Quaternius/glTF/Character.gltf")));
+
+        fs::write(dir.join("Quaternius/Character.glb"), "glTF").unwrap();
+        assert_eq!(find_character_model(&dir), Some(dir.join("Quaternius/Character.glb")));
+
+        fs::write(dir.join("character.glb"), "glTF").unwrap();
+        assert_eq!(find_character_model(&dir), Some(dir.join("character.glb")));
+    }
+
+    #[test]
+    fn test_validate_project_files() {
+        let dir = temp_project("validate");
+        assert_eq!(validate_project_files(&dir)[0].message, "project.godot not found");
+
+        fs::create_dir_all(dir.join("scenes")).unwrap();
+        fs::create_dir_all(dir.join("autoload")).unwrap();
+        fs::write(dir.join("autoload/event_bus.gd"), "").unwrap();
+        fs::write(dir.join("project.godot"), "config_version=5\n\n[application]\n\nrun/main_scene=\"res://scenes/main.tscn\"\n\n[autoload]\n\nEventBus=\"*res://autoload/event_bus.gd\"\nGameState=\"*res://autoload/game_state.gd\"\n").unwrap();
+        fs::write(dir.join("scenes/main.tscn"), "[gd_scene load_steps=2 format=3]\n\n[ext_resource type=\"Script\" path=\"res://scenes/player.gd\" id=\"1\"]\n[ext_resource type=\"Texture2D\" uid=\"uid://abc\" path=\"res://icon.svg\" id=\"2\"]\n").unwrap();
+        fs::write(dir.join("icon.svg"), "").unwrap();
+
+        let messages: Vec<String> = validate_project_files(&dir).into_iter()
+            .map(|issue| format!("{}: {}", issue.severity, issue.message))
+            .collect();
+        assert_eq!(messages, vec![
+            "error: Missing resource res://scenes/player.gd",
+            "warning: Autoload AIController is not registered",
+            "error: Autoload GameState points to missing res://autoload/game_state.gd",
+        ]);
+    }
+
+    #[test]
+    fn test_analyze_unused_assets() {
+        let dir = temp_project("unused");
+        for sub in ["scenes", "autoload", "assets/models", "assets/sfx", "assets/old"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        fs::write(dir.join("project.godot"), "[application]\n\nrun/main_scene=\"res://scenes/main.tscn\"\n\n[autoload]\n\nGameState=\"*res://autoload/game_state.gd\"\n").unwrap();
+        fs::write(dir.join("scenes/main.tscn"), "[ext_resource type=\"PackedScene\" path=\"res://assets/models/level.gltf\" id=\"1\"]\n").unwrap();
+        fs::write(dir.join("autoload/game_state.gd"), "const JUMP = preload(\"res://assets/sfx/jump.wav\")\nvar music_dir = \"res://assets/music/\"\n").unwrap();
+        fs::write(dir.join("assets/models/level.gltf"), r#"{"images":[{"uri":"level%20tex.png"}],"buffers":[{"uri":"level.bin"}]}"#).unwrap();
+        fs::write(dir.join("assets/models/level tex.png"), "png").unwrap();
+        fs::write(dir.join("assets/models/level.bin"), "bin").unwrap();
+        fs::write(dir.join("assets/sfx/jump.wav"), "wav").unwrap();
+        fs::write(dir.join("assets/sfx/jump.wav.import"), "import").unwrap();
+        fs::create_dir_all(dir.join("assets/music")).unwrap();
+        fs::write(dir.join("assets/music/theme.ogg"), "ogg").unwrap();
+        fs::write(dir.join("assets/old/unused_big.png"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("assets/sfx/unused.wav"), "wav").unwrap();
+
+        let project = dir.to_string_lossy().to_string();
+        let unused: Vec<(String, u64)> = analyze_unused_assets(project).unwrap().into_iter()
+            .map(|a| (Path::new(&a.path).strip_prefix(&dir).unwrap().to_string_lossy().replace('\\', "/"), a.size))
+            .collect();
+        assert_eq!(unused, vec![("assets/old/unused_big.png".to_string(), 100), ("assets/sfx/unused.wav".to_string(), 3)]);
+
+        assert!(analyze_unused_assets(dir.join("assets").to_string_lossy().to_string()).is_err());
+    }
+
+    #[test]
+    fn test_web_rendering_method() {
+        let dir = temp_project("renderer");
+        fs::write(dir.join("project.godot"), generate_project_godot("Game", "3d")).unwrap();
+        // forward_plus on desktop still exports with Compatibility
+        assert!(web_renderer_issue(&dir).is_none());
+        let content = fs::read_to_string(dir.join("project.godot")).unwrap();
+        let mut config = parse_godot_config(&content);
+        config.section_or_insert("rendering", &[]).set("renderer/rendering_method.web", "\"forward_plus\"");
+        fs::write(dir.join("project.godot"), write_godot_config(&config)).unwrap();
+        assert!(web_renderer_issue(&dir).is_some());
+
+        let project = dir.to_string_lossy().to_string();
+        assert!(set_rendering_method(project.clone(), "vulkan".to_string()).is_err());
+        set_rendering_method(project, "gl_compatibility".to_string()).unwrap();
+        assert!(web_renderer_issue(&dir).is_none());
+
+        let config = parse_godot_config("[rendering]\n\nrenderer/rendering_method=\"gl_compatibility\"\nrenderer/rendering_method.web=\"mobile\"\n");
+        assert_eq!(web_rendering_method(&config), "mobile");
+        assert_eq!(web_rendering_method(&parse_godot_config("config_version=5\n")), "gl_compatibility");
+    }
+
+    #[test]
+    fn test_expected_sha512() {
+        let sums = "abc123  Godot_v4.3-stable_linux.x86_64.zip\ndef456  Godot_v4.3-stable_export_templates.tpz\n";
+        assert_eq!(expected_sha512(sums, "Godot_v4.3-stable_export_templates.tpz").as_deref(), Some("def456"));
+        assert_eq!(expected_sha512("789 *Godot_v4.3-stable_export_templates.tpz", "Godot_v4.3-stable_export_templates.tpz").as_deref(), Some("789"));
+        assert_eq!(expected_sha512(sums, "Godot_v4.2-stable_export_templates.tpz"), None);
+    }
+
+    #[test]
+    fn test_reveal_command() {
+        let file = Path::new("/projects/game/.tav/web/index.html");
+        assert_eq!(reveal_command("macos", file), ("open".to_string(), vec!["-R".into(), "/projects/game/.tav/web/index.html".into()]));
+        assert_eq!(reveal_command("linux", file), ("xdg-open".to_string(), vec!["/projects/game/.tav/web".into()]));
+        assert_eq!(reveal_command("windows", Path::new(r"C:\Games\demo")), ("explorer".to_string(), vec![r"/select,C:\Games\demo".into()]));
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 // ============================================================================
 // GDScript Templates - Godot Development Rules & Code Templates
 // ============================================================================
@@ -126,6 +128,15 @@ signal game_paused
 signal game_resumed
 signal level_completed
 signal level_failed
+signal options_requested
+signal quit_requested
+
+# Multiplayer Events
+signal peer_connected(peer_id: int)
+signal peer_disconnected(peer_id: int)
+signal connected_to_server
+signal connection_failed
+signal server_disconnected
 
 # Collectibles & Inventory
 signal coin_collected(value: int)
@@ -787,6 +798,8 @@ pub const AI_CONTROLLER_GD: &str = r#"extends Node
 var enabled: bool = false
 var action_queue: Array[Dictionary] = []
 var frame_count: int = 0
+# Frames between auto-captures, set by Kobold through AGENT_CAPTURE_INTERVAL
+var capture_interval: int = 10
 var player: Node = null
 var game_events: Array[String] = []
 
@@ -794,6 +807,8 @@ func _ready() -> void:
 	enabled = OS.get_environment("AGENT_ENABLED") == "true"
 	if not enabled:
 		return
+	if OS.has_environment("AGENT_CAPTURE_INTERVAL"):
+		capture_interval = maxi(1, int(OS.get_environment("AGENT_CAPTURE_INTERVAL")))
 	DirAccess.make_dir_absolute(OS.get_user_data_dir() + "/user_screenshots")
 	_find_player()
 	_connect_events()
@@ -821,10 +836,12 @@ func _process(_delta: float) -> void:
 	_read_actions()
 	if action_queue.size() > 0:
 		_execute_action(action_queue.pop_front())
-	if frame_count % 10 == 0:
+	if frame_count % capture_interval == 0:
 		_capture_screenshot()
 		_save_state()
 
+# agent_input.json is written atomically by Kobold (temp file + rename), so a
+# read never sees a partial action; unparseable content is skipped until next frame
 func _read_actions() -> void:
 	var path = OS.get_user_data_dir() + "/agent_input.json"
 	if not FileAccess.file_exists(path):
@@ -843,19 +860,23 @@ func _read_actions() -> void:
 	if action.has("function"):
 		action_queue.append(action)
 		EventBus.agent_action_received.emit(action.get("function"), action.get("args", []))
-		var clear = FileAccess.open(path, FileAccess.WRITE)
-		if clear:
-			clear.store_string("{}")
-			clear.close()
+		_write_atomic(path, "{}")
 
 func _execute_action(action: Dictionary) -> void:
+	var func_name = action.get("function", "")
+	var args = action.get("args", [])
+	# Input actions go through the InputMap, so they work without a known player
+	if func_name in ["press", "release"]:
+		_do_input_action(func_name, args)
+		return
+	if func_name == "capture":
+		_capture_now(str(args[0]) if args.size() > 0 else str(frame_count))
+		return
 	if not player:
 		_find_player()
 	if not player:
-		_log("ERROR: No player found")
+		_log("No player found", "ERROR")
 		return
-	var func_name = action.get("function", "")
-	var args = action.get("args", [])
 	_log("Executing: %s %s" % [func_name, args])
 	match func_name:
 		"move": _do_move(args)
@@ -868,7 +889,17 @@ func _execute_action(action: Dictionary) -> void:
 				player.interact()
 		"pause": get_tree().paused = true
 		"resume": get_tree().paused = false
-		_: _log("Unknown action: %s" % func_name)
+		_: _log("Unknown action: %s" % func_name, "WARN")
+
+func _do_input_action(func_name: String, args: Array) -> void:
+	if args.size() < 1 or not InputMap.has_action(str(args[0])):
+		_log("Unknown input action: %s" % [args], "WARN")
+		return
+	_log("Executing: %s %s" % [func_name, args])
+	if func_name == "press":
+		Input.action_press(str(args[0]))
+	else:
+		Input.action_release(str(args[0]))
 
 func _do_move(args: Array) -> void:
 	if args.size() < 1:
@@ -901,6 +932,19 @@ func _capture_screenshot() -> void:
 	if img:
 		img.save_png(OS.get_user_data_dir() + "/user_screenshots/frame_%06d.png" % frame_count)
 
+# On-demand capture for capture_now: waits for the current frame to finish drawing,
+# then writes capture_<id>.png via a temp file so Kobold never reads a partial PNG
+func _capture_now(capture_id: String) -> void:
+	await RenderingServer.frame_post_draw
+	var img = get_viewport().get_texture().get_image()
+	if not img:
+		_log("Capture %s failed: no viewport image" % capture_id, "WARN")
+		return
+	var path = OS.get_user_data_dir() + "/user_screenshots/capture_%s.png" % capture_id
+	img.save_png(path + ".tmp")
+	DirAccess.rename_absolute(path + ".tmp", path)
+	_log("Captured frame on demand: %s" % capture_id)
+
 func _save_state() -> void:
 	var state = {
 		"frame": frame_count,
@@ -915,14 +959,22 @@ func _save_state() -> void:
 	if GameState:
 		state["score"] = GameState.score
 		state["inventory"] = GameState.inventory
-	var file = FileAccess.open(OS.get_user_data_dir() + "/game_state.json", FileAccess.WRITE)
-	if file:
-		file.store_string(JSON.stringify(state))
-		file.close()
+	_write_atomic(OS.get_user_data_dir() + "/game_state.json", JSON.stringify(state))
 	EventBus.agent_state_captured.emit(state)
 
-func _log(msg: String) -> void:
-	var entry = "[F%d] %s" % [frame_count, msg]
+# Write to a temp file and rename over the target so readers never see a half-written file
+func _write_atomic(path: String, text: String) -> void:
+	var tmp_path = path + ".tmp"
+	var file = FileAccess.open(tmp_path, FileAccess.WRITE)
+	if not file:
+		return
+	file.store_string(text)
+	file.close()
+	DirAccess.rename_absolute(tmp_path, path)
+
+# Entries carry a [LEVEL] tag (INFO, WARN, ERROR) that get_game_logs can filter on
+func _log(msg: String, level: String = "INFO") -> void:
+	var entry = "[%s] [F%d] %s" % [level, frame_count, msg]
 	game_events.append(entry)
 	print("[AIController] %s" % entry)
 "#;
@@ -1115,6 +1167,21 @@ var _player: Node = null
 var _camera: Node = null  # Can be Camera3D or Camera2D
 var _last_state: Dictionary = {}
 
+# Native sessions request captures through files in the project's .tav folder
+const CAPTURE_REQUEST_PATH = "res://.tav/capture_request.json"
+const CAPTURE_RESULT_PATH = "res://.tav/capture_result.json"
+const CAPTURE_POLL_INTERVAL = 0.25
+var _capture_poll_timer: float = 0.0
+var _capture_busy: bool = false
+# ...and script hot-reloads after .gd-only changes
+const RELOAD_REQUEST_PATH = "res://.tav/reload_request.json"
+const RELOAD_RESULT_PATH = "res://.tav/reload_result.json"
+# ...and window moves/resizes
+const WINDOW_REQUEST_PATH = "res://.tav/window_request.json"
+# ...and scene snapshots for regression diffs
+const SNAPSHOT_REQUEST_PATH = "res://.tav/snapshot_request.json"
+const SNAPSHOT_RESULT_PATH = "res://.tav/snapshot_result.json"
+
 func _ready() -> void:
 	# Register JavaScript callback for web builds
 	if OS.has_feature("web"):
@@ -1175,8 +1242,16 @@ func _find_node_by_name(root: Node, names: Array) -> Node:
 			return found
 	return null
 
-func _physics_process(_delta: float) -> void:
+func _physics_process(delta: float) -> void:
 	_last_state = get_game_state()
+	if not OS.has_feature("web"):
+		_capture_poll_timer += delta
+		if _capture_poll_timer >= CAPTURE_POLL_INTERVAL:
+			_capture_poll_timer = 0.0
+			_poll_capture_request()
+			_poll_reload_request()
+			_poll_window_request()
+			_poll_snapshot_request()
 
 # ============================================================================
 # Public API
@@ -1235,6 +1310,24 @@ func send_input_sequence(actions: Array, duration_ms: int = 500) -> void:
 func get_scene_tree_info() -> Dictionary:
 	return _serialize_node(get_tree().root)
 
+# Flat list of the nodes the current scene owns, i.e. the ones saved in its .tscn.
+# Instanced sub-scene internals and runtime-spawned nodes are left out so a
+# snapshot diffs cleanly against the scene file.
+func get_scene_snapshot() -> Dictionary:
+	var scene = get_tree().current_scene
+	if not scene:
+		return {"error": "No current scene"}
+	var nodes: Array = [{"path": ".", "class": scene.get_class()}]
+	_collect_owned_nodes(scene, scene, nodes)
+	return {"scene": scene.scene_file_path, "nodes": nodes}
+
+func _collect_owned_nodes(scene: Node, node: Node, nodes: Array) -> void:
+	for child in node.get_children():
+		if child.owner != scene:
+			continue
+		nodes.append({"path": str(scene.get_path_to(child)), "class": child.get_class()})
+		_collect_owned_nodes(scene, child, nodes)
+
 # ============================================================================
 # JavaScript Callbacks
 # ============================================================================
@@ -1434,11 +1527,36 @@ func capture_node_multi_angle(node_identifier: String, options: Dictionary = {})
 	
 	_ensure_capture_system()
 	
+	# Resolution is either a single size or [width, height]
+	var resolution = options.get("resolution", 512)
+	if resolution is Array and resolution.size() >= 2:
+		_capture_viewport.size = Vector2i(int(resolution[0]), int(resolution[1]))
+	else:
+		_capture_viewport.size = Vector2i(int(resolution), int(resolution))
+	
+	# Optional key light that follows the capture camera so objects aren't dark
+	var capture_light: DirectionalLight3D = null
+	if options.get("add_light", false):
+		capture_light = DirectionalLight3D.new()
+		capture_light.light_energy = float(options.get("light_energy", 1.0))
+		capture_light.rotation_degrees = Vector3(-30, 20, 0)
+		_capture_camera.add_child(capture_light)
+	
+	# Without an explicit distance, each angle is framed to fit the node's AABB
+	var fit: bool = float(options.get("distance", 0.0)) <= 0
+	var margin: float = options.get("margin", 0.1)
 	var distance: float = options.get("distance", 3.0)
 	var height_offset: float = options.get("height", 1.0)
 	var angles: Array = options.get("angles", ["front", "back", "left", "right"])
 	var include_top: bool = options.get("top", false)
 	var custom_angle: Dictionary = options.get("custom", {})
+	# Save PNGs to disk and return paths instead of base64 (avoids huge eval strings)
+	var to_disk: bool = options.get("to_disk", false)
+	var capture_prefix = ""
+	if to_disk:
+		var captures_dir = OS.get_user_data_dir() + "/user_screenshots/captures"
+		DirAccess.make_dir_recursive_absolute(captures_dir)
+		capture_prefix = "%s/%s_%d_" % [captures_dir, node.name.validate_filename(), Time.get_ticks_msec()]
 	
 	var bounds = get_node_bounds(node)
 	var center = Vector3(bounds.center.x, bounds.center.y, bounds.center.z)
@@ -1452,7 +1570,9 @@ func capture_node_multi_angle(node_identifier: String, options: Dictionary = {})
 	var results: Dictionary = {
 		"node": node_identifier,
 		"bounds": bounds,
-		"captures": {}
+		"captures": {},
+		"mode": "disk" if to_disk else "base64",
+		"framing": "fit" if fit else "fixed"
 	}
 	
 	# Standard angles (yaw in degrees)
@@ -1475,13 +1595,17 @@ func capture_node_multi_angle(node_identifier: String, options: Dictionary = {})
 				height_offset,
 				cos(yaw) * distance
 			)
-			var capture = await _capture_from_position(cam_pos, center)
+			if fit:
+				cam_pos = _fit_camera_position(center, cam_pos, size, margin)
+			var capture = await _capture_from_position(cam_pos, center, _capture_file(capture_prefix, angle_name))
 			results.captures[angle_name] = capture
 	
 	# Top-down view
 	if include_top:
 		var top_pos = center + Vector3(0, distance * 1.5, 0.01)
-		var capture = await _capture_from_position(top_pos, center)
+		if fit:
+			top_pos = _fit_camera_position(center, top_pos, size, margin)
+		var capture = await _capture_from_position(top_pos, center, _capture_file(capture_prefix, "top"))
 		results.captures["top"] = capture
 	
 	# Custom angle
@@ -1494,12 +1618,47 @@ func capture_node_multi_angle(node_identifier: String, options: Dictionary = {})
 			sin(custom_pitch) * custom_dist + height_offset,
 			cos(custom_yaw) * cos(custom_pitch) * custom_dist
 		)
-		var capture = await _capture_from_position(cam_pos, center)
+		if fit and not custom_angle.has("distance"):
+			cam_pos = _fit_camera_position(center, cam_pos, size, margin)
+		var capture = await _capture_from_position(cam_pos, center, _capture_file(capture_prefix, "custom"))
 		results.captures["custom"] = capture
 	
+	if capture_light:
+		capture_light.queue_free()
+	
 	return results
 
-func _capture_from_position(cam_pos: Vector3, look_at_pos: Vector3) -> String:
+# Move the camera along its view direction so the whole AABB fits the frame.
+# Each AABB corner is projected onto the camera plane; the closest distance where
+# every corner stays inside the horizontal and vertical FOV wins.
+func _fit_camera_position(center: Vector3, cam_pos: Vector3, size: Vector3, margin: float) -> Vector3:
+	var view_dir = (cam_pos - center).normalized()
+	var forward = -view_dir
+	var up = Vector3.UP if abs(forward.dot(Vector3.UP)) < 0.99 else Vector3.FORWARD
+	var right = forward.cross(up).normalized()
+	up = right.cross(forward).normalized()
+	
+	var tan_v = tan(deg_to_rad(_capture_camera.fov) / 2.0)
+	var tan_h = tan_v * float(_capture_viewport.size.x) / max(_capture_viewport.size.y, 1)
+	var half = size / 2.0
+	var fit_distance = 0.0
+	for i in 8:
+		var corner = Vector3(
+			half.x if i & 1 else -half.x,
+			half.y if i & 2 else -half.y,
+			half.z if i & 4 else -half.z
+		)
+		var needed_h = abs(corner.dot(right)) * (1.0 + margin) / tan_h
+		var needed_v = abs(corner.dot(up)) * (1.0 + margin) / tan_v
+		fit_distance = max(fit_distance, corner.dot(view_dir) + max(needed_h, needed_v))
+	
+	return center + view_dir * max(fit_distance, _capture_camera.near * 2.0)
+
+func _capture_file(prefix: String, angle_name: String) -> String:
+	return prefix + angle_name + ".png" if prefix != "" else ""
+
+# Returns the PNG as base64, or the saved file path when save_path is set
+func _capture_from_position(cam_pos: Vector3, look_at_pos: Vector3, save_path: String = "") -> String:
 	_capture_camera.global_position = cam_pos
 	_capture_camera.look_at(look_at_pos)
 	
@@ -1507,8 +1666,12 @@ func _capture_from_position(cam_pos: Vector3, look_at_pos: Vector3) -> String:
 	_capture_viewport.render_target_update_mode = SubViewport.UPDATE_ONCE
 	await RenderingServer.frame_post_draw
 	
-	# Get image and convert to base64
 	var img = _capture_viewport.get_texture().get_image()
+	if save_path != "":
+		img.save_png(save_path)
+		return save_path
+	
+	# Convert to base64
 	var png_data = img.save_png_to_buffer()
 	return Marshalls.raw_to_base64(png_data)
 
@@ -1531,6 +1694,97 @@ func _js_capture_node(args: Array) -> String:
 	_start_async_capture(promise_id, node_id, options)
 	return JSON.stringify({"promise_id": promise_id, "status": "pending"})
 
+func _poll_capture_request() -> void:
+	if _capture_busy or not FileAccess.file_exists(CAPTURE_REQUEST_PATH):
+		return
+	var text = FileAccess.get_file_as_string(CAPTURE_REQUEST_PATH)
+	DirAccess.remove_absolute(ProjectSettings.globalize_path(CAPTURE_REQUEST_PATH))
+	var request = JSON.parse_string(text)
+	if not request is Dictionary:
+		return
+	
+	_capture_busy = true
+	var options: Dictionary = request.get("options", {}) if request.get("options") is Dictionary else {}
+	var result = await capture_node_multi_angle(str(request.get("node", "")), options)
+	result["request_id"] = request.get("request_id", "")
+	_write_result(CAPTURE_RESULT_PATH, result)
+	_capture_busy = false
+
+func _poll_reload_request() -> void:
+	if not FileAccess.file_exists(RELOAD_REQUEST_PATH):
+		return
+	var text = FileAccess.get_file_as_string(RELOAD_REQUEST_PATH)
+	DirAccess.remove_absolute(ProjectSettings.globalize_path(RELOAD_REQUEST_PATH))
+	var request = JSON.parse_string(text)
+	if not request is Dictionary:
+		return
+	
+	var scripts: Array = request.get("scripts", []) if request.get("scripts") is Array else []
+	var result = reload_scripts(scripts)
+	result["request_id"] = request.get("request_id", "")
+	_write_result(RELOAD_RESULT_PATH, result)
+
+func _poll_window_request() -> void:
+	if not FileAccess.file_exists(WINDOW_REQUEST_PATH):
+		return
+	var text = FileAccess.get_file_as_string(WINDOW_REQUEST_PATH)
+	DirAccess.remove_absolute(ProjectSettings.globalize_path(WINDOW_REQUEST_PATH))
+	var request = JSON.parse_string(text)
+	if not request is Dictionary:
+		return
+	
+	var size = Vector2i(int(request.get("width", 0)), int(request.get("height", 0)))
+	if size.x > 0 and size.y > 0:
+		DisplayServer.window_set_size(size)
+	DisplayServer.window_set_position(Vector2i(int(request.get("x", 0)), int(request.get("y", 0))))
+	print("[KoboldBridge] Window moved to %s, size %s" % [DisplayServer.window_get_position(), DisplayServer.window_get_size()])
+
+func _poll_snapshot_request() -> void:
+	if not FileAccess.file_exists(SNAPSHOT_REQUEST_PATH):
+		return
+	var text = FileAccess.get_file_as_string(SNAPSHOT_REQUEST_PATH)
+	DirAccess.remove_absolute(ProjectSettings.globalize_path(SNAPSHOT_REQUEST_PATH))
+	var request = JSON.parse_string(text)
+	if not request is Dictionary:
+		return
+	
+	var result = get_scene_snapshot()
+	result["request_id"] = request.get("request_id", "")
+	_write_result(SNAPSHOT_RESULT_PATH, result)
+
+# Reload scripts in place, keeping the state of nodes that use them
+func reload_scripts(paths: Array) -> Dictionary:
+	var reloaded: Array = []
+	var skipped: Array = []
+	var failed: Dictionary = {}
+	for path in paths:
+		path = str(path)
+		# Scripts the game hasn't loaded yet will read the new source when they are
+		if not ResourceLoader.has_cached(path):
+			skipped.append(path)
+			continue
+		var script = load(path)
+		if not script is GDScript:
+			failed[path] = "Not a GDScript resource"
+			continue
+		script.source_code = FileAccess.get_file_as_string(path)
+		var err = script.reload(true)
+		if err != OK:
+			failed[path] = error_string(err)
+		else:
+			reloaded.append(path)
+	print("[KoboldBridge] Reloaded %d script(s), %d failed" % [reloaded.size(), failed.size()])
+	return {"reloaded": reloaded, "skipped": skipped, "failed": failed}
+
+# Write to a temp file then rename so the app never reads a partial result
+func _write_result(path: String, result: Dictionary) -> void:
+	var tmp_path = path + ".tmp"
+	var file = FileAccess.open(tmp_path, FileAccess.WRITE)
+	if file:
+		file.store_string(JSON.stringify(result))
+		file.close()
+		DirAccess.rename_absolute(ProjectSettings.globalize_path(tmp_path), ProjectSettings.globalize_path(path))
+
 func _start_async_capture(promise_id: String, node_id: String, options: Dictionary) -> void:
 	var result = await capture_node_multi_angle(node_id, options)
 	# Emit result to JavaScript
@@ -1539,3 +1793,606 @@ func _start_async_capture(promise_id: String, node_id: String, options: Dictiona
 		var js_code = "window.dispatchEvent(new CustomEvent('kobold-capture-complete', { detail: { id: '%s', result: %s } }));" % [promise_id, js_result]
 		JavaScriptBridge.eval(js_code)
 "#;
+
+// ============================================================================
+// Thumbnail Renderer - run as `godot --script thumbnail.gd -- <source> <output.png> <max_size>`
+// ============================================================================
+
+pub const THUMBNAIL_GD: &str = r#"extends SceneTree
+## Kobold Thumbnail - Writes a downscaled PNG preview of an image or glTF model
+
+func _initialize() -> void:
+	var args := OS.get_cmdline_user_args()
+	if args.size() < 3:
+		printerr("[Thumbnail] Usage: -- <source> <output.png> <max_size>")
+		quit(1)
+		return
+	var source: String = args[0]
+	var output: String = args[1]
+	var max_size := maxi(int(args[2]), 1)
+	var ext := source.get_extension().to_lower()
+	if ext == "glb" or ext == "gltf":
+		_render_model.call_deferred(source, output, max_size)
+	else:
+		quit(_save_image(Image.load_from_file(source), output, max_size))
+
+func _save_image(image: Image, output: String, max_size: int) -> int:
+	if image == null or image.is_empty():
+		printerr("[Thumbnail] Could not load image")
+		return 1
+	if image.is_compressed():
+		image.decompress()
+	var longest := maxi(image.get_width(), image.get_height())
+	if longest > max_size:
+		var scale := float(max_size) / longest
+		var width := maxi(1, int(image.get_width() * scale))
+		var height := maxi(1, int(image.get_height() * scale))
+		image.resize(width, height, Image.INTERPOLATE_LANCZOS)
+	var err := image.save_png(output)
+	if err != OK:
+		printerr("[Thumbnail] Failed to save ", output, ": ", error_string(err))
+		return 1
+	print("[Thumbnail] Saved ", output)
+	return 0
+
+func _render_model(source: String, output: String, max_size: int) -> void:
+	var doc := GLTFDocument.new()
+	var gltf_state := GLTFState.new()
+	if doc.append_from_file(source, gltf_state) != OK:
+		printerr("[Thumbnail] Could not load model: ", source)
+		quit(1)
+		return
+	var model := doc.generate_scene(gltf_state)
+
+	var viewport := SubViewport.new()
+	viewport.size = Vector2i(max_size, max_size)
+	viewport.own_world_3d = true
+	viewport.transparent_bg = true
+	viewport.render_target_update_mode = SubViewport.UPDATE_ALWAYS
+	root.add_child(viewport)
+	viewport.add_child(model)
+
+	# Three-quarter view that fits the model's bounding sphere
+	var aabb := _model_aabb(model)
+	var center := aabb.get_center()
+	var radius := maxf(aabb.size.length() * 0.5, 0.01)
+	var camera := Camera3D.new()
+	viewport.add_child(camera)
+	var distance := radius / sin(deg_to_rad(camera.fov * 0.5)) * 1.05
+	camera.near = maxf(distance - radius * 2.0, 0.01)
+	camera.far = distance + radius * 2.0
+	camera.look_at_from_position(center + Vector3(1.0, 0.8, 1.0).normalized() * distance, center)
+
+	var env := Environment.new()
+	env.ambient_light_source = Environment.AMBIENT_SOURCE_COLOR
+	env.ambient_light_color = Color.WHITE
+	env.ambient_light_energy = 0.4
+	camera.environment = env
+	var light := DirectionalLight3D.new()
+	viewport.add_child(light)
+	light.look_at_from_position(camera.position, center)
+
+	# Give the renderer a few frames to upload meshes and textures
+	for i in 3:
+		await process_frame
+	await RenderingServer.frame_post_draw
+	quit(_save_image(viewport.get_texture().get_image(), output, max_size))
+
+func _model_aabb(model: Node) -> AABB:
+	var aabb := AABB()
+	var found := false
+	for node in model.find_children("*", "VisualInstance3D", true, false):
+		var instance := node as VisualInstance3D
+		var box := instance.global_transform * instance.get_aabb()
+		aabb = aabb.merge(box) if found else box
+		found = true
+	return aabb if found else AABB(Vector3(-0.5, -0.5, -0.5), Vector3.ONE)
+"#;
+
+// ============================================================================
+// Add Component - run as `godot --path <project> --script add_component.gd -- <scene> <node> <script> <base> <name>`
+// ============================================================================
+
+pub const ADD_COMPONENT_GD: &str = r#"extends SceneTree
+## Kobold Add Component - Instances a component script under a node of a saved scene
+
+func _initialize() -> void:
+	var args := OS.get_cmdline_user_args()
+	if args.size() < 5:
+		printerr("[AddComponent] Usage: -- <scene> <node_path> <script> <base_type> <name>")
+		quit(1)
+		return
+	quit(_add_component(args[0], args[1], args[2], args[3], args[4]))
+
+func _add_component(scene_path: String, node_path: String, script_path: String, base_type: String, node_name: String) -> int:
+	var packed := load(scene_path) as PackedScene
+	if packed == null:
+		printerr("[AddComponent] Could not load scene: ", scene_path)
+		return 1
+	var root := packed.instantiate()
+	var target := root.get_node_or_null(node_path)
+	if target == null:
+		printerr("[AddComponent] Node not found: ", node_path)
+		root.free()
+		return 1
+	var component: Node = ClassDB.instantiate(base_type)
+	component.set_script(load(script_path))
+	component.name = node_name
+	target.add_child(component)
+	component.owner = root
+	var err := packed.pack(root)
+	if err == OK:
+		err = ResourceSaver.save(packed, scene_path)
+	root.free()
+	if err != OK:
+		printerr("[AddComponent] Failed to save scene: ", error_string(err))
+		return 1
+	print("[AddComponent] Added ", node_name, " under ", node_path)
+	return 0
+"#;
+
+// ============================================================================
+// FBX to GLB - run as `godot --headless --script fbx_to_glb.gd -- <input.fbx> <output.glb>` (Godot 4.3+)
+// ============================================================================
+
+pub const FBX_TO_GLB_GD: &str = r#"extends SceneTree
+## Kobold FBX to GLB - Converts an FBX file to binary glTF with Godot's FBX importer
+
+func _initialize() -> void:
+	var args := OS.get_cmdline_user_args()
+	if args.size() < 2:
+		printerr("[FbxToGlb] Usage: -- <input.fbx> <output.glb>")
+		quit(1)
+		return
+	quit(_convert(args[0], args[1]))
+
+func _convert(input_path: String, output_path: String) -> int:
+	if not ClassDB.class_exists("FBXDocument"):
+		printerr("[FbxToGlb] FBX import needs Godot 4.3 or newer")
+		return 1
+	var fbx: GLTFDocument = ClassDB.instantiate("FBXDocument")
+	var fbx_state: GLTFState = ClassDB.instantiate("FBXState")
+	var err := fbx.append_from_file(input_path, fbx_state)
+	if err != OK:
+		printerr("[FbxToGlb] Could not read FBX: ", error_string(err))
+		return 1
+	print("[FbxToGlb] Loaded ", input_path)
+	var scene := fbx.generate_scene(fbx_state)
+	if scene == null:
+		printerr("[FbxToGlb] FBX contains no scene")
+		return 1
+	var gltf := GLTFDocument.new()
+	var gltf_state := GLTFState.new()
+	err = gltf.append_from_scene(scene, gltf_state)
+	if err == OK:
+		err = gltf.write_to_filesystem(gltf_state, output_path)
+	scene.free()
+	if err != OK:
+		printerr("[FbxToGlb] Failed to write GLB: ", error_string(err))
+		return 1
+	print("[FbxToGlb] Wrote ", output_path)
+	return 0
+"#;
+
+// ============================================================================
+// Touch Controls - on-screen joystick and jump button for the mobile template option
+// ============================================================================
+
+pub const TOUCH_CONTROLS_GD: &str = r#"extends CanvasLayer
+## On-screen controls for phones and tablets, hidden on devices without a touchscreen
+
+## Show the controls on desktop too (enable "Emulate Touch From Mouse" to test them)
+@export var always_visible: bool = false
+
+func _ready() -> void:
+	visible = always_visible or DisplayServer.is_touchscreen_available()
+"#;
+
+pub const VIRTUAL_JOYSTICK_GD: &str = r#"extends Control
+class_name VirtualJoystick
+## Touch joystick that presses the move_* actions with analog strength
+
+@export var radius: float = 80.0
+@export var deadzone: float = 0.2
+@export var action_left: StringName = &"move_left"
+@export var action_right: StringName = &"move_right"
+@export var action_up: StringName = &"move_up"
+@export var action_down: StringName = &"move_down"
+
+var _touch_index: int = -1
+var _knob := Vector2.ZERO
+
+func _input(event: InputEvent) -> void:
+	if not is_visible_in_tree():
+		return
+	if event is InputEventScreenTouch:
+		if event.pressed and _touch_index == -1 and get_global_rect().has_point(event.position):
+			_touch_index = event.index
+			_move_knob(event.position)
+		elif not event.pressed and event.index == _touch_index:
+			_touch_index = -1
+			_move_knob(get_global_rect().get_center())
+	elif event is InputEventScreenDrag and event.index == _touch_index:
+		_move_knob(event.position)
+
+func _move_knob(touch_position: Vector2) -> void:
+	_knob = (touch_position - get_global_rect().get_center()).limit_length(radius)
+	var direction := _knob / radius
+	if direction.length() < deadzone:
+		direction = Vector2.ZERO
+	_set_action(action_left, -direction.x)
+	_set_action(action_right, direction.x)
+	_set_action(action_up, -direction.y)
+	_set_action(action_down, direction.y)
+	queue_redraw()
+
+func _set_action(action: StringName, strength: float) -> void:
+	if strength > 0.0:
+		Input.action_press(action, strength)
+	else:
+		Input.action_release(action)
+
+func _draw() -> void:
+	var center := size / 2.0
+	draw_circle(center, radius, Color(1, 1, 1, 0.15))
+	draw_circle(center + _knob, radius * 0.4, Color(1, 1, 1, 0.45))
+"#;
+
+pub const TOUCH_BUTTON_GD: &str = r#"extends Control
+class_name TouchButton
+## Touch button that holds an input action while pressed
+
+@export var action: StringName = &"jump"
+@export var label: String = "A"
+
+var _touch_index: int = -1
+
+func _input(event: InputEvent) -> void:
+	if not is_visible_in_tree() or not event is InputEventScreenTouch:
+		return
+	if event.pressed and _touch_index == -1 and get_global_rect().has_point(event.position):
+		_touch_index = event.index
+		Input.action_press(action)
+		queue_redraw()
+	elif not event.pressed and event.index == _touch_index:
+		_touch_index = -1
+		Input.action_release(action)
+		queue_redraw()
+
+func _draw() -> void:
+	var center := size / 2.0
+	var alpha := 0.45 if _touch_index != -1 else 0.2
+	draw_circle(center, minf(size.x, size.y) / 2.0, Color(1, 1, 1, alpha))
+	var font := get_theme_default_font()
+	var font_size := get_theme_default_font_size() * 2
+	var text_size := font.get_string_size(label, HORIZONTAL_ALIGNMENT_CENTER, -1, font_size)
+	draw_string(font, center + Vector2(-text_size.x / 2.0, text_size.y / 4.0), label, HORIZONTAL_ALIGNMENT_CENTER, -1, font_size)
+"#;
+
+pub const TOUCH_CONTROLS_TSCN: &str = r#"[gd_scene load_steps=4 format=3]
+
+[ext_resource type="Script" path="res://src/ui/touch_controls.gd" id="1"]
+[ext_resource type="Script" path="res://src/ui/virtual_joystick.gd" id="2"]
+[ext_resource type="Script" path="res://src/ui/touch_button.gd" id="3"]
+
+[node name="TouchControls" type="CanvasLayer"]
+layer = 10
+script = ExtResource("1")
+
+[node name="Joystick" type="Control" parent="."]
+layout_mode = 3
+anchors_preset = 2
+anchor_top = 1.0
+anchor_bottom = 1.0
+offset_left = 40.0
+offset_top = -240.0
+offset_right = 240.0
+offset_bottom = -40.0
+grow_vertical = 0
+mouse_filter = 2
+script = ExtResource("2")
+
+[node name="JumpButton" type="Control" parent="."]
+layout_mode = 3
+anchors_preset = 3
+anchor_left = 1.0
+anchor_top = 1.0
+anchor_right = 1.0
+anchor_bottom = 1.0
+offset_left = -200.0
+offset_top = -200.0
+offset_right = -60.0
+offset_bottom = -60.0
+grow_horizontal = 0
+grow_vertical = 0
+mouse_filter = 2
+script = ExtResource("3")
+"#;
+
+/// Files the mobile option adds (relative path, contents)
+pub const TOUCH_CONTROLS_FILES: [(&str, &str); 4] = [
+    ("scenes/ui/touch_controls.tscn", TOUCH_CONTROLS_TSCN),
+    ("src/ui/touch_controls.gd", TOUCH_CONTROLS_GD),
+    ("src/ui/virtual_joystick.gd", VIRTUAL_JOYSTICK_GD),
+    ("src/ui/touch_button.gd", TOUCH_BUTTON_GD),
+];
+
+// ============================================================================
+// Built-in Components
+// ============================================================================
+
+/// A reusable component script shipped in src/components
+#[derive(Debug, Clone, Copy)]
+pub struct BuiltinComponent {
+    pub id: &'static str,
+    pub class_name: &'static str,
+    /// Node type the script extends
+    pub base: &'static str,
+    pub source: &'static str,
+}
+
+impl BuiltinComponent {
+    pub fn path(&self) -> String {
+        format!("src/components/{}.gd", self.id)
+    }
+}
+
+pub const BUILTIN_COMPONENTS: [BuiltinComponent; 6] = [
+    BuiltinComponent { id: "health_component", class_name: "HealthComponent", base: "Node", source: HEALTH_COMPONENT_GD },
+    BuiltinComponent { id: "movement_component_2d", class_name: "MovementComponent2D", base: "Node", source: MOVEMENT_COMPONENT_2D_GD },
+    BuiltinComponent { id: "state_machine", class_name: "StateMachine", base: "Node", source: STATE_MACHINE_GD },
+    BuiltinComponent { id: "locomotion_controller", class_name: "LocomotionController", base: "Node", source: LOCOMOTION_CONTROLLER_GD },
+    BuiltinComponent { id: "camera_rig_3d", class_name: "CameraRig3D", base: "Node3D", source: CAMERA_RIG_3D_GD },
+    BuiltinComponent { id: "mixamo_retargeter", class_name: "MixamoRetargeter", base: "Node", source: MIXAMO_RETARGETER_GD },
+];
+
+/// Look up a built-in component by id ("health_component") or class name ("HealthComponent")
+pub fn find_builtin_component(name: &str) -> Option<BuiltinComponent> {
+    let name = name.trim();
+    BUILTIN_COMPONENTS
+        .into_iter()
+        .find(|c| c.id.eq_ignore_ascii_case(name) || c.class_name.eq_ignore_ascii_case(name))
+}
+
+// ============================================================================
+// Project Template Registry
+// ============================================================================
+
+// Embed template files from templates folder at compile time
+pub const THIRD_PERSON_SCENE: &str = include_str!("../../templates/third-person-3d/scene.tscn");
+pub const THIRD_PERSON_PLAYER: &str = include_str!("../../templates/third-person-3d/player.gd");
+// Note: Third-person uses AMSG addon (packages/amsg) for locomotion, camera, and states
+pub const FIRST_PERSON_SCENE: &str = include_str!("../../templates/first-person-3d/scene.tscn");
+pub const FIRST_PERSON_PLAYER: &str = include_str!("../../templates/first-person-3d/player.gd");
+pub const PLATFORMER_2D_SCENE: &str = include_str!("../../templates/platformer-2d/scene.tscn");
+pub const PLATFORMER_2D_PLAYER: &str = include_str!("../../templates/platformer-2d/player.gd");
+pub const PLATFORMER_TILEMAP_2D_SCENE: &str = include_str!("../../templates/platformer-tilemap-2d/scene.tscn");
+pub const TOP_DOWN_2D_SCENE: &str = include_str!("../../templates/top-down-2d/scene.tscn");
+pub const TOP_DOWN_2D_PLAYER: &str = include_str!("../../templates/top-down-2d/player.gd");
+pub const UI_MENU_2D_SCENE: &str = include_str!("../../templates/ui-menu-2d/scene.tscn");
+pub const UI_MENU_2D_SCRIPT: &str = include_str!("../../templates/ui-menu-2d/player.gd");
+pub const MULTIPLAYER_3D_SCENE: &str = include_str!("../../templates/multiplayer-3d/scene.tscn");
+pub const MULTIPLAYER_3D_PLAYER: &str = include_str!("../../templates/multiplayer-3d/player.gd");
+pub const MULTIPLAYER_3D_PLAYER_SCENE: &str = include_str!("../../templates/multiplayer-3d/player.tscn");
+pub const MULTIPLAYER_3D_LOBBY: &str = include_str!("../../templates/multiplayer-3d/network_lobby.gd");
+
+/// A project template for a single dimension
+#[derive(Debug, Clone)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub dimension: String,
+    pub scene: String,
+    pub player_script: String,
+    /// Component scripts (src/components) the template's scene depends on
+    pub components: Vec<String>,
+    /// Extra project files (relative path, contents) beyond the main scene and player script
+    pub files: Vec<(String, String)>,
+    /// Supports the `mobile` option: mobile renderer plus touch controls for move_*/jump
+    pub mobile: bool,
+}
+
+/// Template metadata for the frontend template picker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateMeta {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub dimensions: Vec<String>,
+    pub components: Vec<String>,
+    pub mobile: bool,
+}
+
+fn template(
+    id: &str,
+    name: &str,
+    description: &str,
+    dimension: &str,
+    scene: String,
+    player_script: String,
+    components: &[&str],
+) -> Template {
+    Template {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        dimension: dimension.to_string(),
+        scene,
+        player_script,
+        components: components.iter().map(|c| c.to_string()).collect(),
+        files: Vec::new(),
+        mobile: false,
+    }
+}
+
+impl Template {
+    fn with_files(mut self, files: &[(&str, &str)]) -> Self {
+        self.files = files.iter().map(|(path, content)| (path.to_string(), content.to_string())).collect();
+        self
+    }
+
+    fn with_touch_controls(mut self) -> Self {
+        self.mobile = true;
+        self
+    }
+}
+
+/// Built-in templates - one entry per supported (id, dimension) pair
+pub fn get_template_registry() -> Vec<Template> {
+    vec![
+        template(
+            "platformer", "Platformer", "Side-scrolling jump & run", "2d",
+            PLATFORMER_2D_SCENE.to_string(), PLATFORMER_2D_PLAYER.to_string(),
+            &["health_component", "movement_component_2d"],
+        )
+        .with_touch_controls(),
+        template(
+            "platformer-tilemap", "Platformer (Tilemap)", "Jump & run with a ready-made tile level", "2d",
+            PLATFORMER_TILEMAP_2D_SCENE.to_string(), PLATFORMER_2D_PLAYER.to_string(),
+            &["health_component", "movement_component_2d"],
+        )
+        .with_touch_controls(),
+        template(
+            "top-down", "Top-Down", "Bird's eye view action/RPG", "2d",
+            TOP_DOWN_2D_SCENE.to_string(), TOP_DOWN_2D_PLAYER.to_string(),
+            &["health_component"],
+        )
+        .with_touch_controls(),
+        template(
+            "first-person", "First Person", "FPS / exploration", "3d",
+            FIRST_PERSON_SCENE.to_string(), FIRST_PERSON_PLAYER.to_string(),
+            &["health_component"],
+        ),
+        template(
+            "third-person", "Third Person", "Over-shoulder camera", "3d",
+            THIRD_PERSON_SCENE.to_string(), THIRD_PERSON_PLAYER.to_string(),
+            &["health_component"],
+        ),
+        template(
+            "multiplayer", "Multiplayer", "Host/join lobby with replicated players", "3d",
+            MULTIPLAYER_3D_SCENE.to_string(), MULTIPLAYER_3D_PLAYER.to_string(),
+            &["health_component"],
+        )
+        .with_files(&[
+            ("scenes/player.tscn", MULTIPLAYER_3D_PLAYER_SCENE),
+            ("src/systems/network_lobby.gd", MULTIPLAYER_3D_LOBBY),
+        ]),
+        template(
+            "ui-menu", "Menu / UI", "Main menu with Start, Options & Quit", "2d",
+            UI_MENU_2D_SCENE.to_string(), UI_MENU_2D_SCRIPT.to_string(),
+            &[],
+        ),
+        puzzle_template("2d"),
+        puzzle_template("3d"),
+        empty_template("2d"),
+        empty_template("3d"),
+    ]
+}
+
+/// Look up a template by id for the given dimension
+pub fn find_template(id: &str, dimension: &str) -> Option<Template> {
+    get_template_registry()
+        .into_iter()
+        .find(|t| t.id == id && t.dimension == dimension)
+}
+
+/// Registry grouped by template id, for populating the template picker
+pub fn list_template_meta() -> Vec<TemplateMeta> {
+    let mut metas: Vec<TemplateMeta> = Vec::new();
+    for tpl in get_template_registry() {
+        if let Some(meta) = metas.iter_mut().find(|m| m.id == tpl.id) {
+            meta.dimensions.push(tpl.dimension);
+            continue;
+        }
+        metas.push(TemplateMeta {
+            id: tpl.id,
+            name: tpl.name,
+            description: tpl.description,
+            dimensions: vec![tpl.dimension],
+            components: tpl.components,
+            mobile: tpl.mobile,
+        });
+    }
+    metas
+}
+
+pub fn puzzle_template(dimension: &str) -> Template {
+    let node_type = if dimension == "3d" { "Node3D" } else { "Node2D" };
+    template(
+        "puzzle", "Puzzle", "Logic & matching games", dimension,
+        format!(r#"[gd_scene load_steps=2 format=3]
+
+[ext_resource type="Script" path="res://assets/entities/player/player.gd" id="1"]
+
+[node name="Main" type="{}"]
+script = ExtResource("1")
+
+[node name="UI" type="CanvasLayer" parent="."]
+
+[node name="ScoreLabel" type="Label" parent="UI"]
+offset_right = 200.0
+offset_bottom = 40.0
+text = "Score: 0"
+"#, node_type),
+        r#"extends Node
+## Puzzle Game - Uses EventBus for score tracking
+
+func _ready() -> void:
+	# Listen to score changes from GameState
+	EventBus.score_changed.connect(_on_score_changed)
+	EventBus.coin_collected.connect(_on_coin_collected)
+	_update_display()
+	print("Puzzle ready! Space to add points, uses EventBus + GameState")
+
+func _input(event: InputEvent) -> void:
+	if event.is_action_pressed("ui_accept"):
+		GameState.add_score(10)
+		EventBus.coin_collected.emit(10)
+
+func _on_score_changed(new_score: int) -> void:
+	_update_display()
+
+func _on_coin_collected(value: int) -> void:
+	print("Collected: %d points!" % value)
+
+func _update_display() -> void:
+	$UI/ScoreLabel.text = "Score: %d" % GameState.score
+"#.to_string(),
+        &[],
+    )
+}
+
+/// Empty project with professional architecture
+pub fn empty_template(dimension: &str) -> Template {
+    let node_type = if dimension == "3d" { "Node3D" } else { "Node2D" };
+    template(
+        "empty", "Empty Project", "Start from scratch", dimension,
+        format!(r#"[gd_scene load_steps=2 format=3]
+
+[ext_resource type="Script" path="res://assets/entities/player/player.gd" id="1"]
+
+[node name="Main" type="{}"]
+script = ExtResource("1")
+"#, node_type),
+        format!(r#"extends {}
+## Empty {} Project - Professional Architecture Ready
+## EventBus, GameState, and Components are pre-configured
+
+func _ready() -> void:
+	# EventBus is ready for cross-system communication
+	# GameState persists data across scenes
+	# Components in src/components/ are ready to use
+	
+	# Example: Listen to game events
+	EventBus.player_spawned.connect(func(p): print("Player spawned: ", p))
+	EventBus.level_completed.connect(func(): print("Level done!"))
+	
+	print("Hello from Kobold! Architecture ready.")
+	print("- EventBus: Signal bus for decoupled communication")
+	print("- GameState: Persistent cross-scene data")
+	print("- Components: HealthComponent, MovementComponent2D")
+"#, node_type, if dimension == "3d" { "3D" } else { "2D" }),
+        &[],
+    )
+}
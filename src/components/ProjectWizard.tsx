@@ -11,7 +11,7 @@ export function ProjectWizard() {
   const [creating, setCreating] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
-  const createProject = async (config: { template: string; dimension: string; name: string; path: string }) => {
+  const createProject = async (config: { template: string; dimension: string; name: string; path: string; mobile: boolean }) => {
     setCreating(true);
     setError(null);
 
@@ -21,6 +21,7 @@ export function ProjectWizard() {
         parentPath: config.path,
         dimension: config.dimension,
         template: config.template,
+        mobile: config.mobile,
       });
 
       // Load the new project
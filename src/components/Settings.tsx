@@ -3,6 +3,7 @@ import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import {
   X,
   Check,
@@ -37,6 +38,15 @@ interface SetupItem {
   path?: string;
 }
 
+interface InstallProgress {
+  installId: string;
+  tool: string;
+  line?: string;
+  done: boolean;
+  success?: boolean;
+  message?: string;
+}
+
 export function Settings({ onClose }: { onClose: () => void }) {
   const {
     isSignedIn,
@@ -51,6 +61,32 @@ export function Settings({ onClose }: { onClose: () => void }) {
   const [apiKey, setApiKey] = useState("");
   const [showApiKey, setShowApiKey] = useState(false);
   const [installStatus, setInstallStatus] = useState<string | null>(null);
+
+  // Installs run in the background; follow install-progress until the matching "done" event
+  const runInstall = async (command: string) => {
+    let installId: string | null = null;
+    const early: InstallProgress[] = [];
+    let resolveDone: (event: InstallProgress) => void = () => {};
+    const done = new Promise<InstallProgress>((resolve) => (resolveDone = resolve));
+    const handle = (event: InstallProgress) => {
+      if (event.line) setInstallStatus(event.line);
+      if (event.done) resolveDone(event);
+    };
+    const unlisten = await listen<InstallProgress>("install-progress", ({ payload }) => {
+      if (installId === null) early.push(payload);
+      else if (payload.installId === installId) handle(payload);
+    });
+    try {
+      installId = await invoke<string>(command);
+      early.filter((event) => event.installId === installId).forEach(handle);
+      const result = await done;
+      if (!result.success) throw new Error(result.message);
+      return result.message;
+    } finally {
+      unlisten();
+      setInstallStatus(null);
+    }
+  };
   
   const [godot, setGodot] = useState<SetupItem>({
     id: "godot",
@@ -185,7 +221,7 @@ export function Settings({ onClose }: { onClose: () => void }) {
         } else {
           setGodotMcp((prev) => ({ ...prev, status: "installing" }));
           try {
-            await invoke<string>("install_godot_mcp");
+            await runInstall("install_godot_mcp");
             await invoke("setup_godot_mcp_config");
             setGodotMcp((prev) => ({ ...prev, status: "installed" }));
           } catch {
@@ -205,7 +241,7 @@ export function Settings({ onClose }: { onClose: () => void }) {
         } else {
           setBeads((prev) => ({ ...prev, status: "installing" }));
           try {
-            await invoke<string>("install_beads");
+            await runInstall("install_beads");
             setBeads((prev) => ({ ...prev, status: "installed" }));
           } catch {
             setBeads((prev) => ({ ...prev, status: "not-installed" }));
@@ -264,10 +300,20 @@ export function Settings({ onClose }: { onClose: () => void }) {
     }
   };
 
+  const applyGodotPath = async () => {
+    try {
+      const version = await invoke<string>("set_godot_path", { path: godotPath });
+      setGodot((prev) => ({ ...prev, status: "installed", path: godotPath }));
+      setInstallStatus(`Godot ${version}`);
+    } catch (e) {
+      setInstallStatus(`Error: ${e}`);
+    }
+  };
+
   const installGodotMcp = async () => {
     setGodotMcp((prev) => ({ ...prev, status: "installing" }));
     try {
-      await invoke<string>("install_godot_mcp");
+      await runInstall("install_godot_mcp");
       await invoke("setup_godot_mcp_config");
       setGodotMcp((prev) => ({ ...prev, status: "installed" }));
     } catch (e) {
@@ -418,11 +464,12 @@ export function Settings({ onClose }: { onClose: () => void }) {
                   </div>
                   {godot.status !== "installed" && (
                     <div className="mt-6 space-y-3">
-                      <Input placeholder="Absolute binary path..." value={godotPath} onChange={(e) => setGodotPath(e.target.value)} className="h-10 bg-zinc-950 border-zinc-900 focus-visible:border-zinc-800 focus-visible:ring-0 rounded-lg font-mono text-[10px] text-zinc-400 placeholder:text-zinc-800 transition-all" />
+                      <Input placeholder="Absolute binary path..." value={godotPath} onChange={(e) => setGodotPath(e.target.value)} onKeyDown={(e) => e.key === "Enter" && godotPath.trim() && applyGodotPath()} className="h-10 bg-zinc-950 border-zinc-900 focus-visible:border-zinc-800 focus-visible:ring-0 rounded-lg font-mono text-[10px] text-zinc-400 placeholder:text-zinc-800 transition-all" />
                       <Button variant="outline" size="sm" className="h-10 w-full rounded-lg border-zinc-800 bg-transparent hover:bg-zinc-900 text-zinc-400 font-bold uppercase tracking-widest text-[9px]" onClick={installGodot} disabled={godot.status === "installing"}>
                         {godot.status === "installing" ? <Loader2 className="w-3 h-3 mr-2 animate-spin" /> : <Download className="w-3 h-3 mr-2" />}
                         Autonomous Link
                       </Button>
+                      {installStatus && <p className="text-[10px] font-mono text-zinc-600 truncate">{installStatus}</p>}
                     </div>
                   )}
                 </div>
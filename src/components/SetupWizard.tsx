@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { Button } from "@/components/ui/button";
 import { Loader2, Download, CheckCircle, AlertCircle, ExternalLink } from "lucide-react";
 
@@ -41,13 +42,20 @@ export function SetupWizard({ onComplete }: { onComplete: () => void }) {
     setError(null);
     setDownloadProgress(0);
     
+    // Progress covers the download, then restarts for extraction
+    const unlisten = await listen<{ asset: string; percent: number }>("download-progress", (event) => {
+      if (event.payload.asset === "export_templates") {
+        setDownloadProgress(event.payload.percent);
+      }
+    });
     try {
-      // Start download with progress updates
       const result = await invoke<string>("ensure_export_templates");
       console.log("Download result:", result);
       await checkSetup();
     } catch (e) {
       setError(String(e));
+    } finally {
+      unlisten();
     }
     setDownloading(false);
   };
@@ -118,7 +126,7 @@ export function SetupWizard({ onComplete }: { onComplete: () => void }) {
               {templatesOk ? (
                 <div className="text-[9px] font-mono text-zinc-600">Assets Verified</div>
               ) : downloading ? (
-                <div className="text-[9px] font-mono text-zinc-500 animate-pulse">Syncing... (~1GB)</div>
+                <div className="text-[9px] font-mono text-zinc-500 animate-pulse">Syncing... {downloadProgress}% (~1GB)</div>
               ) : (
                 <div className="text-[9px] font-mono text-zinc-800 uppercase">Awaiting Installation</div>
               )}
@@ -12,13 +12,17 @@ import {
   Grid3X3,
   Puzzle,
   Sparkles,
+  LayoutList,
+  Layers,
+  Users,
   ChevronLeft,
   Loader2,
+  Check,
 } from "lucide-react";
 import { cn } from "@/lib/utils";
 
 type GameDimension = "2d" | "3d";
-type GameTemplate = "platformer" | "top-down" | "first-person" | "third-person" | "puzzle" | "empty";
+type GameTemplate = "platformer" | "platformer-tilemap" | "top-down" | "first-person" | "third-person" | "multiplayer" | "ui-menu" | "puzzle" | "empty";
 
 interface TemplateOption {
   id: GameTemplate;
@@ -26,13 +30,18 @@ interface TemplateOption {
   description: string;
   icon: React.ReactNode;
   dimensions: GameDimension[];
+  /** Has a touch controls variant (mobile option) */
+  mobile?: boolean;
 }
 
 const TEMPLATES: TemplateOption[] = [
-  { id: "platformer", name: "Platformer", description: "Side-scrolling jump & run", icon: <Move className="w-5 h-5" />, dimensions: ["2d"] },
-  { id: "top-down", name: "Top-Down", description: "Bird's eye view action/RPG", icon: <Grid3X3 className="w-5 h-5" />, dimensions: ["2d"] },
+  { id: "platformer", name: "Platformer", description: "Side-scrolling jump & run", icon: <Move className="w-5 h-5" />, dimensions: ["2d"], mobile: true },
+  { id: "platformer-tilemap", name: "Platformer (Tilemap)", description: "Jump & run with a ready-made tile level", icon: <Layers className="w-5 h-5" />, dimensions: ["2d"], mobile: true },
+  { id: "top-down", name: "Top-Down", description: "Bird's eye view action/RPG", icon: <Grid3X3 className="w-5 h-5" />, dimensions: ["2d"], mobile: true },
   { id: "first-person", name: "First Person", description: "FPS / exploration", icon: <Eye className="w-5 h-5" />, dimensions: ["3d"] },
   { id: "third-person", name: "Third Person", description: "Over-shoulder camera", icon: <User className="w-5 h-5" />, dimensions: ["3d"] },
+  { id: "multiplayer", name: "Multiplayer", description: "Host/join lobby with replicated players", icon: <Users className="w-5 h-5" />, dimensions: ["3d"] },
+  { id: "ui-menu", name: "Menu / UI", description: "Main menu with Start, Options & Quit", icon: <LayoutList className="w-5 h-5" />, dimensions: ["2d"] },
   { id: "puzzle", name: "Puzzle", description: "Logic & matching games", icon: <Puzzle className="w-5 h-5" />, dimensions: ["2d", "3d"] },
   { id: "empty", name: "Empty Project", description: "Start from scratch", icon: <Sparkles className="w-5 h-5" />, dimensions: ["2d", "3d"] },
 ];
@@ -42,7 +51,7 @@ interface TemplateSelectorProps {
   existingPath?: string;
   existingName?: string;
   /** Called when template selection is complete */
-  onComplete: (config: { template: GameTemplate; dimension: GameDimension; name: string; path: string }) => void;
+  onComplete: (config: { template: GameTemplate; dimension: GameDimension; name: string; path: string; mobile: boolean }) => void;
   /** Called when user wants to go back/cancel */
   onCancel?: () => void;
   /** Show loading state */
@@ -75,6 +84,7 @@ export function TemplateSelector({
   const [template, setTemplate] = useState<GameTemplate | null>(null);
   const [projectName, setProjectName] = useState(existingName || "");
   const [projectPath, setProjectPath] = useState("");
+  const [mobile, setMobile] = useState(false);
 
   const filteredTemplates = TEMPLATES.filter(t => !dimension || t.dimensions.includes(dimension));
   const totalSteps = needsLocation ? 3 : 2;
@@ -98,13 +108,13 @@ export function TemplateSelector({
       setStep("location");
     } else {
       // Use existing path - complete immediately
-      onComplete({ template: t, dimension: dimension!, name: existingName || "", path: existingPath! });
+      onComplete({ template: t, dimension: dimension!, name: existingName || "", path: existingPath!, mobile });
     }
   };
 
   const handleCreate = () => {
     if (!template || !dimension || !projectName || !projectPath) return;
-    onComplete({ template, dimension, name: projectName, path: projectPath });
+    onComplete({ template, dimension, name: projectName, path: projectPath, mobile });
   };
 
   const goBack = () => {
@@ -203,6 +213,23 @@ export function TemplateSelector({
             </label>
           )}
 
+          {/* Mobile option: mobile renderer + on-screen touch controls */}
+          {filteredTemplates.some((t) => t.mobile) && (
+            <label className="flex items-center gap-3 text-[10px] font-bold uppercase tracking-widest text-zinc-700 cursor-pointer hover:text-zinc-400 justify-center transition-colors">
+              <div className="relative">
+                <input
+                  type="checkbox"
+                  checked={mobile}
+                  onChange={(e) => setMobile(e.target.checked)}
+                  className="peer sr-only"
+                />
+                <div className="w-4 h-4 border border-zinc-800 rounded bg-zinc-950 peer-checked:bg-zinc-200 peer-checked:border-zinc-200 transition-all" />
+                <Check className="absolute inset-0 w-4 h-4 text-black opacity-0 peer-checked:opacity-100 transition-opacity" />
+              </div>
+              Mobile Touch Controls
+            </label>
+          )}
+
           {/* Loading state for direct creation (no location step) */}
           {!needsLocation && loading && (
             <div className="flex flex-col items-center gap-4 animate-in fade-in duration-500">
@@ -254,6 +281,12 @@ export function TemplateSelector({
               <span>Blueprint</span>
               <span className="text-zinc-200">{TEMPLATES.find((t) => t.id === template)?.name}</span>
             </div>
+            {mobile && TEMPLATES.find((t) => t.id === template)?.mobile && (
+              <div className="flex justify-between mb-1">
+                <span>Input</span>
+                <span className="text-zinc-200">Touch</span>
+              </div>
+            )}
             {projectPath && projectName && (
               <div className="mt-3 text-[9px] font-mono text-zinc-800 lowercase truncate tracking-tight">{projectPath}/{projectName}</div>
             )}
@@ -65,7 +65,7 @@ export function Viewfinder() {
     };
   }, [previewUrl, projectPath, autoRebuild, isExporting]);
 
-  const initializeProject = async (config: { template: string; dimension: string }) => {
+  const initializeProject = async (config: { template: string; dimension: string; mobile: boolean }) => {
     if (!projectPath) return;
     setCreating(true);
     setDownloadProgress(null);
@@ -77,6 +77,7 @@ export function Viewfinder() {
         projectPath,
         dimension: config.dimension,
         template: config.template,
+        mobile: config.mobile,
       });
       
       // Download Quaternius character for 3D projects
@@ -125,23 +126,43 @@ export function Viewfinder() {
     setPendingChanges(false);
     
     try {
+      // Pre-flight: surface concrete problems instead of a late export failure
+      const { issues } = await invoke<{ issues: { severity: string; message: string; file?: string }[] }>(
+        "validate_project", { projectPath }
+      );
+      for (const issue of issues) {
+        addConsoleOutput(`${issue.severity === "error" ? "Error" : "Warning"}: ${issue.message}${issue.file ? ` (${issue.file})` : ""}`);
+      }
+      const errors = issues.filter((issue) => issue.severity === "error");
+      if (errors.length > 0) {
+        throw new Error(errors.map((issue) => issue.message).join("; "));
+      }
+
       // Export the project (templates are checked at app startup)
       console.log("[runPreview] Starting export...");
       setBuildStatus("building", "Exporting...");
       addConsoleOutput("Exporting to HTML5...");
-      let exportResult = await invoke<string>("export_project_web", { projectPath, force: forceRebuild });
+      const exportResult = await invoke<{
+        exportDir: string;
+        cached: boolean;
+        size: { total: number; largestFiles: { path: string; size: number }[]; threshold: number; overThreshold: boolean };
+      }>("export_project_web", { projectPath, force: forceRebuild });
       console.log("[runPreview] Export result:", exportResult);
       
-      // Check if cached
-      const isCached = exportResult.startsWith("CACHED:");
-      if (isCached) {
-        exportResult = exportResult.replace("CACHED:", "");
+      if (exportResult.cached) {
         addConsoleOutput("Using cached build (no changes detected)");
       } else {
-        addConsoleOutput(`Exported to: ${exportResult}`);
+        addConsoleOutput(`Exported to: ${exportResult.exportDir}`);
+      }
+      
+      const toMb = (bytes: number) => (bytes / (1024 * 1024)).toFixed(1);
+      const { size } = exportResult;
+      addConsoleOutput(`Build size: ${toMb(size.total)} MB (${size.largestFiles.slice(0, 2).map((f) => `${f.path} ${toMb(f.size)} MB`).join(", ")})`);
+      if (size.overThreshold) {
+        addConsoleOutput(`Warning: build is over ${toMb(size.threshold)} MB and may be slow to load or rejected by some hosts`);
       }
       
-      const exportPath = exportResult;
+      const exportPath = exportResult.exportDir;
       
       // Start the preview server
       console.log("[runPreview] Starting preview server for:", exportPath);
@@ -37,6 +37,8 @@ export interface Message {
   blocks: MessageBlock[];
   timestamp: number;
   isStreaming?: boolean;
+  /** Agent turn that produced this message (for cancel_agent_message / undo_agent_turn) */
+  turnId?: string;
 }
 
 export interface Thread {
@@ -51,15 +53,20 @@ export type ChatMode = "agent" | "plan" | "play";
 export interface NodeCaptureOptions {
   angles?: string[]; // "front", "back", "left", "right", "front_right", etc.
   top?: boolean;
-  distance?: number;
+  distance?: number; // fixed camera distance; omit to fit each angle to the node's bounds
+  margin?: number; // extra framing around the bounds when fitting, default 0.1
   height?: number;
   custom?: { yaw: number; pitch: number; distance?: number };
+  resolution?: number | [number, number]; // capture size in pixels, default 512
+  add_light?: boolean; // add a temporary light that follows the capture camera
+  to_disk?: boolean; // save PNGs under user_screenshots/captures and return paths (native only)
 }
 
 export interface NodeCaptureResult {
   node: string;
   bounds: { center: { x: number; y: number; z: number }; size: { x: number; y: number; z: number }; found_mesh: boolean };
-  captures: Record<string, string>; // angle -> base64 image
+  captures: Record<string, string>; // angle -> base64 image, or PNG path when mode is "disk"
+  mode?: "base64" | "disk";
 }
 
 interface AgentEvent {
@@ -67,6 +74,8 @@ interface AgentEvent {
   content: string;
   toolName: string | null;
   toolArgs: string | null;
+  turnId?: string;
+  cancelled?: boolean;
 }
 
 interface Store {
@@ -86,6 +95,8 @@ interface Store {
   threads: Thread[];
   activeThreadId: string | null;
   isLoading: boolean;
+  /** Agent turn in progress, if any */
+  currentTurnId: string | null;
   currentInput: string;
   chatMode: ChatMode;
 
@@ -176,6 +187,7 @@ export const useStore = create<Store>((set, get) => ({
   threads: [{ id: initialThreadId, name: "Thread 1", messages: [], createdAt: Date.now() }],
   activeThreadId: initialThreadId,
   isLoading: false,
+  currentTurnId: null,
   currentInput: "",
   chatMode: "agent",
   consoleOutput: [],
@@ -608,10 +620,22 @@ export const useStore = create<Store>((set, get) => ({
         const thread = threads.find(t => t.id === threadId);
         const hasHistory = thread?.messages.some((m: Message) => m.role === "assistant" && m.blocks.length > 0) ?? false;
         
+        // The turn id comes from here so the UI can cancel or undo this turn
+        const turnId = crypto.randomUUID();
+        set((s) => ({
+          currentTurnId: turnId,
+          threads: s.threads.map(t =>
+            t.id === threadId
+              ? { ...t, messages: t.messages.map(m => m.id === assistantMessage.id ? { ...m, turnId } : m) }
+              : t
+          ),
+        }));
         response = await invoke<string>("send_agent_message", {
           message: messageToSend,
           projectPath,
           continueSession: hasHistory,
+          threadId,
+          turnId,
         });
         
         // Refresh files in case agent modified them
@@ -630,7 +654,7 @@ export const useStore = create<Store>((set, get) => ({
               )}
             : t
         );
-      set({ threads: newThreads, isLoading: false });
+      set({ threads: newThreads, isLoading: false, currentTurnId: null });
       saveThreadsToDisk(newThreads);
     } catch (e) {
       console.error("Agent communication failed:", e);
@@ -643,13 +667,19 @@ export const useStore = create<Store>((set, get) => ({
               )}
             : t
         );
-      set({ threads: newThreads, isLoading: false });
+      set({ threads: newThreads, isLoading: false, currentTurnId: null });
       saveThreadsToDisk(newThreads);
     }
   },
 
   stopGeneration: () => {
-    set({ isLoading: false });
+    const { currentTurnId } = get();
+    if (currentTurnId) {
+      invoke("cancel_agent_message", { turnId: currentTurnId }).catch((e) =>
+        console.error("Failed to cancel agent turn:", e)
+      );
+    }
+    set({ isLoading: false, currentTurnId: null });
   },
 
   createThread: () => {
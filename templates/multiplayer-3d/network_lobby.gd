@@ -0,0 +1,83 @@
+extends Node3D
+## Multiplayer Lobby - Hosts or joins an ENet session and spawns one player per peer
+## The server adds players under $Players; MultiplayerSpawner replicates them to clients.
+## Connection events are re-emitted on EventBus so other systems never touch `multiplayer`.
+## ENet is unavailable in Web exports - test by running two desktop instances.
+
+const PORT := 7777
+const MAX_PLAYERS := 8
+const PLAYER_SCENE := preload("res://scenes/player.tscn")
+
+@onready var players: Node3D = $Players
+@onready var lobby: Control = $UI/Lobby
+@onready var address_edit: LineEdit = $UI/Lobby/AddressEdit
+@onready var status_label: Label = $UI/Lobby/StatusLabel
+
+func _ready() -> void:
+	$UI/Lobby/HostButton.pressed.connect(host)
+	$UI/Lobby/JoinButton.pressed.connect(func(): join(address_edit.text))
+
+	multiplayer.peer_connected.connect(_on_peer_connected)
+	multiplayer.peer_disconnected.connect(_on_peer_disconnected)
+	multiplayer.connected_to_server.connect(_on_connected_to_server)
+	multiplayer.connection_failed.connect(_on_connection_failed)
+	multiplayer.server_disconnected.connect(_on_server_disconnected)
+	print("Multiplayer ready! Host in one instance, Join %s in another" % address_edit.text)
+
+func host() -> void:
+	var peer := ENetMultiplayerPeer.new()
+	var err := peer.create_server(PORT, MAX_PLAYERS)
+	if err != OK:
+		status_label.text = "Could not host on port %d (error %d)" % [PORT, err]
+		return
+	multiplayer.multiplayer_peer = peer
+	lobby.hide()
+	_add_player(multiplayer.get_unique_id())
+	EventBus.game_started.emit()
+
+func join(address: String) -> void:
+	if address.is_empty():
+		address = "127.0.0.1"
+	var peer := ENetMultiplayerPeer.new()
+	var err := peer.create_client(address, PORT)
+	if err != OK:
+		status_label.text = "Could not connect to %s (error %d)" % [address, err]
+		return
+	multiplayer.multiplayer_peer = peer
+	status_label.text = "Connecting to %s..." % address
+
+func _add_player(peer_id: int) -> void:
+	var player := PLAYER_SCENE.instantiate()
+	player.name = str(peer_id)
+	player.position = Vector3(randf_range(-4.0, 4.0), 1.0, randf_range(-4.0, 4.0))
+	players.add_child(player, true)
+
+func _on_peer_connected(peer_id: int) -> void:
+	if multiplayer.is_server():
+		_add_player(peer_id)
+	EventBus.peer_connected.emit(peer_id)
+
+func _on_peer_disconnected(peer_id: int) -> void:
+	if multiplayer.is_server():
+		var player := players.get_node_or_null(str(peer_id))
+		if player:
+			player.queue_free()
+	EventBus.peer_disconnected.emit(peer_id)
+
+func _on_connected_to_server() -> void:
+	lobby.hide()
+	EventBus.connected_to_server.emit()
+	EventBus.game_started.emit()
+
+func _on_connection_failed() -> void:
+	multiplayer.multiplayer_peer = null
+	status_label.text = "Connection failed"
+	EventBus.connection_failed.emit()
+
+func _on_server_disconnected() -> void:
+	multiplayer.multiplayer_peer = null
+	for player in players.get_children():
+		player.queue_free()
+	lobby.show()
+	status_label.text = "Disconnected from host"
+	EventBus.server_disconnected.emit()
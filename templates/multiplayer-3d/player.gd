@@ -0,0 +1,58 @@
+extends CharacterBody3D
+class_name Player
+## Networked 3D Player - Uses Entity-Component Pattern
+## Each player is named after its peer id; only that peer moves it, and
+## MultiplayerSynchronizer replicates the result to everyone else
+
+@export var speed: float = 5.0
+@export var jump_velocity: float = 4.5
+
+var gravity: float = ProjectSettings.get_setting("physics/3d/default_gravity")
+@onready var camera: Camera3D = $Camera3D
+@onready var health_comp: HealthComponent = $HealthComponent
+
+func _enter_tree() -> void:
+	# Set before _ready so the synchronizer knows which peer owns this player
+	set_multiplayer_authority(name.to_int())
+
+func _ready() -> void:
+	camera.current = is_multiplayer_authority()
+	if health_comp:
+		health_comp.died.connect(_on_died)
+		health_comp.health_changed.connect(_on_health_changed)
+	if is_multiplayer_authority():
+		EventBus.player_spawned.emit(self)
+		print("Player %s ready! Use WASD/Arrows + Space to jump" % name)
+
+func _physics_process(delta: float) -> void:
+	if not is_multiplayer_authority():
+		return
+	if not is_on_floor():
+		velocity.y -= gravity * delta
+
+	if Input.is_action_just_pressed("jump") and is_on_floor():
+		velocity.y = jump_velocity
+
+	var input_dir := Input.get_vector("move_left", "move_right", "move_up", "move_down")
+	var direction := Vector3(input_dir.x, 0, input_dir.y).normalized()
+	if direction:
+		velocity.x = direction.x * speed
+		velocity.z = direction.z * speed
+	else:
+		velocity.x = move_toward(velocity.x, 0, speed)
+		velocity.z = move_toward(velocity.z, 0, speed)
+
+	move_and_slide()
+
+func take_damage(amount: int) -> void:
+	if health_comp:
+		health_comp.take_damage(amount)
+
+func _on_health_changed(current: int, maximum: int) -> void:
+	if is_multiplayer_authority():
+		EventBus.health_changed.emit(current, maximum)
+
+func _on_died() -> void:
+	if is_multiplayer_authority():
+		EventBus.player_died.emit()
+	print("Player %s died!" % name)
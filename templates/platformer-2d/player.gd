@@ -38,4 +38,5 @@ func _on_health_changed(current: int, maximum: int) -> void:
 
 func _on_died() -> void:
 	EventBus.player_died.emit()
+	# Add death animation/respawn logic here
 	print("Player died!")
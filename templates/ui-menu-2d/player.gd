@@ -0,0 +1,62 @@
+extends Control
+class_name MainMenu
+## 2D Menu - Control-based main menu that drives game flow through EventBus
+## Buttons only emit signals, so other systems can react to Start/Options/Quit too
+
+## Scene loaded when Start is pressed (leave empty to stay on the menu)
+@export_file("*.tscn") var game_scene: String = ""
+
+@onready var menu: VBoxContainer = $Menu
+@onready var start_button: Button = $Menu/StartButton
+@onready var options_button: Button = $Menu/OptionsButton
+@onready var quit_button: Button = $Menu/QuitButton
+@onready var options_panel: PanelContainer = $OptionsPanel
+@onready var volume_slider: HSlider = $OptionsPanel/Options/VolumeSlider
+@onready var fullscreen_check: CheckButton = $OptionsPanel/Options/FullscreenCheck
+@onready var back_button: Button = $OptionsPanel/Options/BackButton
+
+func _ready() -> void:
+	start_button.pressed.connect(func(): EventBus.game_started.emit())
+	options_button.pressed.connect(func(): EventBus.options_requested.emit())
+	quit_button.pressed.connect(func(): EventBus.quit_requested.emit())
+	back_button.pressed.connect(_close_options)
+	volume_slider.value_changed.connect(_on_volume_changed)
+	fullscreen_check.toggled.connect(_on_fullscreen_toggled)
+
+	EventBus.game_started.connect(_on_game_started)
+	EventBus.options_requested.connect(_open_options)
+	EventBus.quit_requested.connect(_on_quit_requested)
+
+	start_button.grab_focus()
+	print("Menu ready! Arrows/Enter to navigate, Esc closes Options")
+
+func _unhandled_input(event: InputEvent) -> void:
+	if options_panel.visible and event.is_action_pressed("ui_cancel"):
+		_close_options()
+		get_viewport().set_input_as_handled()
+
+func _on_game_started() -> void:
+	if game_scene.is_empty():
+		print("Start pressed - set game_scene on Main to load your game")
+		return
+	get_tree().change_scene_to_file(game_scene)
+
+func _open_options() -> void:
+	menu.hide()
+	options_panel.show()
+	volume_slider.grab_focus()
+
+func _close_options() -> void:
+	options_panel.hide()
+	menu.show()
+	options_button.grab_focus()
+
+func _on_quit_requested() -> void:
+	get_tree().quit()
+
+func _on_volume_changed(value: float) -> void:
+	AudioServer.set_bus_volume_db(0, linear_to_db(value))
+
+func _on_fullscreen_toggled(enabled: bool) -> void:
+	var mode := DisplayServer.WINDOW_MODE_FULLSCREEN if enabled else DisplayServer.WINDOW_MODE_WINDOWED
+	DisplayServer.window_set_mode(mode)